@@ -4,8 +4,12 @@ use std::fs::create_dir_all;
 use cosmwasm_schema::{export_schema, remove_schemas, schema_for};
 
 use tea::hub::{
-    TeaResponse, AllTeaResponse, ConfigResponse, ExecuteMsg, InstantiateMsg, KeyResponse,
-    KeysResponse, OwnerResponse, OwnersResponse, QueryMsg,
+    TeaResponse, AllSeriesResponse, AllTeaResponse, CanMintResponse, ClaimedByResponse,
+    CollectionResponse, ConfigResponse, DepositResponse, EstimateFeeResponse, ExecuteMsg,
+    FeeMultipliersResponse, FlaggedTeaResponse, HooksResponse, InstantiateMsg, IssuerResponse,
+    IssuersResponse, KeyResponse, KeysResponse, LeaderboardResponse, MetadataHistoryResponse,
+    MigrateMsg, OwnerResponse, OwnersResponse, PendingUpgradesResponse, PreviousNftsResponse,
+    QueryMsg, ScoreResponse, SeriesResponse, StatsResponse, TeaStatsResponse,
 };
 
 fn main() {
@@ -17,12 +21,32 @@ fn main() {
     export_schema(&schema_for!(InstantiateMsg), &out_dir);
     export_schema(&schema_for!(ExecuteMsg), &out_dir);
     export_schema(&schema_for!(QueryMsg), &out_dir);
+    export_schema(&schema_for!(MigrateMsg), &out_dir);
 
     export_schema(&schema_for!(ConfigResponse), &out_dir);
     export_schema(&schema_for!(TeaResponse), &out_dir);
     export_schema(&schema_for!(AllTeaResponse), &out_dir);
+    export_schema(&schema_for!(SeriesResponse), &out_dir);
+    export_schema(&schema_for!(AllSeriesResponse), &out_dir);
     export_schema(&schema_for!(KeyResponse), &out_dir);
     export_schema(&schema_for!(KeysResponse), &out_dir);
     export_schema(&schema_for!(OwnerResponse), &out_dir);
     export_schema(&schema_for!(OwnersResponse), &out_dir);
+    export_schema(&schema_for!(ClaimedByResponse), &out_dir);
+    export_schema(&schema_for!(CanMintResponse), &out_dir);
+    export_schema(&schema_for!(StatsResponse), &out_dir);
+    export_schema(&schema_for!(TeaStatsResponse), &out_dir);
+    export_schema(&schema_for!(DepositResponse), &out_dir);
+    export_schema(&schema_for!(EstimateFeeResponse), &out_dir);
+    export_schema(&schema_for!(FeeMultipliersResponse), &out_dir);
+    export_schema(&schema_for!(IssuerResponse), &out_dir);
+    export_schema(&schema_for!(FlaggedTeaResponse), &out_dir);
+    export_schema(&schema_for!(MetadataHistoryResponse), &out_dir);
+    export_schema(&schema_for!(IssuersResponse), &out_dir);
+    export_schema(&schema_for!(PendingUpgradesResponse), &out_dir);
+    export_schema(&schema_for!(HooksResponse), &out_dir);
+    export_schema(&schema_for!(ScoreResponse), &out_dir);
+    export_schema(&schema_for!(LeaderboardResponse), &out_dir);
+    export_schema(&schema_for!(PreviousNftsResponse), &out_dir);
+    export_schema(&schema_for!(CollectionResponse), &out_dir);
 }
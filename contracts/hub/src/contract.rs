@@ -1,14 +1,17 @@
 use cosmwasm_std::{
-    entry_point, to_json_binary, Binary, Deps, DepsMut, Empty, Env, MessageInfo, StdResult,
+    entry_point, to_json_binary, Binary, Deps, DepsMut, Env, MessageInfo, Reply, StdResult,
 };
 use terp_sdk::Response;
 
 use tea::{
-    hub::{ExecuteMsg, InstantiateMsg, QueryMsg, SudoMsg},
-    Tea,
+    hub::{ExecuteMsg, InstantiateMsg, MigrateMsg, QueryMsg, SudoMsg},
+    Series, Tea, Weight,
 };
 
-use crate::{error::ContractError, execute, query, upgrades};
+use crate::{
+    error::ContractError, execute, helpers::NFT_REPLY_ID, hooks::HOOK_REPLY_ID, query,
+    state::FEE_RATE, upgrades::{self, v0_2::BACKFILL},
+};
 
 pub const CONTRACT_NAME: &str = "crates.io:tea-hub";
 pub const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
@@ -16,12 +19,12 @@ pub const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
 #[entry_point]
 pub fn instantiate(
     deps: DepsMut,
-    _env: Env,
+    env: Env,
     info: MessageInfo,
     msg: InstantiateMsg,
 ) -> StdResult<Response> {
     cw2::set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
-    execute::init(deps, info.sender, msg.fee_rate)
+    execute::init(deps, env, info.sender, msg.fee_rate, msg.nft)
 }
 
 #[entry_point]
@@ -30,6 +33,31 @@ pub fn sudo(deps: DepsMut, _env: Env, msg: SudoMsg) -> StdResult<Response> {
         SudoMsg::SetFeeRate {
             fee_rate,
         } => execute::set_fee_rate(deps, fee_rate),
+        SudoMsg::SetDenomFeeRate {
+            denom,
+            fee_rate,
+        } => execute::set_denom_fee_rate(deps, denom, fee_rate),
+        SudoMsg::SetBountyRate {
+            bounty_rate,
+        } => execute::set_bounty_rate(deps, bounty_rate),
+        SudoMsg::SetFeeMultiplier {
+            address,
+            multiplier,
+        } => execute::set_fee_multiplier(deps, address, multiplier),
+        SudoMsg::SetIssuerVerified {
+            address,
+            verified,
+        } => execute::set_issuer_verified(deps, address, verified),
+        SudoMsg::FlagTea {
+            id,
+            reason,
+        } => execute::flag_tea(deps, id, reason),
+        SudoMsg::UnflagTea {
+            id,
+        } => execute::unflag_tea(deps, id),
+        SudoMsg::SetNftUpdateDelay {
+            delay,
+        } => execute::set_nft_update_delay(deps, delay),
     }
 }
 
@@ -40,6 +68,11 @@ pub fn execute(
     info: MessageInfo,
     msg: ExecuteMsg,
 ) -> Result<Response, ContractError> {
+    // the state is incomplete until the backfill of a migration is done
+    if BACKFILL.exists(deps.storage) && !matches!(msg, ExecuteMsg::Backfill { .. }) {
+        return Err(ContractError::BackfillPending);
+    }
+
     match msg {
         ExecuteMsg::CreateTea {
             manager,
@@ -48,6 +81,8 @@ pub fn execute(
             rule,
             expiry,
             max_supply,
+            reveal,
+            nft,
         } => {
             let tea = Tea {
                 manager: deps.api.addr_validate(&manager)?,
@@ -57,13 +92,49 @@ pub fn execute(
                 expiry,
                 max_supply,
                 current_supply: 0,
+                reveal,
+                nft: nft.map(|nft| deps.api.addr_validate(&nft)).transpose()?,
             };
             execute::create_tea(deps, env, info, tea)
         },
         ExecuteMsg::EditTea {
             id,
             metadata,
-        } => execute::edit_tea(deps, info, id, metadata),
+            retain_previous,
+        } => execute::edit_tea(deps, info, id, metadata, retain_previous),
+        ExecuteMsg::Reveal {
+            id,
+            metadata,
+        } => execute::reveal(deps, env, info, id, metadata),
+        ExecuteMsg::CreateSeries {
+            manager,
+            metadata,
+        } => {
+            let series = Series {
+                manager: deps.api.addr_validate(&manager)?,
+                metadata,
+                tea: vec![],
+            };
+            execute::create_series(deps, info, series)
+        },
+        ExecuteMsg::AddToSeries {
+            id,
+            tea,
+        } => execute::add_to_series(deps, info, id, tea),
+        ExecuteMsg::LockMetadata {
+            id,
+        } => execute::lock_metadata(deps, info, id),
+        ExecuteMsg::SetWeight {
+            id,
+            weight,
+            category,
+        } => {
+            let weight = Weight {
+                weight,
+                category,
+            };
+            execute::set_weight(deps, info, id, weight)
+        },
         ExecuteMsg::AddKeys {
             id,
             keys,
@@ -71,11 +142,14 @@ pub fn execute(
         ExecuteMsg::PurgeKeys {
             id,
             limit,
-        } => execute::purge_keys(deps, env, id, limit),
+        } => execute::purge_keys(deps, env, info, id, limit),
         ExecuteMsg::PurgeOwners {
             id,
             limit,
-        } => execute::purge_owners(deps, env, id, limit),
+        } => execute::purge_owners(deps, env, info, id, limit),
+        ExecuteMsg::PurgeExpired {
+            limit,
+        } => execute::purge_expired(deps, env, info, limit),
         ExecuteMsg::MintByMinter {
             id,
             owners,
@@ -91,23 +165,103 @@ pub fn execute(
             pubkey,
             signature,
         } => execute::mint_by_keys(deps, env, id, owner, pubkey, signature),
+        ExecuteMsg::MintByBurn {
+            id,
+            token_ids,
+        } => execute::mint_by_burn(deps, env, id, token_ids, info.sender),
         ExecuteMsg::SetNft {
             nft,
         } => execute::set_nft(deps, info.sender, &nft),
+        ExecuteMsg::UpdateNft {
+            nft,
+        } => execute::update_nft(deps, env, info.sender, &nft),
+        ExecuteMsg::ApplyNftUpdate {} => execute::apply_nft_update(deps, env),
+        ExecuteMsg::CancelNftUpdate {} => execute::cancel_nft_update(deps, info.sender),
+        ExecuteMsg::RegisterCollection {
+            nft,
+        } => execute::register_collection(deps, env, info.sender, &nft),
+        ExecuteMsg::SetFeeMultiplier {
+            address,
+            multiplier,
+        } => execute::set_fee_multiplier_by_developer(deps, info.sender, address, multiplier),
+        ExecuteMsg::SetIssuerVerified {
+            address,
+            verified,
+        } => execute::set_issuer_verified_by_developer(deps, info.sender, address, verified),
+        ExecuteMsg::FlagTea {
+            id,
+            reason,
+        } => execute::flag_tea_by_developer(deps, info.sender, id, reason),
+        ExecuteMsg::UnflagTea {
+            id,
+        } => execute::unflag_tea_by_developer(deps, info.sender, id),
+        ExecuteMsg::AddHook {
+            contract,
+            id,
+            fatal,
+        } => execute::add_hook(deps, info.sender, contract, id, fatal),
+        ExecuteMsg::RemoveHook {
+            contract,
+            id,
+        } => execute::remove_hook(deps, info.sender, contract, id),
+        ExecuteMsg::RecordBurn {
+            id,
+            serial,
+            owner,
+        } => execute::record_burn(deps, info.sender, id, serial, owner),
+        ExecuteMsg::RecordTransfer {
+            id,
+            serial,
+            from,
+            to,
+        } => execute::record_transfer(deps, info.sender, id, serial, from, to),
+        ExecuteMsg::Backfill {
+            limit,
+        } => execute::backfill(deps, limit),
+    }
+}
+
+#[entry_point]
+pub fn reply(deps: DepsMut, _env: Env, reply: Reply) -> Result<Response, ContractError> {
+    match reply.id {
+        HOOK_REPLY_ID => execute::hook_failed(reply.result),
+        NFT_REPLY_ID => execute::nft_instantiated(deps, reply),
+        id => Err(ContractError::InvalidReplyId(id)),
     }
 }
 
 #[entry_point]
-pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
+pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
     match msg {
         QueryMsg::Config {} => to_json_binary(&query::config(deps)?),
         QueryMsg::Tea {
             id,
         } => to_json_binary(&query::tea(deps, id)?),
         QueryMsg::AllTea {
+            status,
             start_after,
             limit,
-        } => to_json_binary(&query::all_tea(deps, start_after, limit)?),
+            order,
+        } => to_json_binary(&query::all_tea(deps, env, status, start_after, limit, order)?),
+        QueryMsg::TeaByManager {
+            manager,
+            start_after,
+            limit,
+        } => to_json_binary(&query::tea_by_manager(deps, manager, start_after, limit)?),
+        QueryMsg::TeaByRule {
+            rule,
+            start_after,
+            limit,
+        } => to_json_binary(&query::tea_by_rule(deps, rule, start_after, limit)?),
+        QueryMsg::Score {
+            user,
+            category,
+        } => to_json_binary(&query::score(deps, user, category)?),
+        QueryMsg::Leaderboard {
+            category,
+            start_after,
+            limit,
+        } => to_json_binary(&query::leaderboard(deps, category, start_after, limit)?),
         QueryMsg::Key {
             id,
             pubkey,
@@ -126,11 +280,75 @@ pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
             start_after,
             limit,
         } => to_json_binary(&query::owners(deps, id, start_after, limit)?),
+        QueryMsg::ClaimedBy {
+            user,
+            start_after,
+            limit,
+        } => to_json_binary(&query::claimed_by(deps, user, start_after, limit)?),
+        QueryMsg::CanMint {
+            id,
+            owner,
+            pubkey,
+            signature,
+        } => to_json_binary(&query::can_mint(deps, env, id, owner, pubkey, signature)?),
+        QueryMsg::Stats {} => to_json_binary(&query::stats(deps)?),
+        QueryMsg::ActiveTeaCount {
+            start_after,
+            limit,
+        } => to_json_binary(&query::active_tea_count(deps, env, start_after, limit)?),
+        QueryMsg::TeaStats {
+            id,
+        } => to_json_binary(&query::tea_stats(deps, id)?),
+        QueryMsg::Deposit {
+            id,
+        } => to_json_binary(&query::deposit(deps, id)?),
+        QueryMsg::EstimateFee {
+            action,
+            payer,
+        } => to_json_binary(&query::estimate_fee(deps, action, payer)?),
+        QueryMsg::FeeMultipliers {
+            start_after,
+            limit,
+        } => to_json_binary(&query::fee_multipliers(deps, start_after, limit)?),
+        QueryMsg::Issuer {
+            address,
+        } => to_json_binary(&query::issuer(deps, address)?),
+        QueryMsg::VerifiedIssuers {
+            start_after,
+            limit,
+        } => to_json_binary(&query::verified_issuers(deps, start_after, limit)?),
+        QueryMsg::Series {
+            id,
+        } => to_json_binary(&query::series(deps, id)?),
+        QueryMsg::AllSeries {
+            start_after,
+            limit,
+        } => to_json_binary(&query::all_series(deps, start_after, limit)?),
+        QueryMsg::FlaggedTea {
+            start_after,
+            limit,
+        } => to_json_binary(&query::flagged_tea(deps, start_after, limit)?),
+        QueryMsg::MetadataHistory {
+            id,
+            start_after,
+            limit,
+        } => to_json_binary(&query::metadata_history(deps, id, start_after, limit)?),
+        QueryMsg::Hooks {
+            id,
+        } => to_json_binary(&query::hooks(deps, id)?),
+        QueryMsg::PreviousNfts {
+            start_after,
+            limit,
+        } => to_json_binary(&query::previous_nfts(deps, start_after, limit)?),
+        QueryMsg::Collection {
+            nft,
+        } => to_json_binary(&query::collection(deps, nft)?),
+        QueryMsg::PendingUpgrades {} => to_json_binary(&query::pending_upgrades(deps)?),
     }
 }
 
 #[entry_point]
-pub fn migrate(deps: DepsMut, _env: Env, _msg: Empty) -> Result<Response, ContractError> {
+pub fn migrate(mut deps: DepsMut, _env: Env, msg: MigrateMsg) -> Result<Response, ContractError> {
     let cw2::ContractVersion {
         contract,
         version,
@@ -140,11 +358,26 @@ pub fn migrate(deps: DepsMut, _env: Env, _msg: Empty) -> Result<Response, Contra
         return Err(ContractError::incorrect_contract_name(CONTRACT_NAME, contract));
     }
 
-    // in the previous v1.1 update, we forgot to set the contract version to `1.1.0`
-    // so for now it's still `1.0.0`
-    if version != "1.0.0" {
-        return Err(ContractError::incorrect_contract_version("1.0.0", version));
+    // apply the state upgrades in order, then record the version of the current code
+    let upgrades = upgrades::pending(&version, CONTRACT_VERSION)?;
+    for upgrade in &upgrades {
+        (upgrade.migrate)(deps.branch())?;
+    }
+
+    cw2::set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+
+    let mut res = Response::new()
+        .add_attribute("action", "tea/hub/migrate")
+        .add_attribute("from_version", version)
+        .add_attribute("to_version", CONTRACT_VERSION)
+        .add_attribute("upgrades", upgrades.len().to_string());
+
+    if let Some(fee_rate) = msg.fee_rate {
+        FEE_RATE.save(deps.storage, &fee_rate)?;
+        res = res
+            .add_attribute("metadata_fee_rate", fee_rate.metadata.to_string())
+            .add_attribute("key_fee_rate", fee_rate.key.to_string());
     }
 
-    upgrades::v1_2::migrate(deps).map_err(ContractError::from)
-}
\ No newline at end of file
+    Ok(res)
+}
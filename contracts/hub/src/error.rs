@@ -19,7 +19,7 @@ pub enum ContractError {
     #[error(transparent)]
     FromHex(#[from] hex::FromHexError),
 
-    #[error("invalid reply id {0}; must be 1")]
+    #[error("invalid reply id {0}")]
     InvalidReplyId(u64),
 
     #[error("not a valid secp256k1 public key")]
@@ -37,9 +37,37 @@ pub enum ContractError {
     #[error("unauthorized: sender is not tea manager")]
     NotManager,
 
+    #[error("unauthorized: sender is not series manager")]
+    NotSeriesManager,
+
     #[error("unauthorized: sender is not tea minter")]
     NotMinter,
 
+    #[error("unauthorized: sender is not the nft contract")]
+    NotNft,
+
+    #[error("nft contract {nft} is or has been in use")]
+    NftAlreadyUsed {
+        nft: String,
+    },
+
+    #[error("no nft contract update is pending")]
+    NoPendingNftUpdate,
+
+    #[error("nft contract update cannot be applied before {time}")]
+    NftUpdateTooEarly {
+        time: u64,
+    },
+
+    #[error("collection {nft} cannot be registered: {reason}")]
+    InvalidCollection {
+        nft: String,
+        reason: String,
+    },
+
+    #[error("unauthorized: sender has not registered the collection")]
+    NotCollectionOrganizer,
+
     #[error("expecting the tea to be unavailable but it is available")]
     Available,
 
@@ -49,6 +77,72 @@ pub enum ContractError {
     #[error("tea max supply has been been exceeded")]
     SoldOut,
 
+    #[error("tea {id} has been flagged: {reason}")]
+    Flagged {
+        id: u64,
+        reason: String,
+    },
+
+    #[error("metadata of tea {id} has been locked")]
+    MetadataLocked {
+        id: u64,
+    },
+
+    #[error("metadata of tea {id} is pending a reveal")]
+    RevealPending {
+        id: u64,
+    },
+
+    #[error("tea {id} has no pending reveal")]
+    NoPendingReveal {
+        id: u64,
+    },
+
+    #[error("metadata of tea {id} cannot be revealed before {time}")]
+    RevealTooEarly {
+        id: u64,
+        time: u64,
+    },
+
+    #[error("metadata does not match the committed hash: expected {expected}, found {found}")]
+    RevealHashMismatch {
+        expected: String,
+        found: String,
+    },
+
+    #[error("metadata must be left empty until revealed; use the reveal's placeholder instead")]
+    MetadataBeforeReveal,
+
+    #[error("invalid url in `{field}`: {reason}")]
+    InvalidUrl {
+        field: String,
+        reason: String,
+    },
+
+    #[error("url scheme `{scheme}` in `{field}` is not allowed, expecting ipfs|https|ar")]
+    DisallowedUrlScheme {
+        field: String,
+        scheme: String,
+    },
+
+    #[error("`{field}` is too long: {len} exceeds the maximum of {max}")]
+    MetadataTooLong {
+        field: String,
+        len: usize,
+        max: usize,
+    },
+
+    #[error("trait type `{trait_type}` is reserved")]
+    ReservedTrait {
+        trait_type: String,
+    },
+
+    #[error("tea {id} already belongs to series {series}")]
+    AlreadyInSeries {
+        id: u64,
+        series: u64,
+    },
+
     #[error("key {key} already exists for tea {id}")]
     KeyExists {
         id: u64,
@@ -77,6 +171,51 @@ pub enum ContractError {
         found: String,
     },
 
+    #[error("invalid burn inputs: {reason}")]
+    InvalidBurnInputs {
+        reason: String,
+    },
+
+    #[error("wrong number of instances of tea {id} to burn: expected {expected}, found {found}")]
+    WrongBurnInputs {
+        id: u64,
+        expected: u64,
+        found: u64,
+    },
+
+    #[error("token {token_id} is not owned by {user}")]
+    NotTokenOwner {
+        token_id: String,
+        user: String,
+    },
+
+    #[error("weight of tea {id} cannot be changed once an instance has been minted")]
+    WeightLocked {
+        id: u64,
+    },
+
+    #[error("weight {weight} exceeds the maximum of {max}")]
+    WeightTooHigh {
+        weight: u64,
+        max: u64,
+    },
+
+    #[error("invalid category `{category}`: must be 1 to {max} lowercase letters, digits or `_`")]
+    InvalidCategory {
+        category: String,
+        max: usize,
+    },
+
+    #[error("cannot register more than {max} hooks")]
+    TooManyHooks {
+        max: usize,
+    },
+
+    #[error("hook {contract} is not registered")]
+    HookNotFound {
+        contract: String,
+    },
+
     #[error("incorrect contract name: expecting {expect}, found {found}")]
     IncorrectContractName {
         expect: String,
@@ -88,9 +227,174 @@ pub enum ContractError {
         expect: String,
         found: String,
     },
+
+    #[error("invalid version `{version}`: {reason}")]
+    InvalidVersion {
+        version: String,
+        reason: String,
+    },
+
+    #[error("the state is being backfilled after a migration; call `backfill` until it completes")]
+    BackfillPending,
+
+    #[error("no backfill is pending")]
+    NoPendingBackfill,
 }
 
 impl ContractError {
+    /// A machine-readable code identifying the kind of error
+    pub fn code(&self) -> &'static str {
+        match self {
+            ContractError::Std(_) => "std",
+            ContractError::Verification(_) => "verification",
+            ContractError::ParseReply(_) => "parse_reply",
+            ContractError::Fee(_) => "fee",
+            ContractError::FromHex(_) => "from_hex",
+            ContractError::InvalidReplyId(_) => "invalid_reply_id",
+            ContractError::InvalidPubkey => "invalid_pubkey",
+            ContractError::InvalidSignature => "invalid_signature",
+            ContractError::DoubleInit => "double_init",
+            ContractError::NotDeveloper => "not_developer",
+            ContractError::NotManager => "not_manager",
+            ContractError::NotSeriesManager => "not_series_manager",
+            ContractError::NotMinter => "not_minter",
+            ContractError::NotNft => "not_nft",
+            ContractError::NftAlreadyUsed {
+                ..
+            } => "nft_already_used",
+            ContractError::NoPendingNftUpdate => "no_pending_nft_update",
+            ContractError::NftUpdateTooEarly {
+                ..
+            } => "nft_update_too_early",
+            ContractError::InvalidCollection {
+                ..
+            } => "invalid_collection",
+            ContractError::NotCollectionOrganizer => "not_collection_organizer",
+            ContractError::Available => "available",
+            ContractError::Expired => "expired",
+            ContractError::SoldOut => "sold_out",
+            ContractError::Flagged {
+                ..
+            } => "flagged",
+            ContractError::MetadataLocked {
+                ..
+            } => "metadata_locked",
+            ContractError::RevealPending {
+                ..
+            } => "reveal_pending",
+            ContractError::NoPendingReveal {
+                ..
+            } => "no_pending_reveal",
+            ContractError::RevealTooEarly {
+                ..
+            } => "reveal_too_early",
+            ContractError::RevealHashMismatch {
+                ..
+            } => "reveal_hash_mismatch",
+            ContractError::MetadataBeforeReveal => "metadata_before_reveal",
+            ContractError::InvalidUrl {
+                ..
+            } => "invalid_url",
+            ContractError::DisallowedUrlScheme {
+                ..
+            } => "disallowed_url_scheme",
+            ContractError::MetadataTooLong {
+                ..
+            } => "metadata_too_long",
+            ContractError::ReservedTrait {
+                ..
+            } => "reserved_trait",
+            ContractError::AlreadyInSeries {
+                ..
+            } => "already_in_series",
+            ContractError::KeyExists {
+                ..
+            } => "key_exists",
+            ContractError::KeyDoesNotExist {
+                ..
+            } => "key_does_not_exist",
+            ContractError::AlreadyClaimed {
+                ..
+            } => "already_claimed",
+            ContractError::UnknownMintRule {
+                ..
+            } => "unknown_mint_rule",
+            ContractError::WrongMintRule {
+                ..
+            } => "wrong_mint_rule",
+            ContractError::InvalidBurnInputs {
+                ..
+            } => "invalid_burn_inputs",
+            ContractError::WrongBurnInputs {
+                ..
+            } => "wrong_burn_inputs",
+            ContractError::NotTokenOwner {
+                ..
+            } => "not_token_owner",
+            ContractError::WeightLocked {
+                ..
+            } => "weight_locked",
+            ContractError::WeightTooHigh {
+                ..
+            } => "weight_too_high",
+            ContractError::InvalidCategory {
+                ..
+            } => "invalid_category",
+            ContractError::TooManyHooks {
+                ..
+            } => "too_many_hooks",
+            ContractError::HookNotFound {
+                ..
+            } => "hook_not_found",
+            ContractError::IncorrectContractName {
+                ..
+            } => "incorrect_contract_name",
+            ContractError::IncorrectContractVersion {
+                ..
+            } => "incorrect_contract_version",
+            ContractError::InvalidVersion {
+                ..
+            } => "invalid_version",
+            ContractError::BackfillPending => "backfill_pending",
+            ContractError::NoPendingBackfill => "no_pending_backfill",
+        }
+    }
+
+    pub fn flagged(id: u64, reason: impl Into<String>) -> Self {
+        ContractError::Flagged {
+            id,
+            reason: reason.into(),
+        }
+    }
+
+    pub fn invalid_url(field: impl Into<String>, reason: impl ToString) -> Self {
+        ContractError::InvalidUrl {
+            field: field.into(),
+            reason: reason.to_string(),
+        }
+    }
+
+    pub fn disallowed_url_scheme(field: impl Into<String>, scheme: impl Into<String>) -> Self {
+        ContractError::DisallowedUrlScheme {
+            field: field.into(),
+            scheme: scheme.into(),
+        }
+    }
+
+    pub fn metadata_too_long(field: impl Into<String>, len: usize, max: usize) -> Self {
+        ContractError::MetadataTooLong {
+            field: field.into(),
+            len,
+            max,
+        }
+    }
+
+    pub fn reserved_trait(trait_type: impl Into<String>) -> Self {
+        ContractError::ReservedTrait {
+            trait_type: trait_type.into(),
+        }
+    }
+
     pub fn key_exists(id: u64, key: impl Into<String>) -> Self {
         ContractError::KeyExists {
             id,
@@ -124,6 +428,46 @@ impl ContractError {
         }
     }
 
+    pub fn invalid_burn_inputs(reason: impl Into<String>) -> Self {
+        ContractError::InvalidBurnInputs {
+            reason: reason.into(),
+        }
+    }
+
+    pub fn invalid_collection(nft: impl Into<String>, reason: impl Into<String>) -> Self {
+        ContractError::InvalidCollection {
+            nft: nft.into(),
+            reason: reason.into(),
+        }
+    }
+
+    pub fn not_token_owner(token_id: impl Into<String>, user: impl Into<String>) -> Self {
+        ContractError::NotTokenOwner {
+            token_id: token_id.into(),
+            user: user.into(),
+        }
+    }
+
+    pub fn weight_too_high(weight: u64, max: u64) -> Self {
+        ContractError::WeightTooHigh {
+            weight,
+            max,
+        }
+    }
+
+    pub fn invalid_category(category: impl Into<String>, max: usize) -> Self {
+        ContractError::InvalidCategory {
+            category: category.into(),
+            max,
+        }
+    }
+
+    pub fn hook_not_found(contract: impl Into<String>) -> Self {
+        ContractError::HookNotFound {
+            contract: contract.into(),
+        }
+    }
+
     pub fn incorrect_contract_name(expect: impl Into<String>, found: impl Into<String>) -> Self {
         ContractError::IncorrectContractName {
             expect: expect.into(),
@@ -137,4 +481,11 @@ impl ContractError {
             found: found.into(),
         }
     }
+
+    pub fn invalid_version(version: impl Into<String>, reason: impl ToString) -> Self {
+        ContractError::InvalidVersion {
+            version: version.into(),
+            reason: reason.to_string(),
+        }
+    }
 }
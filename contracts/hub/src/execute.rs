@@ -1,29 +1,89 @@
 use std::collections::BTreeSet;
 
-use cosmwasm_std::{to_json_binary, Addr, DepsMut, Empty, Env, MessageInfo, StdResult, WasmMsg};
+use cosmwasm_std::{
+    to_json_binary, Addr, BankMsg, Decimal, DepsMut, Empty, Env, MessageInfo, Order, Reply,
+    StdError, StdResult, Storage, SubMsgResult, Uint128, WasmMsg,
+};
+use cw_utils::parse_reply_instantiate_data;
 // use cw721_base::msg::ExecuteMsg::Mint;
 use terp_metadata::Metadata;
-use terp_sdk::Response;
+use terp_sdk::{Response, SubMsg, NATIVE_FEE_DENOM};
 
-use tea::{Tea, FeeRate, MintRule};
+use tea::{
+    hook::TeaHookMsg,
+    hub::{InstantiateNft, PendingNft},
+    Tea, BountyRate, FeeRate, MetadataVersion, MintRule,
+    Series, Weight,
+};
 
 use crate::{
     error::ContractError,
-    fee::handle_fee,
+    fee::{
+        bounty_msg, bytes_diff, handle_fee, handle_key_fee, handle_series_fee, refund_keys,
+        release_blob, release_purged_keys, stored_bytes, take_bounty,
+    },
     helpers::*,
+    hooks::{hook_msgs, hooks, MAX_HOOKS},
+    metadata::{bytes_added, bytes_replaced, load_tea, record_bytes, save_tea},
     query,
+    scores,
     state::*,
+    stats,
+    upgrades,
 };
 
-pub fn init(deps: DepsMut, developer: Addr, fee_rate: FeeRate) -> StdResult<Response> {
+pub fn init(
+    deps: DepsMut,
+    env: Env,
+    developer: Addr,
+    fee_rate: FeeRate,
+    nft: Option<InstantiateNft>,
+) -> StdResult<Response> {
     DEVELOPER.save(deps.storage, &developer)?;
     TEA_COUNT.save(deps.storage, &0)?;
     FEE_RATE.save(deps.storage, &fee_rate)?;
 
+    // the NFT contract's address is recorded once it has been instantiated, in the reply
+    let msgs = nft
+        .map(|nft| -> StdResult<_> {
+            let msg = WasmMsg::Instantiate {
+                admin: Some(developer.to_string()),
+                code_id: nft.code_id,
+                msg: to_json_binary(&tea::nft::InstantiateMsg {
+                    hub: env.contract.address.to_string(),
+                    api_url: nft.api_url,
+                    collection_info: nft.collection_info,
+                    name: None,
+                    symbol: None,
+                })?,
+                funds: vec![],
+                label: "tea-nft".to_string(),
+            };
+            Ok(SubMsg::reply_on_success(msg, NFT_REPLY_ID))
+        })
+        .transpose()?;
+
     Ok(Response::new()
+        .add_submessages(msgs)
         .add_attribute("action", "tea/hub/init"))
 }
 
+/// Handle the reply to the Hub's instantiation of the NFT contract, recording its address
+pub fn nft_instantiated(deps: DepsMut, reply: Reply) -> Result<Response, ContractError> {
+    let res = parse_reply_instantiate_data(reply)?;
+    let nft_addr = deps.api.addr_validate(&res.contract_address)?;
+
+    if NFT.may_load(deps.storage)?.is_some() {
+        return Err(ContractError::DoubleInit);
+    }
+
+    NFT.save(deps.storage, &nft_addr)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "tea/hub/nft_instantiated")
+        .add_attribute("nft", nft_addr))
+}
+
 pub fn set_nft(deps: DepsMut, sender_addr: Addr, nft: &str) -> Result<Response, ContractError> {
     let developer_addr = DEVELOPER.load(deps.storage)?;
 
@@ -44,6 +104,265 @@ pub fn set_nft(deps: DepsMut, sender_addr: Addr, nft: &str) -> Result<Response,
         .add_attribute("nft", nft))
 }
 
+pub fn update_nft(
+    deps: DepsMut,
+    env: Env,
+    sender_addr: Addr,
+    nft: &str,
+) -> Result<Response, ContractError> {
+    assert_developer(deps.storage, &sender_addr)?;
+
+    let nft_addr = deps.api.addr_validate(nft)?;
+
+    assert_nft_unused(deps.storage, &nft_addr)?;
+
+    let now = env.block.time.seconds();
+    let delay = NFT_UPDATE_DELAY.may_load(deps.storage)?.unwrap_or(0);
+
+    let res = Response::new()
+        .add_attribute("action", "tea/hub/update_nft")
+        .add_attribute("nft", nft)
+        .add_attribute("time", (now + delay).to_string());
+
+    if delay == 0 {
+        PENDING_NFT.remove(deps.storage);
+        let previous_nft = replace_nft(deps.storage, now, nft_addr)?;
+        return Ok(res.add_attribute("previous_nft", previous_nft));
+    }
+
+    PENDING_NFT.save(
+        deps.storage,
+        &PendingNft {
+            nft: nft_addr,
+            time: now + delay,
+        },
+    )?;
+
+    Ok(res)
+}
+
+pub fn apply_nft_update(deps: DepsMut, env: Env) -> Result<Response, ContractError> {
+    let pending = PENDING_NFT.may_load(deps.storage)?.ok_or(ContractError::NoPendingNftUpdate)?;
+
+    let now = env.block.time.seconds();
+    if now < pending.time {
+        return Err(ContractError::NftUpdateTooEarly {
+            time: pending.time,
+        });
+    }
+
+    PENDING_NFT.remove(deps.storage);
+    let previous_nft = replace_nft(deps.storage, now, pending.nft.clone())?;
+
+    Ok(Response::new()
+        .add_attribute("action", "tea/hub/apply_nft_update")
+        .add_attribute("nft", pending.nft)
+        .add_attribute("previous_nft", previous_nft))
+}
+
+pub fn cancel_nft_update(deps: DepsMut, sender_addr: Addr) -> Result<Response, ContractError> {
+    assert_developer(deps.storage, &sender_addr)?;
+
+    let pending = PENDING_NFT.may_load(deps.storage)?.ok_or(ContractError::NoPendingNftUpdate)?;
+
+    PENDING_NFT.remove(deps.storage);
+
+    Ok(Response::new()
+        .add_attribute("action", "tea/hub/cancel_nft_update")
+        .add_attribute("nft", pending.nft))
+}
+
+pub fn register_collection(
+    deps: DepsMut,
+    env: Env,
+    sender_addr: Addr,
+    nft: &str,
+) -> Result<Response, ContractError> {
+    let nft_addr = deps.api.addr_validate(nft)?;
+
+    assert_nft_unused(deps.storage, &nft_addr)?;
+    assert_valid_collection(deps.as_ref(), &env.contract.address, &nft_addr, &sender_addr)?;
+
+    COLLECTIONS.save(deps.storage, &nft_addr, &sender_addr)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "tea/hub/register_collection")
+        .add_attribute("nft", nft)
+        .add_attribute("organizer", sender_addr))
+}
+
+pub fn set_fee_multiplier_by_developer(
+    deps: DepsMut,
+    sender_addr: Addr,
+    address: String,
+    multiplier: Option<Decimal>,
+) -> Result<Response, ContractError> {
+    assert_developer(deps.storage, &sender_addr)?;
+    Ok(set_fee_multiplier(deps, address, multiplier)?)
+}
+
+pub fn set_issuer_verified_by_developer(
+    deps: DepsMut,
+    sender_addr: Addr,
+    address: String,
+    verified: bool,
+) -> Result<Response, ContractError> {
+    assert_developer(deps.storage, &sender_addr)?;
+    Ok(set_issuer_verified(deps, address, verified)?)
+}
+
+pub fn flag_tea_by_developer(
+    deps: DepsMut,
+    sender_addr: Addr,
+    id: u64,
+    reason: String,
+) -> Result<Response, ContractError> {
+    assert_developer(deps.storage, &sender_addr)?;
+    Ok(flag_tea(deps, id, reason)?)
+}
+
+pub fn unflag_tea_by_developer(
+    deps: DepsMut,
+    sender_addr: Addr,
+    id: u64,
+) -> Result<Response, ContractError> {
+    assert_developer(deps.storage, &sender_addr)?;
+    Ok(unflag_tea(deps, id)?)
+}
+
+pub fn record_burn(
+    deps: DepsMut,
+    sender_addr: Addr,
+    id: u64,
+    serial: u64,
+    owner: String,
+) -> Result<Response, ContractError> {
+    assert_nft(deps.storage, &sender_addr, id)?;
+
+    stats::record_burn(deps.storage, id)?;
+    scores::record_loss(deps.storage, id, &owner)?;
+
+    let hooks = hook_msgs(
+        deps.storage,
+        id,
+        &TeaHookMsg::Burned {
+            id,
+            serial,
+            owner: owner.clone(),
+        },
+    )?;
+
+    Ok(Response::new()
+        .add_submessages(hooks)
+        .add_attribute("action", "tea/hub/record_burn")
+        .add_attribute("id", id.to_string())
+        .add_attribute("serial", serial.to_string())
+        .add_attribute("owner", owner))
+}
+
+pub fn record_transfer(
+    deps: DepsMut,
+    sender_addr: Addr,
+    id: u64,
+    serial: u64,
+    from: String,
+    to: String,
+) -> Result<Response, ContractError> {
+    assert_nft(deps.storage, &sender_addr, id)?;
+
+    scores::record_loss(deps.storage, id, &from)?;
+    scores::record_gain(deps.storage, id, &to)?;
+
+    let hooks = hook_msgs(
+        deps.storage,
+        id,
+        &TeaHookMsg::Transferred {
+            id,
+            serial,
+            from: from.clone(),
+            to: to.clone(),
+        },
+    )?;
+
+    Ok(Response::new()
+        .add_submessages(hooks)
+        .add_attribute("action", "tea/hub/record_transfer")
+        .add_attribute("id", id.to_string())
+        .add_attribute("serial", serial.to_string())
+        .add_attribute("from", from)
+        .add_attribute("to", to))
+}
+
+pub fn add_hook(
+    deps: DepsMut,
+    sender_addr: Addr,
+    contract: String,
+    id: Option<u64>,
+    fatal: bool,
+) -> Result<Response, ContractError> {
+    assert_can_manage_hooks(deps.storage, &sender_addr, id)?;
+
+    let contract_addr = deps.api.addr_validate(&contract)?;
+
+    // registering an existing hook again only updates whether it is fatal
+    let registered = hooks(deps.storage, id)?;
+    if registered.len() >= MAX_HOOKS && registered.iter().all(|(addr, _)| *addr != contract_addr) {
+        return Err(ContractError::TooManyHooks {
+            max: MAX_HOOKS,
+        });
+    }
+
+    match id {
+        Some(id) => TEA_HOOKS.save(deps.storage, (id, &contract_addr), &fatal)?,
+        None => GLOBAL_HOOKS.save(deps.storage, &contract_addr, &fatal)?,
+    }
+
+    Ok(Response::new()
+        .add_attribute("action", "tea/hub/add_hook")
+        .add_attribute("id", stringify_option(id))
+        .add_attribute("contract", contract)
+        .add_attribute("fatal", fatal.to_string()))
+}
+
+pub fn remove_hook(
+    deps: DepsMut,
+    sender_addr: Addr,
+    contract: String,
+    id: Option<u64>,
+) -> Result<Response, ContractError> {
+    assert_can_manage_hooks(deps.storage, &sender_addr, id)?;
+
+    let contract_addr = deps.api.addr_validate(&contract)?;
+
+    let registered = match id {
+        Some(id) => TEA_HOOKS.has(deps.storage, (id, &contract_addr)),
+        None => GLOBAL_HOOKS.has(deps.storage, &contract_addr),
+    };
+    if !registered {
+        return Err(ContractError::hook_not_found(contract));
+    }
+
+    match id {
+        Some(id) => TEA_HOOKS.remove(deps.storage, (id, &contract_addr)),
+        None => GLOBAL_HOOKS.remove(deps.storage, &contract_addr),
+    }
+
+    Ok(Response::new()
+        .add_attribute("action", "tea/hub/remove_hook")
+        .add_attribute("id", stringify_option(id))
+        .add_attribute("contract", contract))
+}
+
+/// Handle the reply to a hook whose failure is ignored. The hook's state changes have already
+/// been reverted, so the error is only reported.
+pub fn hook_failed(result: SubMsgResult) -> Result<Response, ContractError> {
+    let error = result.into_result().err().unwrap_or_default();
+
+    Ok(Response::new()
+        .add_attribute("action", "tea/hub/hook_failed")
+        .add_attribute("error", error))
+}
+
 pub fn set_fee_rate(deps: DepsMut, fee_rate: FeeRate) -> StdResult<Response> {
     FEE_RATE.save(deps.storage, &fee_rate)?;
 
@@ -53,6 +372,122 @@ pub fn set_fee_rate(deps: DepsMut, fee_rate: FeeRate) -> StdResult<Response> {
         .add_attribute("key_fee_rate", fee_rate.key.to_string()))
 }
 
+pub fn set_denom_fee_rate(
+    deps: DepsMut,
+    denom: String,
+    fee_rate: Option<FeeRate>,
+) -> StdResult<Response> {
+    // the uthiol fee rate is always accepted, and set with `set_fee_rate`
+    if denom == NATIVE_FEE_DENOM {
+        return Err(StdError::generic_err("use `set_fee_rate` to set the fee rate in uthiol"));
+    }
+
+    let mut res = Response::new()
+        .add_attribute("action", "tea/hub/set_denom_fee_rate")
+        .add_attribute("denom", &denom);
+
+    match fee_rate {
+        Some(fee_rate) => {
+            DENOM_FEE_RATES.save(deps.storage, &denom, &fee_rate)?;
+            res = res
+                .add_attribute("metadata_fee_rate", fee_rate.metadata.to_string())
+                .add_attribute("key_fee_rate", fee_rate.key.to_string());
+        },
+        None => {
+            DENOM_FEE_RATES.remove(deps.storage, &denom);
+        },
+    }
+
+    Ok(res)
+}
+
+pub fn set_fee_multiplier(
+    deps: DepsMut,
+    address: String,
+    multiplier: Option<Decimal>,
+) -> StdResult<Response> {
+    let addr = deps.api.addr_validate(&address)?;
+
+    let mut res = Response::new()
+        .add_attribute("action", "tea/hub/set_fee_multiplier")
+        .add_attribute("address", address);
+
+    match multiplier {
+        Some(multiplier) => {
+            if multiplier > Decimal::one() {
+                return Err(StdError::generic_err("fee multiplier must not exceed 1"));
+            }
+            FEE_MULTIPLIERS.save(deps.storage, &addr, &multiplier)?;
+            res = res.add_attribute("multiplier", multiplier.to_string());
+        },
+        None => {
+            FEE_MULTIPLIERS.remove(deps.storage, &addr);
+        },
+    }
+
+    Ok(res)
+}
+
+pub fn set_issuer_verified(
+    deps: DepsMut,
+    address: String,
+    verified: bool,
+) -> StdResult<Response> {
+    let addr = deps.api.addr_validate(&address)?;
+
+    if verified {
+        VERIFIED_ISSUERS.insert(deps.storage, &addr)?;
+    } else {
+        VERIFIED_ISSUERS.remove(deps.storage, &addr)?;
+    }
+
+    Ok(Response::new()
+        .add_attribute("action", "tea/hub/set_issuer_verified")
+        .add_attribute("address", address)
+        .add_attribute("verified", verified.to_string()))
+}
+
+pub fn flag_tea(deps: DepsMut, id: u64, reason: String) -> StdResult<Response> {
+    // the tea must exist
+    ALL_TEA.load(deps.storage, id)?;
+
+    FLAGGED_TEA.save(deps.storage, id, &reason)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "tea/hub/flag_tea")
+        .add_attribute("id", id.to_string())
+        .add_attribute("reason", reason))
+}
+
+pub fn unflag_tea(deps: DepsMut, id: u64) -> StdResult<Response> {
+    FLAGGED_TEA.remove(deps.storage, id);
+
+    Ok(Response::new()
+        .add_attribute("action", "tea/hub/unflag_tea")
+        .add_attribute("id", id.to_string()))
+}
+
+pub fn set_nft_update_delay(deps: DepsMut, delay: u64) -> StdResult<Response> {
+    NFT_UPDATE_DELAY.save(deps.storage, &delay)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "tea/hub/set_nft_update_delay")
+        .add_attribute("delay", delay.to_string()))
+}
+
+pub fn set_bounty_rate(deps: DepsMut, bounty_rate: BountyRate) -> StdResult<Response> {
+    if bounty_rate.pool_share > Decimal::one() {
+        return Err(StdError::generic_err("bounty pool share must not exceed 1"));
+    }
+
+    BOUNTY_RATE.save(deps.storage, &bounty_rate)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "tea/hub/set_bounty_rate")
+        .add_attribute("pool_share", bounty_rate.pool_share.to_string())
+        .add_attribute("per_item", bounty_rate.per_item))
+}
+
 pub fn create_tea(
     deps: DepsMut,
     env: Env,
@@ -62,15 +497,15 @@ pub fn create_tea(
     // the tea must not have already expired or have a max supply of zero
     assert_available(&tea, &env.block, 1)?;
 
-    // ensure the creator has paid a sufficient fee
-    let fee_rate = FEE_RATE.load(deps.storage)?;
-    let res = handle_fee(
-        deps.as_ref().storage,
-        &info,
-        None,
-        Some(&tea),
-        fee_rate.metadata,
-    )?;
+    assert_valid_metadata(&tea.metadata)?;
+
+    // if the metadata is to be revealed later, it must not be given away until then
+    if let Some(reveal) = &tea.reveal {
+        if tea.metadata != Metadata::default() {
+            return Err(ContractError::MetadataBeforeReveal);
+        }
+        assert_valid_metadata(&reveal.placeholder)?;
+    }
 
     // if the tea uses "by key" mint rule, the key must be a valid secp256k1
     // public key
@@ -79,10 +514,39 @@ pub fn create_tea(
         assert_valid_secp256k1_pubkey(&bytes)?;
     }
 
-    let id = TEA_COUNT.update(deps.storage, |id| StdResult::Ok(id + 1))?;
-    ALL_TEA.save(deps.storage, id, &tea)?;
+    // if the tea uses "by burn" mint rule, the tea to be burned must exist
+    if let MintRule::ByBurn {
+        inputs,
+    } = &tea.rule
+    {
+        assert_valid_burn_inputs(deps.storage, inputs)?;
+    }
 
-    Ok(res
+    // if the tea is to be minted on a dedicated collection, the creator must have registered it
+    if let Some(nft) = &tea.nft {
+        if COLLECTIONS.may_load(deps.storage, nft)? != Some(info.sender.clone()) {
+            return Err(ContractError::NotCollectionOrganizer);
+        }
+    }
+
+    let id = TEA_COUNT.load(deps.storage)? + 1;
+
+    // ensure the creator has paid a sufficient deposit. metadata identical to that of an existing
+    // tea is already stored, so it is not charged for again
+    let bytes = record_bytes(&tea)? + bytes_added(deps.storage, &tea.metadata)?;
+    handle_fee(deps.storage, &info, id, &tea.manager, 0, bytes)?;
+
+    TEA_COUNT.save(deps.storage, &id)?;
+    save_tea(deps.storage, id, &tea)?;
+    stats::record_create(deps.storage, id, &tea)?;
+
+    let version = MetadataVersion {
+        hash: metadata_hash(&tea.metadata)?,
+        metadata: None,
+    };
+    METADATA_HISTORY.save(deps.storage, (id, 1), &version)?;
+
+    Ok(Response::new()
         .add_attribute("action", "tea/hub/create_tea")
         .add_attribute("id", id.to_string())
         .add_attribute("fee", stringify_funds(&info.funds)))
@@ -93,32 +557,246 @@ pub fn edit_tea(
     info: MessageInfo,
     id: u64,
     metadata: Metadata,
+    retain_previous: bool,
 ) -> Result<Response, ContractError> {
-    let mut tea = ALL_TEA.load(deps.storage, id)?;
+    let mut tea = load_tea(deps.storage, id)?;
 
     if info.sender != tea.manager {
         return Err(ContractError::NotManager);
     }
 
-    // ensure the manager pays a sufficient fee
-    let fee_rate = FEE_RATE.load(deps.storage)?;
-    let res = handle_fee(
-        deps.as_ref().storage,
-        &info,
-        Some(&tea.metadata),
-        &metadata,
-        fee_rate.metadata,
-    )?;
+    assert_metadata_unlocked(deps.storage, id)?;
+    assert_valid_metadata(&metadata)?;
+
+    if tea.reveal.is_some() {
+        return Err(ContractError::RevealPending {
+            id,
+        });
+    }
+
+    // ensure the manager pays a sufficient deposit if the metadata grows, or refund part of the
+    // deposit if it shrinks. if the previous metadata is retained in the history, it is charged
+    // for as well
+    let retained = retain_previous.then_some(&tea.metadata);
+    let (bytes_freed, bytes_taken) = bytes_replaced(deps.storage, &tea.metadata, &metadata)?;
+    let (bytes_freed, payer_refund) = release_blob(deps.storage, id, &tea.metadata, bytes_freed)?;
+    let bytes_taken = bytes_taken + stored_bytes(retained)?;
+    let refund = handle_fee(deps.storage, &info, id, &tea.manager, bytes_freed, bytes_taken)?;
+
+    let version = record_metadata_version(deps.storage, id, &tea.metadata, &metadata, retained)?;
 
     tea.metadata = metadata;
-    ALL_TEA.save(deps.storage, id, &tea)?;
+    save_tea(deps.storage, id, &tea)?;
 
-    Ok(res
+    Ok(Response::new()
+        .add_messages(payer_refund)
+        .add_messages(refund)
         .add_attribute("action", "tea/hub/edit_tea")
         .add_attribute("id", id.to_string())
+        .add_attribute("fee", stringify_funds(&info.funds))
+        .add_attribute("metadata_version", version.to_string()))
+}
+
+pub fn reveal(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    id: u64,
+    metadata: Metadata,
+) -> Result<Response, ContractError> {
+    let mut tea = load_tea(deps.storage, id)?;
+
+    if info.sender != tea.manager {
+        return Err(ContractError::NotManager);
+    }
+
+    let Some(reveal) = &tea.reveal else {
+        return Err(ContractError::NoPendingReveal {
+            id,
+        });
+    };
+
+    if env.block.time.seconds() < reveal.time {
+        return Err(ContractError::RevealTooEarly {
+            id,
+            time: reveal.time,
+        });
+    }
+
+    let hash = metadata_hash(&metadata)?;
+    if hash != reveal.hash {
+        return Err(ContractError::RevealHashMismatch {
+            expected: reveal.hash.clone(),
+            found: hash,
+        });
+    }
+
+    assert_valid_metadata(&metadata)?;
+
+    // the final metadata replaces the placeholder, which is no longer stored
+    let old_tea = tea.clone();
+    tea.metadata = metadata;
+    tea.reveal = None;
+
+    let (bytes_freed, bytes_taken) =
+        bytes_replaced(deps.storage, &old_tea.metadata, &tea.metadata)?;
+    let (bytes_freed, payer_refund) =
+        release_blob(deps.storage, id, &old_tea.metadata, bytes_freed)?;
+    let old_bytes = record_bytes(&old_tea)? + bytes_freed;
+    let new_bytes = record_bytes(&tea)? + bytes_taken;
+    let refund = handle_fee(deps.storage, &info, id, &tea.manager, old_bytes, new_bytes)?;
+
+    let version =
+        record_metadata_version(deps.storage, id, &old_tea.metadata, &tea.metadata, None)?;
+
+    save_tea(deps.storage, id, &tea)?;
+
+    Ok(Response::new()
+        .add_messages(payer_refund)
+        .add_messages(refund)
+        .add_attribute("action", "tea/hub/reveal")
+        .add_attribute("id", id.to_string())
+        .add_attribute("fee", stringify_funds(&info.funds))
+        .add_attribute("metadata_version", version.to_string()))
+}
+
+pub fn lock_metadata(
+    deps: DepsMut,
+    info: MessageInfo,
+    id: u64,
+) -> Result<Response, ContractError> {
+    let tea = ALL_TEA.load(deps.storage, id)?;
+
+    if info.sender != tea.manager {
+        return Err(ContractError::NotManager);
+    }
+
+    LOCKED_METADATA.insert(deps.storage, id)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "tea/hub/lock_metadata")
+        .add_attribute("id", id.to_string())
+        .add_attribute("metadata_version", metadata_version(deps.storage, id)?.to_string()))
+}
+
+pub fn set_weight(
+    deps: DepsMut,
+    info: MessageInfo,
+    id: u64,
+    weight: Weight,
+) -> Result<Response, ContractError> {
+    let tea = ALL_TEA.load(deps.storage, id)?;
+
+    if info.sender != tea.manager {
+        return Err(ContractError::NotManager);
+    }
+
+    // instances already minted were scored at the previous weight, which must remain the weight
+    // deducted when they are burned or transferred
+    if tea.current_supply > 0 {
+        return Err(ContractError::WeightLocked {
+            id,
+        });
+    }
+
+    if weight.weight > MAX_WEIGHT {
+        return Err(ContractError::weight_too_high(weight.weight, MAX_WEIGHT));
+    }
+
+    assert_valid_category(&weight.category)?;
+
+    TEA_WEIGHTS.save(deps.storage, id, &weight)?;
+
+    // any manager can weight their tea within its category, but only weights set by the developer
+    // or a verified issuer count towards scores across all categories
+    let global = tea.manager == DEVELOPER.load(deps.storage)?
+        || VERIFIED_ISSUERS.contains(deps.storage, &tea.manager);
+    if global {
+        GLOBAL_WEIGHTS.insert(deps.storage, id)?;
+    } else {
+        GLOBAL_WEIGHTS.remove(deps.storage, id)?;
+    }
+
+    Ok(Response::new()
+        .add_attribute("action", "tea/hub/set_weight")
+        .add_attribute("id", id.to_string())
+        .add_attribute("weight", weight.weight.to_string())
+        .add_attribute("category", weight.category)
+        .add_attribute("global", global.to_string()))
+}
+
+pub fn create_series(
+    deps: DepsMut,
+    info: MessageInfo,
+    series: Series,
+) -> Result<Response, ContractError> {
+    assert_valid_metadata(&series.metadata)?;
+
+    let id = SERIES_COUNT.may_load(deps.storage)?.unwrap_or(0) + 1;
+
+    // ensure the creator has paid a sufficient fee
+    let res = handle_series_fee(deps.storage, &info, bytes_diff(None, &series)?)?;
+
+    SERIES_COUNT.save(deps.storage, &id)?;
+    ALL_SERIES.save(deps.storage, id, &series)?;
+
+    Ok(res
+        .add_attribute("action", "tea/hub/create_series")
+        .add_attribute("id", id.to_string())
         .add_attribute("fee", stringify_funds(&info.funds)))
 }
 
+pub fn add_to_series(
+    deps: DepsMut,
+    info: MessageInfo,
+    id: u64,
+    tea: Vec<u64>,
+) -> Result<Response, ContractError> {
+    let mut series = ALL_SERIES.load(deps.storage, id)?;
+
+    if info.sender != series.manager {
+        return Err(ContractError::NotSeriesManager);
+    }
+
+    let old_series = series.clone();
+    for tea_id in tea {
+        // the sender must also manage the tea, so that no one can claim others' tea
+        if info.sender != ALL_TEA.load(deps.storage, tea_id)?.manager {
+            return Err(ContractError::NotManager);
+        }
+
+        // a tea belongs to at most one series, and appears in it only once
+        let current = match TEA_SERIES.may_load(deps.storage, tea_id)? {
+            Some(current) => Some(current),
+            None => series.tea.contains(&tea_id).then_some(id),
+        };
+        if let Some(current) = current {
+            return Err(ContractError::AlreadyInSeries {
+                id: tea_id,
+                series: current,
+            });
+        }
+
+        series.tea.push(tea_id);
+    }
+
+    // ensure the manager pays a sufficient fee for the series growing
+    let res = handle_series_fee(deps.storage, &info, bytes_diff(Some(&old_series), &series)?)?;
+
+    let added = &series.tea[old_series.tea.len()..];
+    for tea_id in added {
+        TEA_SERIES.save(deps.storage, *tea_id, &id)?;
+    }
+
+    ALL_SERIES.save(deps.storage, id, &series)?;
+
+    Ok(res
+        .add_attribute("action", "tea/hub/add_to_series")
+        .add_attribute("id", id.to_string())
+        .add_attribute("fee", stringify_funds(&info.funds))
+        .add_attribute("tea_added", added.len().to_string()))
+}
+
 pub fn add_keys(
     deps: DepsMut,
     env: Env,
@@ -139,15 +817,8 @@ pub fn add_keys(
         rule => return Err(ContractError::wrong_mint_rule("by_keys", rule)),
     }
 
-    // ensure the manager pays a sufficient fee
-    let fee_rate = FEE_RATE.load(deps.storage)?;
-    let res = handle_fee(
-        deps.as_ref().storage,
-        &info,
-        None,
-        &keys,
-        fee_rate.key,
-    )?;
+    // ensure the manager pays a sufficient deposit
+    handle_key_fee(deps.storage, &info, id, &keys)?;
 
     // the minting deadline must not have been reached
     // the max supply must not have been reached
@@ -169,16 +840,25 @@ pub fn add_keys(
         }
     })?;
 
-    Ok(res
+    stats::record_keys_added(deps.storage, id, keys.len() as u64)?;
+
+    Ok(Response::new()
         .add_attribute("action", "tea/hub/add_keys")
         .add_attribute("id", id.to_string())
         .add_attribute("fee", stringify_funds(&info.funds))
         .add_attribute("keys_added", keys.len().to_string()))
 }
 
+/// Default number of storage items removed in one `PurgeExpired` call
+pub const DEFAULT_PURGE_LIMIT: u32 = 100;
+
+/// Maximum number of storage items removed in one `PurgeExpired` call
+pub const MAX_PURGE_LIMIT: u32 = 1000;
+
 pub fn purge_keys(
     deps: DepsMut,
     env: Env,
+    info: MessageInfo,
     id: u64,
     limit: Option<u32>,
 ) -> Result<Response, ContractError> {
@@ -187,22 +867,23 @@ pub fn purge_keys(
     // can only purge keys once the tea becomes unavailable to be minted
     assert_unavailable(&tea, &env.block)?;
 
-    // need to collect the keys into a Vec first before creating a new iterator to delete them
-    // because of how Rust works
-    let res = query::keys(deps.as_ref(), id, None, limit)?;
-    for key in &res.keys {
-        KEYS.remove(deps.storage, (id, key))?;
-    };
+    let limit = limit.unwrap_or(query::DEFAULT_LIMIT).min(query::MAX_LIMIT) as usize;
+    let (keys_purged, bounty, refund) = remove_keys(deps.storage, id, &tea.manager, limit)?;
+    let msg = bounty_msg(&info.sender, bounty);
 
     Ok(Response::new()
+        .add_messages(refund)
+        .add_messages(msg)
         .add_attribute("action", "tea/hub/purge_keys")
         .add_attribute("id", id.to_string())
-        .add_attribute("keys_purged", res.keys.len().to_string()))
+        .add_attribute("keys_purged", keys_purged.to_string())
+        .add_attribute("bounty", bounty))
 }
 
 pub fn purge_owners(
     deps: DepsMut,
     env: Env,
+    info: MessageInfo,
     id: u64,
     limit: Option<u32>,
 ) -> Result<Response, ContractError> {
@@ -211,17 +892,94 @@ pub fn purge_owners(
     // can only purge user data once the tea becomes unavailable to be minted
     assert_unavailable(&tea, &env.block)?;
 
-    // need to collect the user addresses into a Vec first before creating a new iterator to delete
-    // them because of how Rust works
-    let res = query::owners(deps.as_ref(), id, None, limit)?;
-    for owner in &res.owners {
-        OWNERS.remove(deps.storage, (id, owner))?;
-    };
+    let limit = limit.unwrap_or(query::DEFAULT_LIMIT).min(query::MAX_LIMIT) as usize;
+    let owners_purged = remove_owners(deps.storage, id, limit)?;
+    let bounty = take_bounty(deps.storage, id, owners_purged)?;
+    let msg = bounty_msg(&info.sender, bounty);
 
     Ok(Response::new()
+        .add_messages(msg)
         .add_attribute("action", "tea/hub/purge_owners")
         .add_attribute("id", id.to_string())
-        .add_attribute("owners_purged", res.owners.len().to_string()))
+        .add_attribute("owners_purged", owners_purged.to_string())
+        .add_attribute("bounty", bounty))
+}
+
+pub fn purge_expired(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    limit: Option<u32>,
+) -> Result<Response, ContractError> {
+    let tea_count = TEA_COUNT.load(deps.storage)?;
+    let mut budget = limit.unwrap_or(DEFAULT_PURGE_LIMIT).min(MAX_PURGE_LIMIT) as usize;
+    let mut id = PURGE_CURSOR.may_load(deps.storage)?.unwrap_or(1);
+
+    let mut keys_purged = 0;
+    let mut owners_purged = 0;
+    let mut refunds = vec![];
+    let mut bounty = Uint128::zero();
+
+    while budget > 0 && id <= tea_count {
+        let tea = ALL_TEA.load(deps.storage, id)?;
+
+        if assert_unavailable(&tea, &env.block).is_ok() {
+            let (removed, key_bounty, refund) =
+                remove_keys(deps.storage, id, &tea.manager, budget)?;
+            keys_purged += removed;
+            bounty += key_bounty;
+            refunds.extend(refund);
+            budget -= removed as usize;
+
+            let removed = remove_owners(deps.storage, id, budget)?;
+            owners_purged += removed;
+            bounty += take_bounty(deps.storage, id, removed)?;
+            budget -= removed as usize;
+
+            // this tea may have more items left; the next call resumes from it
+            if budget == 0 {
+                break;
+            }
+        }
+
+        // visiting a tea also counts towards the budget, so that the amount of work done in one
+        // call is bounded even if there is nothing to purge
+        budget -= 1;
+        id += 1;
+    }
+
+    // once all tea have been visited, the next call starts a new sweep from the first tea.
+    // tea that were still available in this sweep will be purged in a later one
+    if id > tea_count {
+        id = 1;
+    }
+    PURGE_CURSOR.save(deps.storage, &id)?;
+
+    let msg = bounty_msg(&info.sender, bounty);
+
+    Ok(Response::new()
+        .add_messages(refunds)
+        .add_messages(msg)
+        .add_attribute("action", "tea/hub/purge_expired")
+        .add_attribute("keys_purged", keys_purged.to_string())
+        .add_attribute("owners_purged", owners_purged.to_string())
+        .add_attribute("cursor", id.to_string())
+        .add_attribute("bounty", bounty))
+}
+
+/// Default number of items processed in one `Backfill` call
+pub const DEFAULT_BACKFILL_LIMIT: u32 = 100;
+
+/// Maximum number of items processed in one `Backfill` call
+pub const MAX_BACKFILL_LIMIT: u32 = 1000;
+
+pub fn backfill(deps: DepsMut, limit: Option<u32>) -> Result<Response, ContractError> {
+    let limit = limit.unwrap_or(DEFAULT_BACKFILL_LIMIT).min(MAX_BACKFILL_LIMIT) as usize;
+    let cursor = upgrades::v0_2::backfill(deps.storage, limit)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "tea/hub/backfill")
+        .add_attribute("complete", cursor.is_none().to_string()))
 }
 
 pub fn mint_by_minter(
@@ -231,17 +989,36 @@ pub fn mint_by_minter(
     owners: BTreeSet<String>,
     sender: Addr,
 ) -> Result<Response, ContractError> {
-    let nft_addr = NFT.load(deps.storage)?;
     let mut tea = ALL_TEA.load(deps.storage, id)?;
+    let nft_addr = tea_nft(deps.storage, &tea)?;
 
     let amount = owners.len() as u64;
     let start_serial = tea.current_supply + 1;
 
+    assert_not_flagged(deps.storage, id)?;
     assert_available(&tea, &env.block, amount)?;
     assert_can_mint_by_minter(&tea, &sender)?;
 
     tea.current_supply += amount;
     ALL_TEA.save(deps.storage, id, &tea)?;
+    stats::record_mints(deps.storage, id, &tea, amount)?;
+
+    let mut hooks = vec![];
+    for (idx, owner) in owners.iter().enumerate() {
+        // the minter may mint to the same user more than once, so recipients are not recorded in
+        // `OWNERS`, only in the reverse index
+        CLAIMED.insert(deps.storage, (owner, id))?;
+        scores::record_gain(deps.storage, id, owner)?;
+        hooks.extend(hook_msgs(
+            deps.storage,
+            id,
+            &TeaHookMsg::Minted {
+                id,
+                serial: start_serial + (idx as u64),
+                owner: owner.clone(),
+            },
+        )?);
+    }
 
     let msgs = owners
         .into_iter()
@@ -263,6 +1040,7 @@ pub fn mint_by_minter(
 
     Ok(Response::new()
         .add_messages(msgs)
+        .add_submessages(hooks)
         .add_attribute("action", "tea/hub/mint_by_minter")
         .add_attribute("id", id.to_string())
         .add_attribute("amount", amount.to_string()))
@@ -275,17 +1053,27 @@ pub fn mint_by_key(
     owner: String,
     signature: String,
 ) -> Result<Response, ContractError> {
-    let nft_addr = NFT.load(deps.storage)?;
     let mut tea = ALL_TEA.load(deps.storage, id)?;
+    let nft_addr = tea_nft(deps.storage, &tea)?;
 
-    assert_available(&tea, &env.block, 1)?;
-    assert_eligible(deps.storage, id, &owner)?;
-    assert_can_mint_by_key(deps.api, id, &tea, &owner, &signature)?;
+    assert_can_claim(deps.as_ref(), &env.block, id, &tea, &owner, None, &signature)?;
 
     tea.current_supply += 1;
     ALL_TEA.save(deps.storage, id, &tea)?;
+    stats::record_mints(deps.storage, id, &tea, 1)?;
 
-    OWNERS.insert(deps.storage, (id, &owner))?;
+    record_claim(deps.storage, id, &owner)?;
+    scores::record_gain(deps.storage, id, &owner)?;
+
+    let hooks = hook_msgs(
+        deps.storage,
+        id,
+        &TeaHookMsg::Minted {
+            id,
+            serial: tea.current_supply,
+            owner: owner.clone(),
+        },
+    )?;
 
     Ok(Response::new()
         .add_message(WasmMsg::Execute {
@@ -301,6 +1089,7 @@ pub fn mint_by_key(
             })?,
             funds: vec![],
         })
+        .add_submessages(hooks)
         .add_attribute("action", "tea/hub/mint_by_key")
         .add_attribute("id", id.to_string())
         .add_attribute("serial", tea.current_supply.to_string())
@@ -315,20 +1104,34 @@ pub fn mint_by_keys(
     pubkey: String,
     signature: String,
 ) -> Result<Response, ContractError> {
-    let nft_addr = NFT.load(deps.storage)?;
     let mut tea = ALL_TEA.load(deps.storage, id)?;
+    let nft_addr = tea_nft(deps.storage, &tea)?;
 
-    assert_available(&tea, &env.block, 1)?;
-    assert_eligible(deps.storage, id, &owner)?;
-    assert_can_mint_by_keys(deps.as_ref(), id, &tea, &owner, &pubkey, &signature)?;
+    assert_can_claim(deps.as_ref(), &env.block, id, &tea, &owner, Some(&pubkey), &signature)?;
 
     tea.current_supply += 1;
     ALL_TEA.save(deps.storage, id, &tea)?;
+    stats::record_mints(deps.storage, id, &tea, 1)?;
 
+    // the key is consumed, so part of the key deposit is refunded to the manager
     KEYS.remove(deps.storage, (id, &pubkey))?;
-    OWNERS.insert(deps.storage, (id, &owner))?;
+    let refund = refund_keys(deps.storage, id, &tea.manager, 1)?;
+    stats::record_keys_removed(deps.storage, id, 1, false)?;
+    record_claim(deps.storage, id, &owner)?;
+    scores::record_gain(deps.storage, id, &owner)?;
+
+    let hooks = hook_msgs(
+        deps.storage,
+        id,
+        &TeaHookMsg::Minted {
+            id,
+            serial: tea.current_supply,
+            owner: owner.clone(),
+        },
+    )?;
 
     Ok(Response::new()
+        .add_messages(refund)
         .add_message(WasmMsg::Execute {
             contract_addr: nft_addr.to_string(),
             msg: to_json_binary(&terp721::ExecuteMsg::<_, Empty>::Mint {
@@ -339,8 +1142,177 @@ pub fn mint_by_keys(
             })?,
             funds: vec![],
         })
+        .add_submessages(hooks)
         .add_attribute("action", "tea/hub/mint_by_keys")
         .add_attribute("id", id.to_string())
         .add_attribute("serial", tea.current_supply.to_string())
         .add_attribute("recipient", owner))
 }
+
+pub fn mint_by_burn(
+    deps: DepsMut,
+    env: Env,
+    id: u64,
+    token_ids: BTreeSet<String>,
+    sender: Addr,
+) -> Result<Response, ContractError> {
+    let mut tea = ALL_TEA.load(deps.storage, id)?;
+    let nft_addr = tea_nft(deps.storage, &tea)?;
+    let owner = sender.to_string();
+
+    assert_not_flagged(deps.storage, id)?;
+    assert_available(&tea, &env.block, 1)?;
+    assert_eligible(deps.storage, id, &owner)?;
+    let holders = assert_can_mint_by_burn(deps.as_ref(), &tea, &owner, &token_ids)?;
+
+    tea.current_supply += 1;
+    ALL_TEA.save(deps.storage, id, &tea)?;
+    stats::record_mints(deps.storage, id, &tea, 1)?;
+
+    record_claim(deps.storage, id, &owner)?;
+    scores::record_gain(deps.storage, id, &owner)?;
+
+    // the burns are executed with the Hub's operator approval, on whichever NFT contract holds each
+    // token, and are reported back to the Hub by that contract like any other burn
+    let burn_msgs = token_ids
+        .iter()
+        .zip(holders)
+        .map(|(token_id, holder)| {
+            Ok(WasmMsg::Execute {
+                contract_addr: holder.to_string(),
+                msg: to_json_binary(&terp721::ExecuteMsg::<Empty, Empty>::Burn {
+                    token_id: token_id.clone(),
+                })?,
+                funds: vec![],
+            })
+        })
+        .collect::<StdResult<Vec<_>>>()?;
+
+    let hooks = hook_msgs(
+        deps.storage,
+        id,
+        &TeaHookMsg::Minted {
+            id,
+            serial: tea.current_supply,
+            owner: owner.clone(),
+        },
+    )?;
+
+    Ok(Response::new()
+        .add_messages(burn_msgs)
+        .add_message(WasmMsg::Execute {
+            contract_addr: nft_addr.to_string(),
+            msg: to_json_binary(&terp721::ExecuteMsg::<_, Empty>::Mint {
+                token_id: token_id(id, tea.current_supply),
+                owner: owner.clone(),
+                token_uri: None,
+                extension: None::<Empty>,
+            })?,
+            funds: vec![],
+        })
+        .add_submessages(hooks)
+        .add_attribute("action", "tea/hub/mint_by_burn")
+        .add_attribute("id", id.to_string())
+        .add_attribute("serial", tea.current_supply.to_string())
+        .add_attribute("recipient", owner)
+        .add_attribute("burned", token_ids.len().to_string()))
+}
+
+/// Make `nft` the NFT contract new instances are minted on, recording the current one as replaced
+/// at time `now`. Returns the replaced contract.
+fn replace_nft(store: &mut dyn Storage, now: u64, nft: Addr) -> StdResult<Addr> {
+    let previous_nft = NFT.load(store)?;
+    PREVIOUS_NFTS.save(store, &previous_nft, &now)?;
+    NFT.save(store, &nft)?;
+    Ok(previous_nft)
+}
+
+/// Remove up to `limit` whitelisted keys of a tea, paying the bounty for purging them out of the
+/// corresponding part of the key deposit and refunding the rest to the tea's manager. Returns the
+/// number of keys removed, the bounty, and the refund message, if any.
+fn remove_keys(
+    store: &mut dyn Storage,
+    id: u64,
+    manager: &Addr,
+    limit: usize,
+) -> StdResult<(u64, Uint128, Option<BankMsg>)> {
+    // need to collect the keys into a Vec first before creating a new iterator to delete them
+    // because of how Rust works
+    let keys = KEYS
+        .prefix(id)
+        .keys(store, None, None, Order::Ascending)
+        .take(limit)
+        .collect::<StdResult<Vec<_>>>()?;
+    for key in &keys {
+        KEYS.remove(store, (id, key))?;
+    }
+
+    let (bounty, refund) = release_purged_keys(store, id, manager, keys.len() as u64)?;
+    stats::record_keys_removed(store, id, keys.len() as u64, true)?;
+
+    Ok((keys.len() as u64, bounty, refund))
+}
+
+/// Remove up to `limit` owners of a tea, along with their entries in the reverse index. Returns
+/// the number of owners removed.
+fn remove_owners(store: &mut dyn Storage, id: u64, limit: usize) -> StdResult<u64> {
+    // need to collect the user addresses into a Vec first before creating a new iterator to delete
+    // them because of how Rust works
+    let owners = OWNERS
+        .prefix(id)
+        .keys(store, None, None, Order::Ascending)
+        .take(limit)
+        .collect::<StdResult<Vec<_>>>()?;
+    for owner in &owners {
+        OWNERS.remove(store, (id, owner))?;
+        CLAIMED.remove(store, (owner, id))?;
+    }
+
+    stats::record_owners_purged(store, id, owners.len() as u64)?;
+
+    Ok(owners.len() as u64)
+}
+
+/// Mark a user as having claimed a tea, in both `OWNERS` and its reverse index `CLAIMED`.
+fn record_claim(store: &mut dyn Storage, id: u64, user: &str) -> StdResult<()> {
+    if OWNERS.insert(store, (id, user))? {
+        stats::record_owner_added(store, id)?;
+    }
+    CLAIMED.insert(store, (user, id))?;
+    Ok(())
+}
+
+/// Record `new` as the next version of a tea's metadata, replacing `old`, and return the new
+/// version. If `retained` is provided, it is kept in the history as the full previous version.
+///
+/// Tea created before the history was introduced have their previous metadata recorded as
+/// version 1 upon their first edit.
+fn record_metadata_version(
+    store: &mut dyn Storage,
+    id: u64,
+    old: &Metadata,
+    new: &Metadata,
+    retained: Option<&Metadata>,
+) -> StdResult<u32> {
+    let version = metadata_version(store, id)?;
+
+    let mut previous = match METADATA_HISTORY.may_load(store, (id, version))? {
+        Some(previous) => previous,
+        None => MetadataVersion {
+            hash: metadata_hash(old)?,
+            metadata: None,
+        },
+    };
+    if let Some(retained) = retained {
+        previous.metadata = Some(retained.clone());
+    }
+    METADATA_HISTORY.save(store, (id, version), &previous)?;
+
+    let current = MetadataVersion {
+        hash: metadata_hash(new)?,
+        metadata: None,
+    };
+    METADATA_HISTORY.save(store, (id, version + 1), &current)?;
+
+    Ok(version + 1)
+}
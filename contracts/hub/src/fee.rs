@@ -1,34 +1,374 @@
-use cosmwasm_std::{to_json_binary, MessageInfo, Storage, Uint128, Decimal};
-use terp_sdk::Response;
+use std::collections::BTreeSet;
 
-use crate::{error::ContractError, state::DEVELOPER};
+use cosmwasm_std::{
+    coin, to_json_binary, Addr, BankMsg, Coin, Decimal, MessageInfo, Order, StdResult, Storage,
+    Uint128,
+};
+use cw_utils::PaymentError;
+use terp_fee::{fair_burn, ibc_denom_fair_burn, FeeError};
+use terp_sdk::{Response, NATIVE_FEE_DENOM};
 
-// TODO: add docs
-pub fn handle_fee<T: serde::Serialize>(
-    store: &dyn Storage,
+use terp_metadata::Metadata;
+use tea::FeeRate;
+
+use crate::{
+    error::ContractError,
+    metadata::blob_payer,
+    state::{
+        ALL_TEA, BOUNTY_POOL, BOUNTY_RATE, DENOM_FEE_RATES, DEPOSITS, DEVELOPER, FEE_MULTIPLIERS,
+        FEE_RATE, KEY_COUNT,
+    },
+    stats,
+};
+
+/// Number of bytes by which storage grows when `old_data` is replaced with `new_data`, which is
+/// what storage fees are charged for. Zero if the storage shrinks.
+pub fn bytes_diff<T: serde::Serialize>(old_data: Option<T>, new_data: T) -> StdResult<u64> {
+    let old_bytes = stored_bytes(old_data)?;
+    let new_bytes = stored_bytes(Some(new_data))?;
+    Ok(new_bytes.saturating_sub(old_bytes))
+}
+
+/// The multiplier applied to the fees paid by an account; 1 if it is not in the registry
+pub fn fee_multiplier(store: &dyn Storage, payer: &Addr) -> StdResult<Decimal> {
+    Ok(FEE_MULTIPLIERS.may_load(store, payer)?.unwrap_or(Decimal::one()))
+}
+
+/// All accepted fee denoms with their rates, uthiol first
+pub fn fee_rates(store: &dyn Storage) -> StdResult<Vec<(String, FeeRate)>> {
+    let mut fee_rates = vec![(NATIVE_FEE_DENOM.to_string(), FEE_RATE.load(store)?)];
+    for item in DENOM_FEE_RATES.range(store, None, None, Order::Ascending) {
+        fee_rates.push(item?);
+    }
+    Ok(fee_rates)
+}
+
+/// Charge a storage deposit for the storage taken by a tea's data growing from `old_bytes` to
+/// `new_bytes`, or refund part of the deposit to `manager` if it shrinks.
+///
+/// Metadata blobs shared with other tea only count towards the bytes of the tea that stored them,
+/// so that identical metadata is only paid for once (see `release_blob`).
+///
+/// The deposit is held by the Hub, except for the share retained in the bounty pool, which is not
+/// refundable. Refunds are proportional to the number of bytes freed.
+pub fn handle_fee(
+    store: &mut dyn Storage,
     info: &MessageInfo,
-    old_data: Option<T>,
-    new_data: T,
-    fee_per_byte: Decimal,
-) -> Result<Response, ContractError> {
-    // compute how much storage (in bytes) is taken
-    let old_bytes = old_data
-        .map(|data| to_json_binary(&data))
-        .transpose()?
-        .map(|bytes| bytes.len())
-        .unwrap_or(0);
-    let new_bytes = to_json_binary(&new_data)?.len();
-    let bytes_diff = new_bytes.saturating_sub(old_bytes);
-
-    // fee amount is bytes * fee rate (uthiol per bytes)
-    let fee = Uint128::new(bytes_diff as u128) * fee_per_byte;
+    id: u64,
+    manager: &Addr,
+    old_bytes: u64,
+    new_bytes: u64,
+) -> Result<Option<BankMsg>, ContractError> {
+    if new_bytes < old_bytes {
+        return Ok(refund_metadata(store, id, manager, old_bytes - new_bytes)?);
+    }
+
+    let mut deposit = DEPOSITS.may_load(store, id)?.unwrap_or_default();
+
+    let bytes_diff = new_bytes - old_bytes;
+    deposit.metadata_bytes += bytes_diff;
+    let (paid, pool_share) = collect_fee(store, info, bytes_diff, |rate| rate.metadata)?;
+    if let Some(paid) = paid {
+        add_coin(&mut deposit.metadata_deposit, paid);
+    }
+    deposit.bounty_credit += pool_share;
+
+    DEPOSITS.save(store, id, &deposit)?;
+
+    Ok(None)
+}
+
+/// Refund part of a tea's metadata deposit to `manager` for `bytes` bytes freed, proportionally to
+/// the bytes the deposit was paid for.
+pub fn refund_metadata(
+    store: &mut dyn Storage,
+    id: u64,
+    manager: &Addr,
+    bytes: u64,
+) -> StdResult<Option<BankMsg>> {
+    let mut deposit = DEPOSITS.may_load(store, id)?.unwrap_or_default();
+
+    let bytes_freed = bytes.min(deposit.metadata_bytes);
+    let refund = take_share(&mut deposit.metadata_deposit, bytes_freed, deposit.metadata_bytes);
+    deposit.metadata_bytes -= bytes_freed;
+
+    DEPOSITS.save(store, id, &deposit)?;
+
+    Ok(send_msg(manager, refund))
+}
 
+/// Release `bytes` bytes freed by tea `id` no longer referencing the blob of `metadata`. If another
+/// tea paid for the blob, its deposit is refunded to its manager and no bytes are counted as freed
+/// by `id`; otherwise all of them are.
+pub fn release_blob(
+    store: &mut dyn Storage,
+    id: u64,
+    metadata: &Metadata,
+    bytes: u64,
+) -> StdResult<(u64, Option<BankMsg>)> {
+    let payer = blob_payer(store, id, metadata)?;
+    if bytes == 0 || payer == id {
+        return Ok((bytes, None));
+    }
+
+    let manager = ALL_TEA.load(store, payer)?.manager;
+    Ok((0, refund_metadata(store, payer, &manager, bytes)?))
+}
+
+/// Charge a storage deposit for whitelisting a set of keys for a tea
+pub fn handle_key_fee(
+    store: &mut dyn Storage,
+    info: &MessageInfo,
+    id: u64,
+    keys: &BTreeSet<String>,
+) -> Result<(), ContractError> {
+    let bytes = bytes_diff(None, keys)?;
+
+    let mut deposit = DEPOSITS.may_load(store, id)?.unwrap_or_default();
+    deposit.key_bytes += bytes;
+    let (paid, pool_share) = collect_fee(store, info, bytes, |rate| rate.key)?;
+    if let Some(paid) = paid {
+        add_coin(&mut deposit.key_deposit, paid);
+    }
+    deposit.bounty_credit += pool_share;
+
+    DEPOSITS.save(store, id, &deposit)?;
+
+    Ok(())
+}
+
+/// Charge a fee for a series growing by `bytes` bytes, at the metadata fee rate.
+///
+/// Series can't shrink, so there is no deposit to refund, and they hold no data to be purged, so
+/// there is no bounty to fund. The fee is fair-burned instead, like tea fees before deposits.
+pub fn handle_series_fee(
+    store: &mut dyn Storage,
+    info: &MessageInfo,
+    bytes: u64,
+) -> Result<Response, ContractError> {
     let mut res = Response::new();
 
-    if !fee.is_zero() {
-        let developer = DEVELOPER.load(store)?;
-        terp_fee::checked_fair_burn(info, fee.u128(), Some(developer), &mut res)?;
+    let Some(fee) = charge(store, info, bytes, |rate| rate.metadata)? else {
+        return Ok(res);
+    };
+
+    stats::record_fee(store, &fee.denom, fee.amount)?;
+
+    let developer = DEVELOPER.load(store)?;
+    if fee.denom == NATIVE_FEE_DENOM {
+        fair_burn(fee.amount.u128(), Some(developer), &mut res);
+    } else {
+        ibc_denom_fair_burn(fee, Some(developer), &mut res)?;
     }
 
     Ok(res)
 }
+
+/// Refund part of a tea's key deposit to `manager` for `amount` keys consumed by minting.
+///
+/// Must be invoked before the keys are removed from `KEY_COUNT`, as the refund is the key deposit
+/// divided evenly among the keys remaining.
+pub fn refund_keys(
+    store: &mut dyn Storage,
+    id: u64,
+    manager: &Addr,
+    amount: u64,
+) -> StdResult<Option<BankMsg>> {
+    let refund = take_key_deposit(store, id, amount)?;
+    Ok(send_msg(manager, refund))
+}
+
+/// Release part of a tea's key deposit for `amount` keys being purged. The bounty for purging them
+/// is paid out of it, and the rest is refunded to `manager`.
+///
+/// Keys paid for their own storage, so their bounty is funded by their deposit rather than the
+/// bounty pool. This way, a manager purging their own keys recovers no more than they paid.
+///
+/// Must be invoked before the keys are removed from `KEY_COUNT`, like `refund_keys`. Returns the
+/// bounty, in uthiol, and the message refunding the rest to the manager, if any.
+pub fn release_purged_keys(
+    store: &mut dyn Storage,
+    id: u64,
+    manager: &Addr,
+    amount: u64,
+) -> StdResult<(Uint128, Option<BankMsg>)> {
+    let mut refund = take_key_deposit(store, id, amount)?;
+
+    let bounty_rate = BOUNTY_RATE.may_load(store)?.unwrap_or_default();
+    let mut bounty = bounty_rate.per_item.saturating_mul(Uint128::from(amount));
+    match refund.iter_mut().find(|coin| coin.denom == NATIVE_FEE_DENOM) {
+        Some(coin) => {
+            bounty = bounty.min(coin.amount);
+            coin.amount -= bounty;
+        },
+        None => bounty = Uint128::zero(),
+    }
+    refund.retain(|coin| !coin.amount.is_zero());
+
+    Ok((bounty, send_msg(manager, refund)))
+}
+
+/// Take the share of a tea's key deposit corresponding to `amount` of its remaining keys
+fn take_key_deposit(store: &mut dyn Storage, id: u64, amount: u64) -> StdResult<Vec<Coin>> {
+    let key_count = KEY_COUNT.may_load(store, id)?.unwrap_or(0);
+    let Some(mut deposit) = DEPOSITS.may_load(store, id)? else {
+        return Ok(vec![]);
+    };
+
+    let amount = amount.min(key_count);
+    let taken = take_share(&mut deposit.key_deposit, amount, key_count);
+    deposit.key_bytes -= share(deposit.key_bytes.into(), amount, key_count).u128() as u64;
+
+    DEPOSITS.save(store, id, &deposit)?;
+
+    Ok(taken)
+}
+
+/// Compute the bounty for purging `items` storage items of a tea, and deduct it from the bounty
+/// pool and the tea's bounty credit.
+///
+/// The bounty never exceeds the tea's credit, i.e. what its own fees put into the pool, so that no
+/// one can drain the pool funded by other organizers by purging data that cost nothing to create.
+/// If the credit or the pool does not hold enough funds, whatever is left is paid.
+pub fn take_bounty(store: &mut dyn Storage, id: u64, items: u64) -> StdResult<Uint128> {
+    let Some(mut deposit) = DEPOSITS.may_load(store, id)? else {
+        return Ok(Uint128::zero());
+    };
+
+    let bounty_rate = BOUNTY_RATE.may_load(store)?.unwrap_or_default();
+    let pool = BOUNTY_POOL.may_load(store)?.unwrap_or_default();
+    let bounty = bounty_rate
+        .per_item
+        .saturating_mul(Uint128::from(items))
+        .min(deposit.bounty_credit)
+        .min(pool);
+
+    if bounty.is_zero() {
+        return Ok(bounty);
+    }
+
+    deposit.bounty_credit -= bounty;
+    DEPOSITS.save(store, id, &deposit)?;
+    BOUNTY_POOL.save(store, &(pool - bounty))?;
+
+    Ok(bounty)
+}
+
+/// The message sending a bounty to the account that purged the data, if any
+pub fn bounty_msg(recipient: &Addr, bounty: Uint128) -> Option<BankMsg> {
+    if bounty.is_zero() {
+        return None;
+    }
+    send_msg(recipient, vec![coin(bounty.u128(), NATIVE_FEE_DENOM)])
+}
+
+/// Size of a piece of data in storage, in bytes
+pub fn stored_bytes<T: serde::Serialize>(data: Option<T>) -> StdResult<u64> {
+    data.map(|data| to_json_binary(&data))
+        .transpose()
+        .map(|bytes| bytes.map(|bytes| bytes.len() as u64).unwrap_or(0))
+}
+
+/// Check that the sender has paid the fee for storing `bytes` bytes, in any of the accepted denoms,
+/// and move the bounty pool's share of it into the pool. Returns the remainder, which is held as a
+/// refundable deposit, or None if no fee is charged, along with the pool's share.
+fn collect_fee(
+    store: &mut dyn Storage,
+    info: &MessageInfo,
+    bytes: u64,
+    fee_per_byte: fn(&FeeRate) -> Decimal,
+) -> Result<(Option<Coin>, Uint128), ContractError> {
+    let Some(fee) = charge(store, info, bytes, fee_per_byte)? else {
+        return Ok((None, Uint128::zero()));
+    };
+
+    // bounties are paid in uthiol, so only uthiol fees fund the pool
+    let mut pool_share = Uint128::zero();
+    if fee.denom == NATIVE_FEE_DENOM {
+        let bounty_rate = BOUNTY_RATE.may_load(store)?.unwrap_or_default();
+        pool_share = fee.amount * bounty_rate.pool_share;
+        let pool = BOUNTY_POOL.may_load(store)?.unwrap_or_default();
+        BOUNTY_POOL.save(store, &(pool + pool_share))?;
+    }
+
+    stats::record_fee(store, &fee.denom, fee.amount)?;
+
+    Ok((Some(coin((fee.amount - pool_share).u128(), fee.denom)), pool_share))
+}
+
+/// Check that the sender has paid the fee for storing `bytes` bytes, in any of the accepted denoms.
+/// Returns the fee, or None if no fee is charged.
+fn charge(
+    store: &dyn Storage,
+    info: &MessageInfo,
+    bytes: u64,
+    fee_per_byte: fn(&FeeRate) -> Decimal,
+) -> Result<Option<Coin>, ContractError> {
+    // the fee is paid in the denom sent by the sender, or uthiol if nothing is sent
+    let (denom, payment, fee_rate) = match info.funds.as_slice() {
+        [] => (NATIVE_FEE_DENOM.to_string(), Uint128::zero(), FEE_RATE.load(store)?),
+        [funds] if funds.denom == NATIVE_FEE_DENOM => {
+            (funds.denom.clone(), funds.amount, FEE_RATE.load(store)?)
+        },
+        [funds] => {
+            let fee_rate = DENOM_FEE_RATES
+                .may_load(store, &funds.denom)?
+                .ok_or_else(|| FeeError::from(PaymentError::ExtraDenom(funds.denom.clone())))?;
+            (funds.denom.clone(), funds.amount, fee_rate)
+        },
+        _ => return Err(FeeError::from(PaymentError::MultipleDenoms {}).into()),
+    };
+
+    // fee amount is bytes * fee rate (denom per bytes), discounted for organizers in the registry
+    let multiplier = fee_multiplier(store, &info.sender)?;
+    let fee = Uint128::from(bytes) * (fee_per_byte(&fee_rate) * multiplier);
+    if fee.is_zero() {
+        return Ok(None);
+    }
+
+    if payment < fee {
+        return Err(FeeError::InsufficientFee(fee.u128(), payment.u128()).into());
+    }
+
+    Ok(Some(coin(fee.u128(), denom)))
+}
+
+/// The part of `total` corresponding to `part` out of `whole` units
+fn share(total: Uint128, part: u64, whole: u64) -> Uint128 {
+    if whole == 0 {
+        return Uint128::zero();
+    }
+    total.multiply_ratio(part, whole)
+}
+
+fn add_coin(coins: &mut Vec<Coin>, new: Coin) {
+    match coins.iter_mut().find(|coin| coin.denom == new.denom) {
+        Some(coin) => coin.amount += new.amount,
+        None => coins.push(new),
+    }
+}
+
+/// Take the part corresponding to `part` out of `whole` units of each coin. Coins that are used up
+/// are removed.
+fn take_share(coins: &mut Vec<Coin>, part: u64, whole: u64) -> Vec<Coin> {
+    let taken = coins
+        .iter_mut()
+        .filter_map(|coin| {
+            let amount = share(coin.amount, part, whole);
+            coin.amount -= amount;
+            (!amount.is_zero()).then(|| Coin::new(amount.u128(), &coin.denom))
+        })
+        .collect();
+    coins.retain(|coin| !coin.amount.is_zero());
+    taken
+}
+
+fn send_msg(recipient: &Addr, amount: Vec<Coin>) -> Option<BankMsg> {
+    if amount.is_empty() {
+        return None;
+    }
+    Some(BankMsg::Send {
+        to_address: recipient.into(),
+        amount,
+    })
+}
@@ -1,13 +1,25 @@
+use std::collections::{BTreeMap, BTreeSet};
 use std::fmt;
 
-use cosmwasm_std::{Addr, Api, BlockInfo, Deps, Storage, Coin};
+use cosmwasm_std::{
+    to_json_string, Addr, Api, BlockInfo, Coin, Deps, Order, StdError, StdResult, Storage,
+};
 use sha2::{Digest, Sha256};
+use terp_metadata::Metadata;
+use url::Url;
 
-use tea::{Tea, MintRule};
+use tea::{
+    hub::TeaStatus,
+    nft::{CollectionInfoResponse, MinterResponse, OwnerOfResponse, QueryMsg as NftQueryMsg},
+    Tea, MintRule,
+};
 
 use crate::{
     error::ContractError,
-    state::{KEYS, OWNERS},
+    state::{
+        ALL_TEA, COLLECTIONS, DEVELOPER, FLAGGED_TEA, KEYS, LOCKED_METADATA, METADATA_HISTORY, NFT,
+        OWNERS, PREVIOUS_NFTS,
+    },
 };
 
 /// Length of a serialized compressed public key
@@ -15,11 +27,44 @@ const ECDSA_COMPRESSED_PUBKEY_LEN: usize = 33;
 /// Length of a serialized uncompressed public key
 const ECDSA_UNCOMPRESSED_PUBKEY_LEN: usize = 65;
 
+/// Schemes allowed in the URL fields of a tea's metadata
+pub const ALLOWED_URL_SCHEMES: [&str; 3] = ["ipfs", "https", "ar"];
+/// Trait types that the NFT contract adds to every token's metadata, which a tea may not use
+pub const RESERVED_TRAITS: [&str; 6] =
+    ["id", "serial", "metadata_version", "series", "verified", "flagged"];
+/// Maximum length, in bytes, of a tea's name
+pub const MAX_NAME_LEN: usize = 256;
+/// Maximum length, in bytes, of a tea's description
+pub const MAX_DESCRIPTION_LEN: usize = 4096;
+/// Maximum length, in bytes, of a URL in a tea's metadata
+pub const MAX_URL_LEN: usize = 1024;
+/// Maximum number of traits a tea may have
+pub const MAX_TRAITS: usize = 32;
+/// Maximum length, in bytes, of a trait's display type, type, or value
+pub const MAX_TRAIT_LEN: usize = 256;
+/// Maximum total number of instances a "by burn" rule may require to be burned
+pub const MAX_BURN_INPUTS: u64 = 30;
+/// Maximum length, in bytes, of a weighted tea's category
+pub const MAX_CATEGORY_LEN: usize = 64;
+/// Maximum weight of a tea
+pub const MAX_WEIGHT: u64 = 1_000_000;
+
+/// Id of the reply to the Hub's instantiation of the NFT contract
+pub const NFT_REPLY_ID: u64 = 2;
+
 /// Each NFT's token id is simply the tea id and the serial separated by a pipe.
 pub fn token_id(id: u64, serial: u64) -> String {
     format!("{}|{}", id, serial)
 }
 
+/// Split a token id into tea id and serial; the inverse of `token_id`
+pub fn parse_token_id(token_id: &str) -> StdResult<(u64, u64)> {
+    token_id
+        .split_once('|')
+        .and_then(|(id, serial)| Some((id.parse().ok()?, serial.parse().ok()?)))
+        .ok_or_else(|| StdError::generic_err(format!("invalid token id `{}`", token_id)))
+}
+
 /// The message the user needs to sign to claim the tea under "by key" or "by keys" rule
 pub fn message(id: u64, user: impl fmt::Display) -> String {
     format!("claim tea {} for user {}", id, user)
@@ -33,6 +78,23 @@ pub fn hash(msg: &str) -> Vec<u8> {
     hasher.finalize().to_vec()
 }
 
+/// Hex-encoded SHA256 hash of a tea's metadata, serialized in JSON
+pub fn metadata_hash(metadata: &Metadata) -> StdResult<String> {
+    let json = to_json_string(metadata)?;
+    Ok(hex::encode(hash(&json)))
+}
+
+/// The current version of a tea's metadata, that is, the latest one in its history; 1 if no
+/// history has been recorded for the tea.
+pub fn metadata_version(store: &dyn Storage, id: u64) -> StdResult<u32> {
+    let latest = METADATA_HISTORY
+        .prefix(id)
+        .keys(store, None, None, Order::Descending)
+        .next()
+        .transpose()?;
+    Ok(latest.unwrap_or(1))
+}
+
 /// A helper function to help casting Option to String
 pub fn stringify_option(opt: Option<impl fmt::Display>) -> String {
     opt.map_or_else(|| "undefined".to_string(), |value| value.to_string())
@@ -75,8 +137,8 @@ pub fn assert_valid_signature(
 
 // Assert the tea is available to be minted.
 // Throw an error if the mint deadline or the max supply has been reached.
-pub fn assert_available(
-    tea: &Tea,
+pub fn assert_available<M>(
+    tea: &Tea<M>,
     block: &BlockInfo,
     amount: u64,
 ) -> Result<(), ContractError> {
@@ -95,8 +157,149 @@ pub fn assert_available(
     Ok(())
 }
 
+/// Assert that a tea has not been flagged as abusive.
+pub fn assert_not_flagged(store: &dyn Storage, id: u64) -> Result<(), ContractError> {
+    match FLAGGED_TEA.may_load(store, id)? {
+        Some(reason) => Err(ContractError::flagged(id, reason)),
+        None => Ok(()),
+    }
+}
+
+/// Assert that a tea's metadata has not been locked.
+pub fn assert_metadata_unlocked(store: &dyn Storage, id: u64) -> Result<(), ContractError> {
+    if LOCKED_METADATA.contains(store, id) {
+        return Err(ContractError::MetadataLocked {
+            id,
+        });
+    }
+    Ok(())
+}
+
+/// Assert that the sender is the developer.
+pub fn assert_developer(store: &dyn Storage, sender: &Addr) -> Result<(), ContractError> {
+    if *sender != DEVELOPER.load(store)? {
+        return Err(ContractError::NotDeveloper);
+    }
+    Ok(())
+}
+
+/// The NFT contracts of all generations: the current one, followed by the previous ones from the
+/// most recently replaced
+pub fn nft_generations(store: &dyn Storage) -> StdResult<Vec<Addr>> {
+    let mut previous = PREVIOUS_NFTS
+        .range(store, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<_>>>()?;
+    previous.sort_by(|(_, a), (_, b)| b.cmp(a));
+
+    Ok(std::iter::once(NFT.load(store)?).chain(previous.into_iter().map(|(nft, _)| nft)).collect())
+}
+
+/// The NFT contract new instances of a tea are minted on: its dedicated collection if it has one,
+/// otherwise the current generation of the shared one
+pub fn tea_nft<M>(store: &dyn Storage, tea: &Tea<M>) -> StdResult<Addr> {
+    match &tea.nft {
+        Some(nft) => Ok(nft.clone()),
+        None => NFT.load(store),
+    }
+}
+
+/// The NFT contracts instances of a tea may be held on: its dedicated collection if it has one,
+/// otherwise the shared one of any generation, from the current one
+pub fn tea_nfts(store: &dyn Storage, id: u64) -> StdResult<Vec<Addr>> {
+    match ALL_TEA.may_load(store, id)?.and_then(|tea| tea.nft) {
+        Some(nft) => Ok(vec![nft]),
+        None => nft_generations(store),
+    }
+}
+
+/// Burns and transfers of a tea's instances are reported by its dedicated collection, or by the
+/// shared NFT contract of any generation, since instances minted on a previous one remain there
+pub fn assert_nft(store: &dyn Storage, sender: &Addr, id: u64) -> Result<(), ContractError> {
+    if !tea_nfts(store, id)?.contains(sender) {
+        return Err(ContractError::NotNft);
+    }
+    Ok(())
+}
+
+/// An NFT contract is never reused, neither as a generation of the shared one nor as a dedicated
+/// collection, so that each token is held by exactly one contract
+pub fn assert_nft_unused(store: &dyn Storage, nft: &Addr) -> Result<(), ContractError> {
+    let used = NFT.may_load(store)?.as_ref() == Some(nft)
+        || PREVIOUS_NFTS.has(store, nft)
+        || COLLECTIONS.has(store, nft);
+    if used {
+        return Err(ContractError::NftAlreadyUsed {
+            nft: nft.to_string(),
+        });
+    }
+    Ok(())
+}
+
+/// Assert that a collection to be registered by `organizer` runs the same code as the Hub's NFT
+/// contract, so that it can be trusted to report burns and transfers; has the Hub as its minter, so
+/// that tea can be minted on it; and has the organizer as its creator, so that no one can register
+/// another organizer's collection.
+pub fn assert_valid_collection(
+    deps: Deps,
+    hub: &Addr,
+    nft: &Addr,
+    organizer: &Addr,
+) -> Result<(), ContractError> {
+    let code_id = deps.querier.query_wasm_contract_info(NFT.load(deps.storage)?)?.code_id;
+    if deps.querier.query_wasm_contract_info(nft)?.code_id != code_id {
+        return Err(ContractError::invalid_collection(nft, "not a tea-nft contract"));
+    }
+
+    let minter: MinterResponse = deps.querier.query_wasm_smart(nft, &NftQueryMsg::Minter {})?;
+    if minter.minter.as_deref() != Some(hub.as_str()) {
+        return Err(ContractError::invalid_collection(nft, "minter is not the hub"));
+    }
+
+    let info: CollectionInfoResponse =
+        deps.querier.query_wasm_smart(nft, &NftQueryMsg::CollectionInfo {})?;
+    if info.creator != *organizer {
+        return Err(ContractError::invalid_collection(nft, "creator is not the sender"));
+    }
+
+    Ok(())
+}
+
+/// Categories are kept to short identifiers, so that they can be used as storage keys and typed
+/// into queries
+pub fn assert_valid_category(category: &str) -> Result<(), ContractError> {
+    let valid = !category.is_empty()
+        && category.len() <= MAX_CATEGORY_LEN
+        && category.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_');
+    if !valid {
+        return Err(ContractError::invalid_category(category, MAX_CATEGORY_LEN));
+    }
+    Ok(())
+}
+
+/// Hooks of all tea are managed by the developer, and those of a single tea by its manager
+pub fn assert_can_manage_hooks(
+    store: &dyn Storage,
+    sender: &Addr,
+    id: Option<u64>,
+) -> Result<(), ContractError> {
+    match id {
+        Some(id) if *sender != ALL_TEA.load(store, id)?.manager => Err(ContractError::NotManager),
+        Some(_) => Ok(()),
+        None => assert_developer(store, sender),
+    }
+}
+
+/// The tea's status at the given block, following the same logic as `assert_available`.
+pub fn tea_status<M>(tea: &Tea<M>, block: &BlockInfo) -> TeaStatus {
+    match assert_available(tea, block, 1) {
+        Err(ContractError::Expired) => TeaStatus::Expired,
+        Err(_) => TeaStatus::SoldOut,
+        Ok(_) => TeaStatus::Active,
+    }
+}
+
 // Assert the tea it NOT available to be minted. Throw an error if it is available.
-pub fn assert_unavailable(tea: &Tea, block: &BlockInfo) -> Result<(), ContractError> {
+pub fn assert_unavailable<M>(tea: &Tea<M>, block: &BlockInfo) -> Result<(), ContractError> {
     match assert_available(tea, block, 1) {
         Ok(_) => Err(ContractError::Available),
         Err(_) => Ok(()),
@@ -113,7 +316,7 @@ pub fn assert_eligible(store: &dyn Storage, id: u64, user: &str) -> Result<(), C
 }
 
 /// Assert that a tea indeed uses the "by minter" rule, and that the sender is the minter.
-pub fn assert_can_mint_by_minter(tea: &Tea, sender: &Addr) -> Result<(), ContractError> {
+pub fn assert_can_mint_by_minter<M>(tea: &Tea<M>, sender: &Addr) -> Result<(), ContractError> {
     match &tea.rule {
         MintRule::ByMinter(minter) => {
             if minter != sender {
@@ -128,10 +331,10 @@ pub fn assert_can_mint_by_minter(tea: &Tea, sender: &Addr) -> Result<(), Contrac
 
 /// Assert that a tea indeed uses the "by key" rule, and the signature was produced by signing the
 /// correct message with the correct privkey.
-pub fn assert_can_mint_by_key(
+pub fn assert_can_mint_by_key<M>(
     api: &dyn Api,
     id: u64,
-    tea: &Tea,
+    tea: &Tea<M>,
     owner: &str,
     signature: &str,
 ) -> Result<(), ContractError> {
@@ -150,10 +353,10 @@ pub fn assert_can_mint_by_key(
 
 /// Assert that a tea indeed uses the "by keys" rule, and that the signature was produced by
 /// signing the correct message using a whitelisted privkey.
-pub fn assert_can_mint_by_keys(
+pub fn assert_can_mint_by_keys<M>(
     deps: Deps,
     id: u64,
-    tea: &Tea,
+    tea: &Tea<M>,
     owner: &str,
     pubkey: &str,
     signature: &str,
@@ -176,6 +379,184 @@ pub fn assert_can_mint_by_keys(
     Ok(())
 }
 
+/// Assert that the inputs of a "by burn" rule are well-formed: there is at least one, each refers
+/// to an existing tea at most once and requires at least one instance, and the total number of
+/// instances does not exceed `MAX_BURN_INPUTS`.
+pub fn assert_valid_burn_inputs(
+    store: &dyn Storage,
+    inputs: &[(u64, u64)],
+) -> Result<(), ContractError> {
+    if inputs.is_empty() {
+        return Err(ContractError::invalid_burn_inputs("no inputs"));
+    }
+
+    let mut ids = BTreeSet::new();
+    for (id, count) in inputs {
+        if !ids.insert(*id) {
+            return Err(ContractError::invalid_burn_inputs(format!("tea {} is listed twice", id)));
+        }
+        if *count == 0 {
+            return Err(ContractError::invalid_burn_inputs(format!("tea {} has a count of 0", id)));
+        }
+        if !ALL_TEA.has(store, *id) {
+            return Err(ContractError::invalid_burn_inputs(format!("tea {} does not exist", id)));
+        }
+    }
+
+    let total: u64 = inputs.iter().map(|(_, count)| count).sum();
+    if total > MAX_BURN_INPUTS {
+        return Err(ContractError::invalid_burn_inputs(format!(
+            "{} instances exceed the maximum of {}",
+            total, MAX_BURN_INPUTS
+        )));
+    }
+
+    Ok(())
+}
+
+/// Assert that a tea indeed uses the "by burn" rule, that the tokens to be burned are exactly the
+/// instances its inputs require, and that all of them are owned by `owner`. Each token is looked
+/// up on the NFT contracts its tea may be held on, in order; the contract holding each token is
+/// returned, so that it can be burned there.
+pub fn assert_can_mint_by_burn<M>(
+    deps: Deps,
+    tea: &Tea<M>,
+    owner: &str,
+    token_ids: &BTreeSet<String>,
+) -> Result<Vec<Addr>, ContractError> {
+    // the tea must use the "by burn" minting rule
+    let inputs = match &tea.rule {
+        MintRule::ByBurn {
+            inputs,
+        } => inputs,
+        rule => return Err(ContractError::wrong_mint_rule("by_burn", rule)),
+    };
+
+    // the number of tokens of each tea must match the inputs, with no other tokens
+    let mut found = BTreeMap::new();
+    for token_id in token_ids {
+        let (id, _) = parse_token_id(token_id)?;
+        *found.entry(id).or_insert(0u64) += 1;
+    }
+    for (id, expected) in inputs {
+        let found = found.remove(id).unwrap_or(0);
+        if found != *expected {
+            return Err(ContractError::WrongBurnInputs {
+                id: *id,
+                expected: *expected,
+                found,
+            });
+        }
+    }
+    if let Some((id, found)) = found.into_iter().next() {
+        return Err(ContractError::WrongBurnInputs {
+            id,
+            expected: 0,
+            found,
+        });
+    }
+
+    // the tokens must all be owned by the owner, on whichever contract holds them
+    token_ids
+        .iter()
+        .map(|token_id| {
+            let (id, _) = parse_token_id(token_id)?;
+            let query = NftQueryMsg::OwnerOf {
+                token_id: token_id.clone(),
+                include_expired: None,
+            };
+            tea_nfts(deps.storage, id)?
+                .into_iter()
+                .find(|nft| {
+                    deps.querier
+                        .query_wasm_smart::<OwnerOfResponse>(nft, &query)
+                        .is_ok_and(|res| res.owner == owner)
+                })
+                .ok_or_else(|| ContractError::not_token_owner(token_id, owner))
+        })
+        .collect()
+}
+
+/// Run the checks of `mint_by_keys` if a whitelisted `pubkey` is provided, or of `mint_by_key`
+/// otherwise, without modifying any state. Both mint paths, and the `CanMint` query, go through
+/// here.
+pub fn assert_can_claim<M>(
+    deps: Deps,
+    block: &BlockInfo,
+    id: u64,
+    tea: &Tea<M>,
+    owner: &str,
+    pubkey: Option<&str>,
+    signature: &str,
+) -> Result<(), ContractError> {
+    assert_not_flagged(deps.storage, id)?;
+    assert_available(tea, block, 1)?;
+    assert_eligible(deps.storage, id, owner)?;
+
+    match pubkey {
+        Some(pubkey) => assert_can_mint_by_keys(deps, id, tea, owner, pubkey, signature),
+        None => assert_can_mint_by_key(deps.api, id, tea, owner, signature),
+    }
+}
+
+/// Assert that a tea's metadata is well-formed: URLs parse and use an allowed scheme, text fields
+/// are within their maximum lengths, and no trait collides with those added by the NFT contract.
+pub fn assert_valid_metadata(metadata: &Metadata) -> Result<(), ContractError> {
+    let urls = [
+        ("image", &metadata.image),
+        ("external_url", &metadata.external_url),
+        ("animation_url", &metadata.animation_url),
+    ];
+    for (field, url) in urls {
+        if let Some(url) = url {
+            assert_max_len(field, url, MAX_URL_LEN)?;
+            assert_valid_url(field, url)?;
+        }
+    }
+
+    if let Some(name) = &metadata.name {
+        assert_max_len("name", name, MAX_NAME_LEN)?;
+    }
+
+    if let Some(description) = &metadata.description {
+        assert_max_len("description", description, MAX_DESCRIPTION_LEN)?;
+    }
+
+    let traits = metadata.attributes.as_deref().unwrap_or_default();
+    if traits.len() > MAX_TRAITS {
+        return Err(ContractError::metadata_too_long("attributes", traits.len(), MAX_TRAITS));
+    }
+
+    for t in traits {
+        if let Some(display_type) = &t.display_type {
+            assert_max_len("attributes.display_type", display_type, MAX_TRAIT_LEN)?;
+        }
+        assert_max_len("attributes.trait_type", &t.trait_type, MAX_TRAIT_LEN)?;
+        assert_max_len("attributes.value", &t.value, MAX_TRAIT_LEN)?;
+
+        if RESERVED_TRAITS.contains(&t.trait_type.as_str()) {
+            return Err(ContractError::reserved_trait(&t.trait_type));
+        }
+    }
+
+    Ok(())
+}
+
+fn assert_max_len(field: &str, value: &str, max: usize) -> Result<(), ContractError> {
+    if value.len() > max {
+        return Err(ContractError::metadata_too_long(field, value.len(), max));
+    }
+    Ok(())
+}
+
+fn assert_valid_url(field: &str, url: &str) -> Result<(), ContractError> {
+    let url = Url::parse(url).map_err(|err| ContractError::invalid_url(field, err))?;
+    if !ALLOWED_URL_SCHEMES.contains(&url.scheme()) {
+        return Err(ContractError::disallowed_url_scheme(field, url.scheme()));
+    }
+    Ok(())
+}
+
 /// Assert that a byte array is a valid secp256k1 public key.
 ///
 /// Copied from cosmwasm-crypto:
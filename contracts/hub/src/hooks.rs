@@ -0,0 +1,45 @@
+use cosmwasm_std::{Addr, Order, StdResult, Storage};
+use terp_sdk::SubMsg;
+
+use tea::hook::TeaHookMsg;
+
+use crate::state::{GLOBAL_HOOKS, TEA_HOOKS};
+
+/// Id of the reply to a hook whose failure is ignored
+pub const HOOK_REPLY_ID: u64 = 1;
+
+/// Maximum number of hooks registered for all tea, and separately for each tea, so that the gas
+/// cost of minting stays bounded
+pub const MAX_HOOKS: usize = 10;
+
+/// Hook contracts registered for the events of tea `id`, or if None, for those of all tea, along
+/// with whether their failures are fatal
+pub fn hooks(store: &dyn Storage, id: Option<u64>) -> StdResult<Vec<(Addr, bool)>> {
+    match id {
+        Some(id) => TEA_HOOKS.prefix(id).range(store, None, None, Order::Ascending).collect(),
+        None => GLOBAL_HOOKS.range(store, None, None, Order::Ascending).collect(),
+    }
+}
+
+/// Submessages delivering an event of tea `id` to the hooks registered for all tea, followed by
+/// those registered for this tea. Failures of fatal hooks fail the mint; those of the other hooks
+/// are caught in the reply.
+///
+/// Burns and transfers are executed by holders on the NFT contract, which reports them to the Hub,
+/// so failing them would let a hook stop holders from burning or moving their own tokens. Failures
+/// of any hook on these events are therefore always caught.
+pub fn hook_msgs(store: &dyn Storage, id: u64, msg: &TeaHookMsg) -> StdResult<Vec<SubMsg>> {
+    let is_mint = matches!(msg, TeaHookMsg::Minted { .. });
+    hooks(store, None)?
+        .into_iter()
+        .chain(hooks(store, Some(id))?)
+        .map(|(contract, fatal)| {
+            let wasm_msg = msg.clone().into_wasm_msg(contract)?;
+            Ok(if fatal && is_mint {
+                SubMsg::new(wasm_msg)
+            } else {
+                SubMsg::reply_on_error(wasm_msg, HOOK_REPLY_ID)
+            })
+        })
+        .collect()
+}
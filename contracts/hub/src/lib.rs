@@ -4,6 +4,10 @@ pub mod error;
 pub mod execute;
 pub mod fee;
 pub mod helpers;
+pub mod hooks;
+pub mod metadata;
 pub mod query;
+pub mod scores;
 pub mod state;
+pub mod stats;
 pub mod upgrades;
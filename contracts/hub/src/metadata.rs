@@ -0,0 +1,93 @@
+use cosmwasm_std::{StdResult, Storage};
+use terp_metadata::Metadata;
+
+use tea::Tea;
+
+use crate::{
+    fee::stored_bytes,
+    helpers::metadata_hash,
+    state::{StoredTea, ALL_TEA, METADATA, METADATA_PAYERS, METADATA_REFS},
+};
+
+/// Number of bytes newly taken by storing `metadata`: the size of its blob, or zero if an identical
+/// blob is already stored.
+pub fn bytes_added(store: &dyn Storage, metadata: &Metadata) -> StdResult<u64> {
+    if METADATA_REFS.has(store, &metadata_hash(metadata)?) {
+        return Ok(0);
+    }
+    stored_bytes(Some(metadata))
+}
+
+/// Number of bytes freed and taken, respectively, by a tea's metadata being replaced from `old` to
+/// `new`. The old blob is only freed if no other tea references it, and the new blob is only taken
+/// if it is not already stored.
+pub fn bytes_replaced(
+    store: &dyn Storage,
+    old: &Metadata,
+    new: &Metadata,
+) -> StdResult<(u64, u64)> {
+    let old_hash = metadata_hash(old)?;
+    if old_hash == metadata_hash(new)? {
+        return Ok((0, 0));
+    }
+    let freed = match METADATA_REFS.load(store, &old_hash)? {
+        1 => stored_bytes(Some(old))?,
+        _ => 0,
+    };
+    Ok((freed, bytes_added(store, new)?))
+}
+
+/// The tea whose deposit holds the bytes of the blob of `metadata`, referenced by tea `id`: the tea
+/// that paid for storing it, or `id` itself if no payer was recorded
+pub fn blob_payer(store: &dyn Storage, id: u64, metadata: &Metadata) -> StdResult<u64> {
+    Ok(METADATA_PAYERS.may_load(store, &metadata_hash(metadata)?)?.unwrap_or(id))
+}
+
+/// Number of bytes taken by a tea's own record, not counting its metadata blob
+pub fn record_bytes(tea: &Tea) -> StdResult<u64> {
+    stored_bytes(Some(tea.clone().with_metadata(metadata_hash(&tea.metadata)?)))
+}
+
+/// Load a tea, with its metadata resolved from the blob it references
+pub fn load_tea(store: &dyn Storage, id: u64) -> StdResult<Tea> {
+    resolve(store, ALL_TEA.load(store, id)?)
+}
+
+/// Replace a stored tea's metadata hash with the metadata itself
+pub fn resolve(store: &dyn Storage, tea: StoredTea) -> StdResult<Tea> {
+    let metadata = METADATA.load(store, &tea.metadata)?;
+    Ok(tea.with_metadata(metadata))
+}
+
+/// Save a tea, storing its metadata blob if not already stored, with the tea as its payer, and
+/// releasing the blob the tea previously referenced, if any
+pub fn save_tea(store: &mut dyn Storage, id: u64, tea: &Tea) -> StdResult<()> {
+    let hash = metadata_hash(&tea.metadata)?;
+    let old_tea = ALL_TEA.may_load(store, id)?;
+
+    let refs = METADATA_REFS.may_load(store, &hash)?.unwrap_or(0);
+    if refs == 0 {
+        METADATA.save(store, &hash, &tea.metadata)?;
+        METADATA_PAYERS.save(store, &hash, &id)?;
+    }
+    METADATA_REFS.save(store, &hash, &(refs + 1))?;
+
+    if let Some(old_tea) = old_tea {
+        release(store, &old_tea.metadata)?;
+    }
+
+    ALL_TEA.save(store, id, &tea.clone().with_metadata(hash))
+}
+
+/// Drop a reference to a metadata blob, removing the blob once no tea references it
+fn release(store: &mut dyn Storage, hash: &str) -> StdResult<()> {
+    match METADATA_REFS.load(store, hash)? {
+        1 => {
+            METADATA_REFS.remove(store, hash);
+            METADATA.remove(store, hash);
+            METADATA_PAYERS.remove(store, hash);
+        },
+        refs => METADATA_REFS.save(store, hash, &(refs - 1))?,
+    }
+    Ok(())
+}
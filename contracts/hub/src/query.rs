@@ -1,36 +1,143 @@
-use cosmwasm_std::{Deps, Order, StdResult};
+use cosmwasm_std::{coin, Coin, Decimal, Deps, Env, Order, StdError, StdResult, Storage, Uint128};
 use cw_storage_plus::Bound;
 
 use tea::hub::{
-    TeaResponse, AllTeaResponse, ConfigResponse, KeyResponse, KeysResponse, OwnerResponse,
-    OwnersResponse,
+    TeaResponse, AllSeriesResponse, AllTeaResponse, CanMintResponse, CollectionResponse,
+    ConfigResponse, DepositResponse, EstimateFeeResponse, FeeAction, FeeMultiplierResponse,
+    FeeMultipliersResponse, FlagResponse, FlaggedTeaResponse, HookResponse, HooksResponse,
+    IssuerResponse, IssuersResponse, KeyResponse, KeysResponse, LeaderboardResponse,
+    MetadataHistoryResponse, MetadataVersionResponse, OwnerResponse, OrderBy, OwnersResponse,
+    ActiveTeaCountResponse, ClaimedByResponse,
+    PendingUpgradesResponse, PreviousNftResponse, PreviousNftsResponse, ScoreResponse,
+    SeriesResponse, StatsResponse, TeaStatsResponse, TeaStatus, UpgradeResponse,
 };
+use tea::{FeeRate, MintRule, Series, Tea};
 
-use crate::state::*;
+use crate::{
+    contract::CONTRACT_VERSION,
+    fee,
+    helpers::{assert_can_claim, metadata_version, tea_status},
+    hooks,
+    metadata::{blob_payer, bytes_added, bytes_replaced, load_tea, record_bytes, resolve},
+    scores::ALL_CATEGORIES,
+    state::*,
+    upgrades,
+};
 
 pub const DEFAULT_LIMIT: u32 = 10;
 pub const MAX_LIMIT: u32 = 30;
 
+/// Limits for queries that only count entries, which are much cheaper to go through
+pub const DEFAULT_COUNT_LIMIT: u32 = 100;
+pub const MAX_COUNT_LIMIT: u32 = 500;
+
+/// Maximum number of tea a query filtering tea goes through in a single call, whether or not they
+/// match the filter
+pub const MAX_SCAN: usize = 300;
+
 pub fn config(deps: Deps) -> StdResult<ConfigResponse> {
     let developer_addr = DEVELOPER.load(deps.storage)?;
     let nft_addr = NFT.load(deps.storage)?;
     let tea_count = TEA_COUNT.load(deps.storage)?;
     let fee_rate = FEE_RATE.load(deps.storage)?;
+    let denom_fee_rates = DENOM_FEE_RATES
+        .range(deps.storage, None, None, Order::Ascending)
+        .collect::<StdResult<_>>()?;
+    let bounty_rate = BOUNTY_RATE.may_load(deps.storage)?.unwrap_or_default();
+    let bounty_pool = BOUNTY_POOL.may_load(deps.storage)?.unwrap_or_default();
+    let nft_update_delay = NFT_UPDATE_DELAY.may_load(deps.storage)?.unwrap_or(0);
+    let pending_nft = PENDING_NFT.may_load(deps.storage)?;
     Ok(ConfigResponse {
         developer: developer_addr.into(),
         nft: nft_addr.into(),
         tea_count,
         fee_rate,
+        denom_fee_rates,
+        bounty_rate,
+        bounty_pool,
+        nft_update_delay,
+        pending_nft,
     })
 }
 
 pub fn tea(deps: Deps, id: u64) -> StdResult<TeaResponse> {
     let tea = ALL_TEA.load(deps.storage, id)?;
-    Ok((id, tea).into())
+    tea_response(deps.storage, id, tea)
 }
 
 pub fn all_tea(
     deps: Deps,
+    env: Env,
+    status: Option<TeaStatus>,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+    order: Option<OrderBy>,
+) -> StdResult<AllTeaResponse> {
+    let order = order.map(Order::from).unwrap_or(Order::Ascending);
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+
+    // in descending order, the cursor is the upper bound of the range
+    let (min, max) = match order {
+        Order::Ascending => (start_after.map(Bound::exclusive), None),
+        Order::Descending => (None, start_after.map(Bound::exclusive)),
+    };
+
+    // stop once the page is full or `MAX_SCAN` tea have been gone through, so that a filter
+    // matching few tea doesn't make the query go through all of them. in either case, the last
+    // tea gone through is returned as the cursor to resume from
+    let mut tea = vec![];
+    let mut last = None;
+    let mut next = None;
+    for (scanned, item) in ALL_TEA.range(deps.storage, min, max, order).enumerate() {
+        if tea.len() == limit || scanned == MAX_SCAN {
+            next = last;
+            break;
+        }
+
+        let (id, t) = item?;
+        last = Some(id);
+
+        if status.is_none_or(|status| tea_status(&t, &env.block) == status) {
+            tea.push(tea_response(deps.storage, id, t)?);
+        }
+    }
+
+    Ok(AllTeaResponse {
+        tea,
+        next,
+    })
+}
+
+pub fn tea_by_manager(
+    deps: Deps,
+    manager: String,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<AllTeaResponse> {
+    let start = start_after.map(Bound::exclusive);
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+
+    let tea = ALL_TEA
+        .idx
+        .manager
+        .prefix(manager)
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| {
+            let (id, tea) = item?;
+            tea_response(deps.storage, id, tea)
+        })
+        .collect::<StdResult<Vec<_>>>()?;
+
+    Ok(AllTeaResponse {
+        tea,
+        next: None,
+    })
+}
+
+pub fn tea_by_rule(
+    deps: Deps,
+    rule: String,
     start_after: Option<u64>,
     limit: Option<u32>,
 ) -> StdResult<AllTeaResponse> {
@@ -38,16 +145,44 @@ pub fn all_tea(
     let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
 
     let tea = ALL_TEA
+        .idx
+        .rule
+        .prefix(rule)
         .range(deps.storage, start, None, Order::Ascending)
         .take(limit)
         .map(|item| {
             let (id, tea) = item?;
-            Ok((id, tea).into())
+            tea_response(deps.storage, id, tea)
         })
         .collect::<StdResult<Vec<_>>>()?;
 
     Ok(AllTeaResponse {
         tea,
+        next: None,
+    })
+}
+
+pub fn series(deps: Deps, id: u64) -> StdResult<SeriesResponse> {
+    let series = ALL_SERIES.load(deps.storage, id)?;
+    Ok((id, series).into())
+}
+
+pub fn all_series(
+    deps: Deps,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<AllSeriesResponse> {
+    let start = start_after.map(Bound::exclusive);
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+
+    let series = ALL_SERIES
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| item.map(SeriesResponse::from))
+        .collect::<StdResult<Vec<_>>>()?;
+
+    Ok(AllSeriesResponse {
+        series,
     })
 }
 
@@ -80,6 +215,45 @@ pub fn keys(
     })
 }
 
+pub fn score(deps: Deps, user: String, category: Option<String>) -> StdResult<ScoreResponse> {
+    let key = category.as_deref().unwrap_or(ALL_CATEGORIES);
+    let score = SCORES.may_load(deps.storage, (key, &user))?.unwrap_or(0);
+    Ok(ScoreResponse {
+        user,
+        category,
+        score,
+    })
+}
+
+pub fn leaderboard(
+    deps: Deps,
+    category: Option<String>,
+    start_after: Option<(u64, String)>,
+    limit: Option<u32>,
+) -> StdResult<LeaderboardResponse> {
+    let key = category.as_deref().unwrap_or(ALL_CATEGORIES);
+    let end = start_after.as_ref().map(|(score, user)| Bound::exclusive((*score, user.as_str())));
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+
+    let scores = LEADERBOARD
+        .sub_prefix(key)
+        .keys(deps.storage, None, end, Order::Descending)
+        .take(limit)
+        .map(|item| {
+            let (score, user) = item?;
+            Ok(ScoreResponse {
+                user,
+                category: category.clone(),
+                score,
+            })
+        })
+        .collect::<StdResult<_>>()?;
+
+    Ok(LeaderboardResponse {
+        scores,
+    })
+}
+
 /// This function takes `impl Into<String>` instead of `String` so that i can type a few characters
 /// less in the unit tests =)
 pub fn owner(deps: Deps, id: u64, user: impl Into<String>) -> OwnerResponse {
@@ -110,3 +284,422 @@ pub fn owners(
         owners,
     })
 }
+
+pub fn claimed_by(
+    deps: Deps,
+    user: String,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<ClaimedByResponse> {
+    let start = start_after.map(Bound::exclusive);
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+
+    let ids = CLAIMED
+        .prefix(&user)
+        .keys(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .collect::<StdResult<Vec<_>>>()?;
+
+    Ok(ClaimedByResponse {
+        user,
+        ids,
+    })
+}
+
+pub fn can_mint(
+    deps: Deps,
+    env: Env,
+    id: u64,
+    owner: String,
+    pubkey: Option<String>,
+    signature: String,
+) -> StdResult<CanMintResponse> {
+    let Some(tea) = ALL_TEA.may_load(deps.storage, id)? else {
+        return Ok(CanMintResponse {
+            ok: false,
+            reason: Some("not_found".to_string()),
+        });
+    };
+
+    // follow the mint path of the tea's rule; under the "by keys" rule, a missing pubkey is
+    // reported as a key that doesn't exist
+    let pubkey = match tea.rule {
+        MintRule::ByKeys => Some(pubkey.as_deref().unwrap_or_default()),
+        _ => None,
+    };
+
+    let res = assert_can_claim(deps, &env.block, id, &tea, &owner, pubkey, &signature);
+
+    Ok(CanMintResponse {
+        ok: res.is_ok(),
+        reason: res.err().map(|err| err.code().to_string()),
+    })
+}
+
+pub fn stats(deps: Deps) -> StdResult<StatsResponse> {
+    let fees_collected = FEES_COLLECTED
+        .range(deps.storage, None, None, Order::Ascending)
+        .map(|item| {
+            let (denom, amount) = item?;
+            Ok(coin(amount.u128(), denom))
+        })
+        .collect::<StdResult<Vec<_>>>()?;
+
+    Ok(StatsResponse {
+        tea_created: TEA_COUNT.load(deps.storage)?,
+        tea_minted: TOTAL_MINTED.may_load(deps.storage)?.unwrap_or(0),
+        fees_collected,
+        keys_purged: KEYS_PURGED.may_load(deps.storage)?.unwrap_or(0),
+        owners_purged: OWNERS_PURGED.may_load(deps.storage)?.unwrap_or(0),
+    })
+}
+
+pub fn active_tea_count(
+    deps: Deps,
+    env: Env,
+    start_after: Option<(u64, u64)>,
+    limit: Option<u32>,
+) -> StdResult<ActiveTeaCountResponse> {
+    let limit = limit.unwrap_or(DEFAULT_COUNT_LIMIT).min(MAX_COUNT_LIMIT) as usize;
+
+    // tea that have not sold out and whose minting deadline has not been reached
+    let now = env.block.time.seconds();
+    let start = match start_after {
+        Some(key) if key >= (now, 0) => Bound::exclusive(key),
+        _ => Bound::inclusive((now, 0)),
+    };
+
+    let keys = OPEN_TEA
+        .items(deps.storage, Some(start), None, Order::Ascending)
+        .take(limit)
+        .collect::<StdResult<Vec<_>>>()?;
+
+    Ok(ActiveTeaCountResponse {
+        count: keys.len() as u64,
+        next: if keys.len() < limit {
+            None
+        } else {
+            keys.last().copied()
+        },
+    })
+}
+
+pub fn tea_stats(deps: Deps, id: u64) -> StdResult<TeaStatsResponse> {
+    let mints = MINTS
+        .prefix(id)
+        .range(deps.storage, None, None, Order::Ascending)
+        .collect::<StdResult<_>>()?;
+
+    Ok(TeaStatsResponse {
+        id,
+        mints,
+        keys_remaining: KEY_COUNT.may_load(deps.storage, id)?.unwrap_or(0),
+        owners: OWNER_COUNT.may_load(deps.storage, id)?.unwrap_or(0),
+        burns: BURNS.may_load(deps.storage, id)?.unwrap_or(0),
+    })
+}
+
+pub fn deposit(deps: Deps, id: u64) -> StdResult<DepositResponse> {
+    // tea created before storage deposits were introduced have no deposit
+    let deposit = DEPOSITS.may_load(deps.storage, id)?.unwrap_or_default();
+    Ok((id, deposit).into())
+}
+
+pub fn estimate_fee(
+    deps: Deps,
+    action: FeeAction,
+    payer: Option<String>,
+) -> StdResult<EstimateFeeResponse> {
+    // count the bytes exactly as they are counted when executing the action
+    let (bytes, fee_per_byte): (_, fn(&FeeRate) -> Decimal) = match action {
+        FeeAction::CreateTea {
+            manager,
+            metadata,
+            transferrable,
+            rule,
+            expiry,
+            max_supply,
+            reveal,
+            nft,
+        } => {
+            let tea = Tea {
+                manager: deps.api.addr_validate(&manager)?,
+                metadata,
+                transferrable,
+                rule,
+                expiry,
+                max_supply,
+                current_supply: 0,
+                reveal,
+                nft: nft.map(|nft| deps.api.addr_validate(&nft)).transpose()?,
+            };
+            let bytes = record_bytes(&tea)? + bytes_added(deps.storage, &tea.metadata)?;
+            (bytes, |rate| rate.metadata)
+        },
+        FeeAction::EditTea {
+            id,
+            metadata,
+            retain_previous,
+        } => {
+            let tea = load_tea(deps.storage, id)?;
+            let retained = retain_previous.then_some(&tea.metadata);
+            let (bytes_freed, bytes_taken) =
+                bytes_replaced(deps.storage, &tea.metadata, &metadata)?;
+            // bytes of a blob paid for by another tea are refunded to that tea instead
+            let bytes_freed = if blob_payer(deps.storage, id, &tea.metadata)? == id {
+                bytes_freed
+            } else {
+                0
+            };
+            let bytes_taken = bytes_taken + fee::stored_bytes(retained)?;
+            (bytes_taken.saturating_sub(bytes_freed), |rate| rate.metadata)
+        },
+        FeeAction::AddKeys {
+            keys,
+            ..
+        } => (fee::bytes_diff(None, &keys)?, |rate| rate.key),
+        FeeAction::CreateSeries {
+            manager,
+            metadata,
+        } => {
+            let series = Series {
+                manager: deps.api.addr_validate(&manager)?,
+                metadata,
+                tea: vec![],
+            };
+            (fee::bytes_diff(None, &series)?, |rate| rate.metadata)
+        },
+        FeeAction::AddToSeries {
+            id,
+            tea,
+        } => {
+            let old_series = ALL_SERIES.load(deps.storage, id)?;
+            let mut series = old_series.clone();
+            series.tea.extend(tea);
+            (fee::bytes_diff(Some(&old_series), &series)?, |rate| rate.metadata)
+        },
+    };
+
+    let multiplier = match payer {
+        Some(payer) => fee::fee_multiplier(deps.storage, &deps.api.addr_validate(&payer)?)?,
+        None => Decimal::one(),
+    };
+
+    let fees = fee::fee_rates(deps.storage)?
+        .into_iter()
+        .map(|(denom, fee_rate)| {
+            let amount = Uint128::from(bytes) * (fee_per_byte(&fee_rate) * multiplier);
+            Coin::new(amount.u128(), denom)
+        })
+        .collect();
+
+    Ok(EstimateFeeResponse {
+        fees,
+    })
+}
+
+pub fn fee_multipliers(
+    deps: Deps,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<FeeMultipliersResponse> {
+    let start = start_after.map(|address| Bound::ExclusiveRaw(address.into_bytes()));
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+
+    let multipliers = FEE_MULTIPLIERS
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| {
+            let (address, multiplier) = item?;
+            Ok(FeeMultiplierResponse {
+                address: address.into(),
+                multiplier,
+            })
+        })
+        .collect::<StdResult<_>>()?;
+
+    Ok(FeeMultipliersResponse {
+        multipliers,
+    })
+}
+
+pub fn issuer(deps: Deps, address: String) -> StdResult<IssuerResponse> {
+    let addr = deps.api.addr_validate(&address)?;
+    let verified = VERIFIED_ISSUERS.contains(deps.storage, &addr);
+    Ok(IssuerResponse {
+        address,
+        verified,
+    })
+}
+
+pub fn verified_issuers(
+    deps: Deps,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<IssuersResponse> {
+    let start = start_after.map(|address| Bound::ExclusiveRaw(address.into_bytes()));
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+
+    let issuers = VERIFIED_ISSUERS
+        .items(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| item.map(String::from))
+        .collect::<StdResult<_>>()?;
+
+    Ok(IssuersResponse {
+        issuers,
+    })
+}
+
+pub fn flagged_tea(
+    deps: Deps,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<FlaggedTeaResponse> {
+    let start = start_after.map(Bound::exclusive);
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+
+    let tea = FLAGGED_TEA
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| {
+            let (id, reason) = item?;
+            Ok(FlagResponse {
+                id,
+                reason,
+            })
+        })
+        .collect::<StdResult<_>>()?;
+
+    Ok(FlaggedTeaResponse {
+        tea,
+    })
+}
+
+pub fn metadata_history(
+    deps: Deps,
+    id: u64,
+    start_after: Option<u32>,
+    limit: Option<u32>,
+) -> StdResult<MetadataHistoryResponse> {
+    let tea = ALL_TEA.load(deps.storage, id)?;
+    let start = start_after.map(Bound::exclusive);
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+
+    let mut versions = METADATA_HISTORY
+        .prefix(id)
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| {
+            let (version, entry) = item?;
+            Ok(MetadataVersionResponse {
+                version,
+                hash: entry.hash,
+                metadata: entry.metadata,
+            })
+        })
+        .collect::<StdResult<Vec<_>>>()?;
+
+    // tea created before the history was introduced, and not edited since, only have version 1
+    let current_version = metadata_version(deps.storage, id)?;
+    if start_after.is_none() && !METADATA_HISTORY.has(deps.storage, (id, 1)) {
+        versions.push(MetadataVersionResponse {
+            version: 1,
+            hash: tea.metadata,
+            metadata: None,
+        });
+    }
+
+    Ok(MetadataHistoryResponse {
+        id,
+        current_version,
+        locked: LOCKED_METADATA.contains(deps.storage, id),
+        versions,
+    })
+}
+
+pub fn previous_nfts(
+    deps: Deps,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<PreviousNftsResponse> {
+    let start = start_after.map(|nft| Bound::ExclusiveRaw(nft.into_bytes()));
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+
+    let nfts = PREVIOUS_NFTS
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| {
+            let (nft, replaced_at) = item?;
+            Ok(PreviousNftResponse {
+                nft: nft.into(),
+                replaced_at,
+            })
+        })
+        .collect::<StdResult<_>>()?;
+
+    Ok(PreviousNftsResponse {
+        nfts,
+    })
+}
+
+pub fn collection(deps: Deps, nft: String) -> StdResult<CollectionResponse> {
+    let organizer = COLLECTIONS.load(deps.storage, &deps.api.addr_validate(&nft)?)?;
+    Ok(CollectionResponse {
+        nft,
+        organizer: organizer.into(),
+    })
+}
+
+pub fn hooks(deps: Deps, id: Option<u64>) -> StdResult<HooksResponse> {
+    let hooks = hooks::hooks(deps.storage, id)?
+        .into_iter()
+        .map(|(contract, fatal)| HookResponse {
+            contract: contract.into(),
+            fatal,
+        })
+        .collect();
+
+    Ok(HooksResponse {
+        hooks,
+    })
+}
+
+pub fn pending_upgrades(deps: Deps) -> StdResult<PendingUpgradesResponse> {
+    let version = cw2::get_contract_version(deps.storage)?.version;
+
+    let upgrades = upgrades::pending(&version, CONTRACT_VERSION)
+        .map_err(|err| StdError::generic_err(err.to_string()))?
+        .into_iter()
+        .map(|upgrade| UpgradeResponse {
+            from: upgrade.from.to_string(),
+            to: upgrade.to.to_string(),
+        })
+        .collect();
+
+    Ok(PendingUpgradesResponse {
+        current_version: version,
+        target_version: CONTRACT_VERSION.to_string(),
+        upgrades,
+    })
+}
+
+/// The tea's response, with the manager's current status in the registry of verified issuers, the
+/// flag on the tea, if any, and the version of its metadata
+fn tea_response(store: &dyn Storage, id: u64, tea: StoredTea) -> StdResult<TeaResponse> {
+    let issuer_verified = VERIFIED_ISSUERS.contains(store, &tea.manager);
+    let flag_reason = FLAGGED_TEA.may_load(store, id)?;
+    let metadata_version = metadata_version(store, id)?;
+    let metadata_locked = LOCKED_METADATA.contains(store, id);
+    let series = TEA_SERIES.may_load(store, id)?;
+    let weight = TEA_WEIGHTS.may_load(store, id)?;
+    Ok(TeaResponse {
+        issuer_verified,
+        flag_reason,
+        metadata_version,
+        metadata_locked,
+        series,
+        weight,
+        ..(id, resolve(store, tea)?).into()
+    })
+}
@@ -0,0 +1,70 @@
+use cosmwasm_std::{Empty, StdResult, Storage};
+
+use tea::Weight;
+
+use crate::state::{GLOBAL_WEIGHTS, LEADERBOARD, SCORES, TEA_WEIGHTS};
+
+/// Category under which the scores across all categories are keyed in `SCORES` and `LEADERBOARD`.
+/// No tea can be weighted in this category, since categories cannot be empty.
+pub const ALL_CATEGORIES: &str = "";
+
+/// Apply `action` to a user's score within a category, keeping the leaderboard in sync
+fn update(
+    store: &mut dyn Storage,
+    category: &str,
+    user: &str,
+    action: impl FnOnce(u64) -> u64,
+) -> StdResult<()> {
+    let old_score = SCORES.may_load(store, (category, user))?.unwrap_or(0);
+    let new_score = action(old_score);
+
+    if old_score > 0 {
+        LEADERBOARD.remove(store, (category, old_score, user));
+    }
+
+    if new_score > 0 {
+        SCORES.save(store, (category, user), &new_score)?;
+        LEADERBOARD.save(store, (category, new_score, user), &Empty {})?;
+    } else {
+        SCORES.remove(store, (category, user));
+    }
+
+    Ok(())
+}
+
+/// The tea's weight, if any, and the categories it counts towards: its own, and all categories if
+/// the weight was approved
+fn weighted_categories(store: &dyn Storage, id: u64) -> StdResult<Option<(Weight, Vec<String>)>> {
+    let Some(weight) = TEA_WEIGHTS.may_load(store, id)? else {
+        return Ok(None);
+    };
+
+    let mut categories = vec![weight.category.clone()];
+    if GLOBAL_WEIGHTS.contains(store, id) {
+        categories.push(ALL_CATEGORIES.to_string());
+    }
+
+    Ok(Some((weight, categories)))
+}
+
+/// Record a user having received an instance of a tea, by minting or by transfer, adding the tea's
+/// weight, if any, to their score
+pub fn record_gain(store: &mut dyn Storage, id: u64, user: &str) -> StdResult<()> {
+    if let Some((weight, categories)) = weighted_categories(store, id)? {
+        for category in &categories {
+            update(store, category, user, |score| score.saturating_add(weight.weight))?;
+        }
+    }
+    Ok(())
+}
+
+/// Record a user having given up an instance of a tea, by burning or by transfer, deducting the
+/// tea's weight, if any, from their score
+pub fn record_loss(store: &mut dyn Storage, id: u64, user: &str) -> StdResult<()> {
+    if let Some((weight, categories)) = weighted_categories(store, id)? {
+        for category in &categories {
+            update(store, category, user, |score| score.saturating_sub(weight.weight))?;
+        }
+    }
+    Ok(())
+}
@@ -1,8 +1,11 @@
-use cosmwasm_std::Addr;
+use cosmwasm_std::{Addr, Decimal, Empty, Uint128};
 use cw_item_set::Set;
-use cw_storage_plus::{Item, Map};
+use cw_storage_plus::{Index, IndexList, IndexedMap, Item, Map, MultiIndex};
 
-use tea::{Tea, FeeRate};
+use terp_metadata::Metadata;
+
+use tea::hub::PendingNft;
+use tea::{Tea, BountyRate, Deposit, FeeRate, MetadataVersion, Series, Weight};
 
 /// Address of the developer
 pub const DEVELOPER: Item<Addr> = Item::new("owner");
@@ -10,20 +13,179 @@ pub const DEVELOPER: Item<Addr> = Item::new("owner");
 /// Address of tea nft contract
 pub const NFT: Item<Addr> = Item::new("nft");
 
+/// Number of seconds an NFT contract update must wait before it can be applied. Defaults to zero
+pub const NFT_UPDATE_DELAY: Item<u64> = Item::new("nft_update_delay");
+
+/// The NFT contract update scheduled by the developer, if any
+pub const PENDING_NFT: Item<PendingNft> = Item::new("pending_nft");
+
+/// NFT contracts that have been replaced, keyed by address, with the time they were replaced.
+/// Earlier instances of tea remain on these contracts, which may still report burns and transfers
+pub const PREVIOUS_NFTS: Map<&Addr, u64> = Map::new("previous_nfts");
+
+/// Dedicated collections registered by organizers, keyed by address, with the organizer who
+/// registered each. Only tea created by that organizer can be minted on a collection
+pub const COLLECTIONS: Map<&Addr, Addr> = Map::new("collections");
+
 /// The fee rate, in uthiol per byte, charged for storing data on-chain
 pub const FEE_RATE: Item<FeeRate> = Item::new("fee_rate");
 
+/// Fee rates, keyed by denom, for denoms other than uthiol in which fees are also accepted
+pub const DENOM_FEE_RATES: Map<&str, FeeRate> = Map::new("denom_fee_rates");
+
+/// Multipliers applied to the fees paid by certain organizers, e.g. partner universities and
+/// nonprofits. Organizers not in the registry pay the full fee
+pub const FEE_MULTIPLIERS: Map<&Addr, Decimal> = Map::new("fee_multipliers");
+
+/// Registry of verified issuers, managed by the developer or L1 governance
+pub const VERIFIED_ISSUERS: Set<&Addr> = Set::new("verified_issuers");
+
+/// The share of fees retained for, and the bounty paid from, the bounty pool. Bounties are
+/// disabled if not set
+pub const BOUNTY_RATE: Item<BountyRate> = Item::new("bounty_rate");
+
+/// Amount of uthiol held by the hub for paying purge bounties
+pub const BOUNTY_POOL: Item<Uint128> = Item::new("bounty_pool");
+
 /// Total number of tea
 pub const TEA_COUNT: Item<u64> = Item::new("tea_count");
 
-/// All tea tokens, indexed by ids
-pub const ALL_TEA: Map<u64, Tea> = Map::new("tea");
+/// A tea as stored by the Hub, holding the hash of its metadata in place of the metadata itself
+pub type StoredTea = Tea<String>;
+
+pub struct TeaIndexes<'a> {
+    /// Tea indexed by their managers
+    pub manager: MultiIndex<'a, String, StoredTea, u64>,
+    /// Tea indexed by the kind of their mint rule, i.e. `by_minter`, `by_key`, `by_keys` or
+    /// `by_burn`
+    pub rule: MultiIndex<'a, String, StoredTea, u64>,
+}
+
+impl<'a> IndexList<StoredTea> for TeaIndexes<'a> {
+    fn get_indexes(&'_ self) -> Box<dyn Iterator<Item = &'_ dyn Index<StoredTea>> + '_> {
+        let v: Vec<&dyn Index<StoredTea>> = vec![&self.manager, &self.rule];
+        Box::new(v.into_iter())
+    }
+}
+
+/// All tea tokens, indexed by ids, and additionally by managers and mint rules.
+///
+/// Tea being created or having their metadata replaced must be saved through `metadata::save_tea`,
+/// which keeps the reference counts of the metadata blobs in sync.
+pub const ALL_TEA: IndexedMap<u64, StoredTea, TeaIndexes> = IndexedMap::new(
+    "tea",
+    TeaIndexes {
+        manager: MultiIndex::new(|_, tea| tea.manager.to_string(), "tea", "tea__manager"),
+        rule: MultiIndex::new(|_, tea| tea.rule.kind().to_string(), "tea", "tea__rule"),
+    },
+);
+
+/// Metadata blobs, keyed by the hex-encoded SHA256 hash of their JSON serialization. Each blob is
+/// stored once, however many tea share it.
+pub const METADATA: Map<&str, Metadata> = Map::new("metadata");
+
+/// Number of tea referencing each metadata blob, keyed by hash. A blob is removed once no tea
+/// references it.
+pub const METADATA_REFS: Map<&str, u64> = Map::new("metadata_refs");
+
+/// The tea that paid for storing each metadata blob, keyed by hash. The blob's bytes are counted in
+/// this tea's deposit, which is refunded once no tea references the blob, whichever tea releases it
+/// last.
+pub const METADATA_PAYERS: Map<&str, u64> = Map::new("metadata_payers");
+
+/// Tea flagged as abusive by the developer or L1 governance, keyed by tea id, with the reasons
+pub const FLAGGED_TEA: Map<u64, String> = Map::new("flagged_tea");
+
+/// Versions of each tea's metadata, keyed {tea_id, version}. Versions start from 1.
+///
+/// Tea created before the history was introduced have no entries until they are first edited, at
+/// which point their metadata at the time is recorded as version 1.
+pub const METADATA_HISTORY: Map<(u64, u32), MetadataVersion> = Map::new("metadata_history");
+
+/// Tea whose metadata have been permanently frozen by their managers
+pub const LOCKED_METADATA: Set<u64> = Set::new("locked_metadata");
+
+/// Total number of series
+pub const SERIES_COUNT: Item<u64> = Item::new("series_count");
+
+/// All series, keyed by series id
+pub const ALL_SERIES: Map<u64, Series> = Map::new("series");
+
+/// The series each tea belongs to, keyed by tea id. Tea not in any series have no entry
+pub const TEA_SERIES: Map<u64, u64> = Map::new("tea_series");
+
+/// The weight each tea contributes to its holders' reputation scores, keyed by tea id. Tea whose
+/// manager has not set a weight have no entry, and do not count towards scores
+pub const TEA_WEIGHTS: Map<u64, Weight> = Map::new("tea_weights");
+
+/// Tea whose weight also counts towards scores across all categories, i.e. whose weight was set by
+/// the developer or a verified issuer. Recorded when the weight is set, since the weight is locked
+/// once an instance has been minted, so that instances are deducted as they were scored.
+pub const GLOBAL_WEIGHTS: Set<u64> = Set::new("global_weights");
+
+/// Reputation score of each user, keyed {category, user_addr}, with the score across all
+/// categories keyed under the empty category. Users with a score of zero have no entry
+pub const SCORES: Map<(&str, &str), u64> = Map::new("scores");
+
+/// Index of `SCORES` keyed {category, score, user_addr}, for ranking users by score. A map of
+/// empty values rather than a set, so that a single category can be ranged over.
+///
+/// Must always be updated together with `SCORES`.
+pub const LEADERBOARD: Map<(&str, u64, &str), Empty> = Map::new("leaderboard");
+
+/// Storage deposits held for each tea, keyed by tea id
+pub const DEPOSITS: Map<u64, Deposit> = Map::new("deposits");
 
 /// Pubkeys that are whitelisted to mint a tea
 pub const KEYS: Set<(u64, &str)> = Set::new("keys");
 
+/// Id of the tea from which the next `PurgeExpired` call resumes purging
+pub const PURGE_CURSOR: Item<u64> = Item::new("purge_cursor");
+
 /// User addresses that have already claimed a tea. If a composite key {tea_id, user_addr}
 /// exists in the map, then this user has already claimed.
 ///
 /// Note that we don't verify the addresses here. The verifification is done by the NFT contract.
 pub const OWNERS: Set<(u64, &str)> = Set::new("claimed");
+
+/// Reverse index of `OWNERS`, keyed {user_addr, tea_id}, for looking up all tea a user has claimed.
+/// Also records the recipients of "by minter" mints, which are not tracked in `OWNERS`; these
+/// entries are never purged.
+///
+/// Must always be updated together with `OWNERS`.
+pub const CLAIMED: Set<(&str, u64)> = Set::new("user_claims");
+
+/// Tea that have not sold out, keyed {expiry, tea_id}, with tea without an expiry keyed at
+/// `u64::MAX`. Used to count the tea that are active at a given time.
+pub const OPEN_TEA: Set<(u64, u64)> = Set::new("open_tea");
+
+/// Total number of tea instances minted across all tea
+pub const TOTAL_MINTED: Item<u64> = Item::new("total_minted");
+
+/// Total amount of fees collected, by denom
+pub const FEES_COLLECTED: Map<&str, Uint128> = Map::new("fees_collected");
+
+/// Total number of keys removed from storage by purging
+pub const KEYS_PURGED: Item<u64> = Item::new("keys_purged");
+
+/// Total number of user addresses removed from storage by purging
+pub const OWNERS_PURGED: Item<u64> = Item::new("owners_purged");
+
+/// Number of instances of a tea minted, keyed {tea_id, mint_rule_kind}
+pub const MINTS: Map<(u64, &str), u64> = Map::new("mints");
+
+/// Number of whitelisted keys remaining for a tea, i.e. the size of `KEYS.prefix(tea_id)`
+pub const KEY_COUNT: Map<u64, u64> = Map::new("key_count");
+
+/// Number of users who have claimed a tea, i.e. the size of `OWNERS.prefix(tea_id)`
+pub const OWNER_COUNT: Map<u64, u64> = Map::new("owner_count");
+
+/// Number of instances of a tea that have been burned, as reported by the NFT contract
+pub const BURNS: Map<u64, u64> = Map::new("burns");
+
+/// Hook contracts notified of the events of all tea, and whether their failures are fatal
+pub const GLOBAL_HOOKS: Map<&Addr, bool> = Map::new("global_hooks");
+
+/// Hook contracts notified of the events of a single tea, keyed {tea_id, contract_addr}, and
+/// whether their failures are fatal
+pub const TEA_HOOKS: Map<(u64, &Addr), bool> = Map::new("tea_hooks");
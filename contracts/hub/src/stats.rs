@@ -0,0 +1,93 @@
+use cosmwasm_std::{StdResult, Storage, Uint128};
+use cw_storage_plus::{Item, Map, PrimaryKey};
+
+use tea::Tea;
+
+use crate::state::*;
+
+/// Key of a tea in `OPEN_TEA`
+fn open_tea_key<M>(id: u64, tea: &Tea<M>) -> (u64, u64) {
+    (tea.expiry.unwrap_or(u64::MAX), id)
+}
+
+fn add<'a>(store: &mut dyn Storage, item: &Item<'a, u64>, amount: u64) -> StdResult<()> {
+    let count = item.may_load(store)?.unwrap_or(0);
+    item.save(store, &(count + amount))
+}
+
+fn add_to<'a, K: PrimaryKey<'a>>(
+    store: &mut dyn Storage,
+    map: &Map<'a, K, u64>,
+    key: K,
+    amount: u64,
+) -> StdResult<()> {
+    map.update(store, key, |count| StdResult::Ok(count.unwrap_or(0) + amount)).map(|_| ())
+}
+
+fn sub_from<'a, K: PrimaryKey<'a>>(
+    store: &mut dyn Storage,
+    map: &Map<'a, K, u64>,
+    key: K,
+    amount: u64,
+) -> StdResult<()> {
+    map.update(store, key, |count| StdResult::Ok(count.unwrap_or(0).saturating_sub(amount)))
+        .map(|_| ())
+}
+
+pub fn record_create<M>(store: &mut dyn Storage, id: u64, tea: &Tea<M>) -> StdResult<()> {
+    OPEN_TEA.insert(store, open_tea_key(id, tea)).map(|_| ())
+}
+
+/// Record `amount` new instances of a tea having been minted. `tea` is the tea after its current
+/// supply has been updated.
+pub fn record_mints<M>(
+    store: &mut dyn Storage,
+    id: u64,
+    tea: &Tea<M>,
+    amount: u64,
+) -> StdResult<()> {
+    add(store, &TOTAL_MINTED, amount)?;
+    add_to(store, &MINTS, (id, tea.rule.kind()), amount)?;
+
+    if tea.max_supply == Some(tea.current_supply) {
+        OPEN_TEA.remove(store, open_tea_key(id, tea))?;
+    }
+
+    Ok(())
+}
+
+pub fn record_fee(store: &mut dyn Storage, denom: &str, amount: Uint128) -> StdResult<()> {
+    FEES_COLLECTED
+        .update(store, denom, |total| StdResult::Ok(total.unwrap_or_default() + amount))
+        .map(|_| ())
+}
+
+pub fn record_keys_added(store: &mut dyn Storage, id: u64, amount: u64) -> StdResult<()> {
+    add_to(store, &KEY_COUNT, id, amount)
+}
+
+/// Record keys having been removed, either consumed by minting or purged
+pub fn record_keys_removed(
+    store: &mut dyn Storage,
+    id: u64,
+    amount: u64,
+    purged: bool,
+) -> StdResult<()> {
+    if purged {
+        add(store, &KEYS_PURGED, amount)?;
+    }
+    sub_from(store, &KEY_COUNT, id, amount)
+}
+
+pub fn record_owner_added(store: &mut dyn Storage, id: u64) -> StdResult<()> {
+    add_to(store, &OWNER_COUNT, id, 1)
+}
+
+pub fn record_owners_purged(store: &mut dyn Storage, id: u64, amount: u64) -> StdResult<()> {
+    add(store, &OWNERS_PURGED, amount)?;
+    sub_from(store, &OWNER_COUNT, id, amount)
+}
+
+pub fn record_burn(store: &mut dyn Storage, id: u64) -> StdResult<()> {
+    add_to(store, &BURNS, id, 1)
+}
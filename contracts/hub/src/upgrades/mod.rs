@@ -1,2 +1,63 @@
+use cosmwasm_std::{DepsMut, StdResult};
+use semver::{Version, VersionReq};
+
+use crate::error::ContractError;
+
+pub mod v0_2;
 pub mod v1_1;
-pub mod v1_2;
\ No newline at end of file
+pub mod v1_2;
+
+/// A transformation of the contract state, bringing the contract from a range of versions to a
+/// newer one.
+#[derive(Debug)]
+pub struct Upgrade {
+    /// Semver requirement the stored contract version must satisfy for this upgrade to be applied
+    pub from: &'static str,
+    /// The contract version once this upgrade has been applied
+    pub to: &'static str,
+    /// The function that transforms the contract state
+    pub migrate: fn(DepsMut) -> StdResult<()>,
+}
+
+/// All state upgrades, in the order they are to be applied.
+///
+/// When a release requires transforming the contract state, add a `vX_Y` module that declares its
+/// `UPGRADE`, and append it here.
+///
+/// NOTE: Hubs deployed before this registry report `1.0.0`, while the following release recorded
+/// the workspace version, `0.1.0`, in its place. The `v1_2` upgrade, which takes the former to the
+/// latter, is therefore listed first.
+pub const UPGRADES: &[Upgrade] = &[v1_2::UPGRADE, v0_2::UPGRADE];
+
+pub fn parse_version(version: &str) -> Result<Version, ContractError> {
+    Version::parse(version).map_err(|err| ContractError::invalid_version(version, err))
+}
+
+/// Find the upgrades that need to be applied, in order, to bring the contract from the `current`
+/// version to the `target` version.
+///
+/// An upgrade is selected if the version reached so far satisfies its `from` requirement, and its
+/// `to` version does not go beyond the target. Downgrading to an older version is not allowed: the
+/// version reached once all upgrades are selected must not be newer than the target.
+pub fn pending(current: &str, target: &str) -> Result<Vec<&'static Upgrade>, ContractError> {
+    let target_version = parse_version(target)?;
+    let mut version = parse_version(current)?;
+
+    let mut upgrades = vec![];
+    for upgrade in UPGRADES {
+        let from = VersionReq::parse(upgrade.from)
+            .map_err(|err| ContractError::invalid_version(upgrade.from, err))?;
+        let to = parse_version(upgrade.to)?;
+
+        if from.matches(&version) && to <= target_version {
+            upgrades.push(upgrade);
+            version = to;
+        }
+    }
+
+    if version > target_version {
+        return Err(ContractError::incorrect_contract_version(format!("<={}", target), current));
+    }
+
+    Ok(upgrades)
+}
@@ -0,0 +1,217 @@
+use cosmwasm_schema::cw_serde;
+use cosmwasm_std::{DepsMut, Order, StdResult, Storage};
+use cw_storage_plus::{Bound, Item, Map};
+
+use tea::Tea;
+
+use crate::{
+    error::ContractError,
+    metadata::save_tea,
+    state::{CLAIMED, KEYS, KEY_COUNT, OWNERS, OWNER_COUNT},
+    stats,
+};
+
+use super::Upgrade;
+
+/// Deployed hubs hold the v1.2 state, recorded as `0.1.0`, or as `1.0.0` by those that have not been
+/// migrated since v1.1; the latter reach `0.1.0` through the `v1_2` upgrade first. The state layout
+/// introduced here is recorded as `0.2.0`, so that hubs it has been applied to are told apart.
+pub const UPGRADE: Upgrade = Upgrade {
+    from: ">=0.1.0, <0.2.0",
+    to: "0.2.0",
+    migrate,
+};
+
+/// Progress of the v0.2 backfill, which goes over all existing tea, owners and keys, and may exceed
+/// the gas limit of a single transaction. It is applied in batches by the `Backfill` execute
+/// message, and this item only exists until it is complete.
+pub const BACKFILL: Item<Backfill> = Item::new("backfill");
+
+/// A stage of the v0.2 backfill, with the last item it has processed
+#[cw_serde]
+pub enum Backfill {
+    Tea {
+        after: Option<u64>,
+    },
+    Claims {
+        after: Option<(u64, String)>,
+    },
+    KeyCounts {
+        after: Option<(u64, String)>,
+    },
+}
+
+pub fn migrate(deps: DepsMut) -> StdResult<()> {
+    // the state is backfilled in batches once the migration is done
+    BACKFILL.save(
+        deps.storage,
+        &Backfill::Tea {
+            after: None,
+        },
+    )
+}
+
+/// Process up to `limit` items of the pending backfill, resuming from where the previous call
+/// stopped. Returns the stage the next call resumes from, or `None` if the backfill is complete.
+///
+/// - move the metadata of existing tea into blobs, build the manager and mint rule indexes of the
+///   tea, and their statistics
+/// - build the reverse index of users' claims, and count the owners of each tea
+/// - count the remaining keys of each tea
+pub fn backfill(store: &mut dyn Storage, limit: usize) -> Result<Option<Backfill>, ContractError> {
+    let mut cursor = BACKFILL.may_load(store)?.ok_or(ContractError::NoPendingBackfill)?;
+    let mut budget = limit;
+
+    loop {
+        let (processed, next) = match cursor {
+            Backfill::Tea {
+                after,
+            } => {
+                let (processed, after) = backfill_tea(store, after, budget)?;
+                (
+                    processed,
+                    Backfill::Tea {
+                        after,
+                    },
+                )
+            },
+            Backfill::Claims {
+                after,
+            } => {
+                let (processed, after) = backfill_claims(store, after, budget)?;
+                (
+                    processed,
+                    Backfill::Claims {
+                        after,
+                    },
+                )
+            },
+            Backfill::KeyCounts {
+                after,
+            } => {
+                let (processed, after) = backfill_key_counts(store, after, budget)?;
+                (
+                    processed,
+                    Backfill::KeyCounts {
+                        after,
+                    },
+                )
+            },
+        };
+        budget -= processed;
+
+        // this stage may have more items left; the next call resumes from it
+        if budget == 0 {
+            cursor = next;
+            break;
+        }
+
+        // a stage that processed fewer items than its budget is complete
+        cursor = match next {
+            Backfill::Tea {
+                ..
+            } => Backfill::Claims {
+                after: None,
+            },
+            Backfill::Claims {
+                ..
+            } => Backfill::KeyCounts {
+                after: None,
+            },
+            Backfill::KeyCounts {
+                ..
+            } => {
+                BACKFILL.remove(store);
+                return Ok(None);
+            },
+        };
+    }
+
+    BACKFILL.save(store, &cursor)?;
+
+    Ok(Some(cursor))
+}
+
+/// Prior to v0.2, `ALL_TEA` was a plain map, holding each tea's metadata inline. Its primary
+/// namespace is unchanged, so existing tea are read in the legacy format and saved again, which
+/// writes their index entries and stores their metadata as shared blobs.
+///
+/// Statistics were also introduced in v0.2. Mints are attributed to each tea's current rule; fees
+/// collected and burns prior to v0.2 are unknown.
+fn backfill_tea(
+    store: &mut dyn Storage,
+    after: Option<u64>,
+    limit: usize,
+) -> StdResult<(usize, Option<u64>)> {
+    // tea after the cursor have not been saved anew, so they are all in the legacy format
+    let legacy_all_tea: Map<u64, Tea> = Map::new("tea");
+    let all_tea = legacy_all_tea
+        .range(store, after.map(Bound::exclusive), None, Order::Ascending)
+        .take(limit)
+        .collect::<StdResult<Vec<_>>>()?;
+    let last = all_tea.last().map(|(id, _)| *id).or(after);
+
+    for (id, tea) in &all_tea {
+        // the legacy record cannot be read as a stored tea, so it is removed before saving anew
+        legacy_all_tea.remove(store, *id);
+        save_tea(store, *id, tea)?;
+
+        stats::record_create(store, *id, tea)?;
+        if tea.current_supply > 0 {
+            stats::record_mints(store, *id, tea, tea.current_supply)?;
+        }
+    }
+
+    Ok((all_tea.len(), last))
+}
+
+/// `CLAIMED`, the reverse index of `OWNERS`, was introduced in v0.2.
+///
+/// NOTE: Recipients of "by minter" mints are not in `OWNERS`, and no other record of them is kept
+/// by the Hub, so those minted before v0.2 are missing from the index.
+fn backfill_claims(
+    store: &mut dyn Storage,
+    after: Option<(u64, String)>,
+    limit: usize,
+) -> StdResult<(usize, Option<(u64, String)>)> {
+    let claims = OWNERS
+        .items(
+            store,
+            after.as_ref().map(|(id, user)| Bound::exclusive((*id, user.as_str()))),
+            None,
+            Order::Ascending,
+        )
+        .take(limit)
+        .collect::<StdResult<Vec<_>>>()?;
+    let last = claims.last().cloned().or(after);
+
+    for (id, user) in &claims {
+        CLAIMED.insert(store, (user, *id))?;
+        OWNER_COUNT.update(store, *id, |count| StdResult::Ok(count.unwrap_or(0) + 1))?;
+    }
+
+    Ok((claims.len(), last))
+}
+
+fn backfill_key_counts(
+    store: &mut dyn Storage,
+    after: Option<(u64, String)>,
+    limit: usize,
+) -> StdResult<(usize, Option<(u64, String)>)> {
+    let keys = KEYS
+        .items(
+            store,
+            after.as_ref().map(|(id, key)| Bound::exclusive((*id, key.as_str()))),
+            None,
+            Order::Ascending,
+        )
+        .take(limit)
+        .collect::<StdResult<Vec<_>>>()?;
+    let last = keys.last().cloned().or(after);
+
+    for (id, _) in &keys {
+        KEY_COUNT.update(store, *id, |count| StdResult::Ok(count.unwrap_or(0) + 1))?;
+    }
+
+    Ok((keys.len(), last))
+}
@@ -1,26 +1,27 @@
 use cosmwasm_std::{DepsMut, StdResult};
-use terp_sdk::Response;
 
 // use tea::MintRule;
 
-use crate::{
-    contract::{CONTRACT_NAME, CONTRACT_VERSION},
-    // state::ALL_TEA,
-};
+// use crate::state::ALL_TEA;
+
+use super::Upgrade;
 
 // const NEW_TEA_17_KEY: &str = "036986114808be5b9f9009754014bdf5ae210cc17c93f4e1d010164be74b8653f4";
 
-pub fn migrate(deps: DepsMut) -> StdResult<Response> {
+/// In the previous v1.1 update, we forgot to set the contract version to `1.1.0`, so contracts
+/// running v1.1 still report `1.0.0`. The v1.2 update then recorded the workspace version, `0.1.0`,
+/// without changing the state, so hubs at either version hold the same state.
+pub const UPGRADE: Upgrade = Upgrade {
+    from: "=1.0.0",
+    to: "0.1.0",
+    migrate,
+};
+
+pub fn migrate(_deps: DepsMut) -> StdResult<()> {
     // correct the claim key of tea 17
     // update_tea_17_key(deps.storage)?;
 
-    // set the contract version to v1.2.0
-    cw2::set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
-
-    Ok(Response::new()
-        .add_attribute("action", "tea/hub/migrate"))
-        // .add_attribute("from_version", "1.1.0")
-        // .add_attribute("to_version", "1.2.0"))
+    Ok(())
 }
 
 // fn update_tea_17_key(store: &mut dyn Storage) -> StdResult<()> {
@@ -1,12 +1,21 @@
-use cosmwasm_std::testing::{mock_dependencies, mock_env, mock_info, MockApi, MockQuerier, MockStorage};
-use cosmwasm_std::{attr, Addr, Decimal, DepsMut, Empty, OwnedDeps};
-use terp_metadata::Metadata;
+use cosmwasm_std::testing::{
+    mock_dependencies, mock_env, mock_info, MockApi, MockQuerier, MockStorage, MOCK_CONTRACT_ADDR,
+};
+use cosmwasm_std::{
+    attr, from_json, to_json_binary, Addr, ContractInfoResponse, ContractResult, Decimal, DepsMut,
+    Empty, OwnedDeps, SystemResult, WasmQuery,
+};
+use terp_metadata::{Metadata, Trait};
 use terp_sdk::Response;
 
 use tea_hub::error::ContractError;
+use tea_hub::helpers::metadata_hash;
+use tea_hub::metadata::save_tea;
 use tea_hub::state::*;
 use tea_hub::{execute, query};
-use tea::{Tea, MintRule, FeeRate};
+use tea::hub::{CollectionResponse, MetadataHistoryResponse, MetadataVersionResponse};
+use tea::nft::{CollectionInfoResponse, MinterResponse};
+use tea::{Tea, MintRule, FeeRate, Reveal};
 
 mod utils;
 
@@ -51,6 +60,8 @@ fn mock_tea() -> Tea {
         expiry: Some(12345),
         max_supply: Some(100),
         current_supply: 0,
+        reveal: None,
+        nft: None,
     }
 }
 
@@ -94,6 +105,25 @@ fn creating_unavailable_teas() {
         .unwrap_err();
         assert_eq!(err, ContractError::SoldOut);
     }
+
+    // cannot create a tea with invalid metadata
+    {
+        let mut tea = mock_tea();
+        tea.metadata.attributes = Some(vec![Trait {
+            display_type: None,
+            trait_type: "serial".to_string(),
+            value: "1".to_string(),
+        }]);
+
+        let err = execute::create_tea(
+            deps.as_mut(),
+            utils::mock_env_at_timestamp(10000),
+            mock_info("creator", &[]),
+            tea,
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::reserved_trait("serial"));
+    }
 }
 
 #[test]
@@ -113,6 +143,8 @@ fn creating_tea() {
             expiry: Some(12345),
             max_supply: Some(100),
             current_supply: 0,
+            reveal: None,
+            nft: None,
         };
 
         let res = create_tea(deps.as_mut(), &tea);
@@ -146,6 +178,8 @@ fn creating_tea() {
             expiry: None,
             max_supply: None,
             current_supply: 0,
+            reveal: None,
+            nft: None,
         };
 
         let res = create_tea(deps.as_mut(), &tea);
@@ -181,6 +215,7 @@ fn editing_tea() {
             mock_info("jake", &[]),
             1,
             Metadata::default(),
+            false,
         )
         .unwrap_err();
         assert_eq!(err, ContractError::NotManager);
@@ -193,6 +228,7 @@ fn editing_tea() {
             mock_info(tea.manager.as_str(), &[]),
             1,
             Metadata::default(),
+            false,
         )
         .unwrap();
         assert_eq!(res.messages, vec![]);
@@ -202,12 +238,340 @@ fn editing_tea() {
                 attr("action", "tea/hub/edit_tea"),
                 attr("id", "1"),
                 attr("fee", "[]"),
+                attr("metadata_version", "2"),
             ],
         );
 
         let b = query::tea(deps.as_ref(), 1).unwrap();
         assert_eq!(b.metadata, Metadata::default());
     }
+
+    // cannot edit to invalid metadata
+    {
+        let err = execute::edit_tea(
+            deps.as_mut(),
+            mock_info(tea.manager.as_str(), &[]),
+            1,
+            Metadata {
+                image: Some("http://example.com/image.png".to_string()),
+                ..Default::default()
+            },
+            false,
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::disallowed_url_scheme("image", "http"));
+    }
+}
+
+#[test]
+fn recording_metadata_history() {
+    let mut deps = setup_test();
+
+    let tea = mock_tea();
+    create_tea(deps.as_mut(), &tea);
+
+    let first = tea.metadata.clone();
+    let second = Metadata {
+        name: Some("second-name".to_string()),
+        ..Default::default()
+    };
+    let third = Metadata {
+        name: Some("third-name".to_string()),
+        ..Default::default()
+    };
+
+    // the initial metadata is recorded as version 1
+    {
+        let res = query::metadata_history(deps.as_ref(), 1, None, None).unwrap();
+        assert_eq!(
+            res,
+            MetadataHistoryResponse {
+                id: 1,
+                current_version: 1,
+                locked: false,
+                versions: vec![MetadataVersionResponse {
+                    version: 1,
+                    hash: metadata_hash(&first).unwrap(),
+                    metadata: None,
+                }],
+            },
+        );
+    }
+
+    // edit without retaining the previous version, then edit retaining it
+    {
+        execute::edit_tea(deps.as_mut(), mock_info("larry", &[]), 1, second.clone(), false)
+            .unwrap();
+
+        let res =
+            execute::edit_tea(deps.as_mut(), mock_info("larry", &[]), 1, third.clone(), true)
+                .unwrap();
+        assert_eq!(res.attributes[3], attr("metadata_version", "3"));
+
+        let res = query::metadata_history(deps.as_ref(), 1, None, None).unwrap();
+        assert_eq!(res.current_version, 3);
+        assert_eq!(
+            res.versions,
+            vec![
+                MetadataVersionResponse {
+                    version: 1,
+                    hash: metadata_hash(&first).unwrap(),
+                    metadata: None,
+                },
+                MetadataVersionResponse {
+                    version: 2,
+                    hash: metadata_hash(&second).unwrap(),
+                    metadata: Some(second),
+                },
+                MetadataVersionResponse {
+                    version: 3,
+                    hash: metadata_hash(&third).unwrap(),
+                    metadata: None,
+                },
+            ],
+        );
+
+        let res = query::metadata_history(deps.as_ref(), 1, Some(2), None).unwrap();
+        assert_eq!(res.versions.len(), 1);
+        assert_eq!(res.versions[0].version, 3);
+
+        let res = query::tea(deps.as_ref(), 1).unwrap();
+        assert_eq!(res.metadata_version, 3);
+    }
+
+    // tea created before the history was introduced record their metadata as version 1 when
+    // first edited
+    {
+        save_tea(deps.as_mut().storage, 2, &tea).unwrap();
+
+        let res = query::metadata_history(deps.as_ref(), 2, None, None).unwrap();
+        assert_eq!(res.current_version, 1);
+        assert_eq!(res.versions[0].hash, metadata_hash(&first).unwrap());
+
+        execute::edit_tea(deps.as_mut(), mock_info("larry", &[]), 2, third.clone(), false)
+            .unwrap();
+
+        let res = query::metadata_history(deps.as_ref(), 2, None, None).unwrap();
+        assert_eq!(res.current_version, 2);
+        assert_eq!(
+            res.versions.into_iter().map(|v| v.hash).collect::<Vec<_>>(),
+            vec![metadata_hash(&first).unwrap(), metadata_hash(&third).unwrap()],
+        );
+    }
+}
+
+#[test]
+fn locking_metadata() {
+    let mut deps = setup_test();
+
+    let tea = mock_tea();
+    create_tea(deps.as_mut(), &tea);
+
+    // non-manager cannot lock
+    {
+        let err = execute::lock_metadata(deps.as_mut(), mock_info("jake", &[]), 1).unwrap_err();
+        assert_eq!(err, ContractError::NotManager);
+    }
+
+    // manager locks the metadata
+    {
+        let res = execute::lock_metadata(deps.as_mut(), mock_info("larry", &[]), 1).unwrap();
+        assert_eq!(
+            res.attributes,
+            vec![
+                attr("action", "tea/hub/lock_metadata"),
+                attr("id", "1"),
+                attr("metadata_version", "1"),
+            ],
+        );
+
+        let res = query::tea(deps.as_ref(), 1).unwrap();
+        assert!(res.metadata_locked);
+    }
+
+    // locked metadata can no longer be edited
+    {
+        let err = execute::edit_tea(
+            deps.as_mut(),
+            mock_info("larry", &[]),
+            1,
+            Metadata::default(),
+            false,
+        )
+        .unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::MetadataLocked {
+                id: 1,
+            },
+        );
+    }
+}
+
+#[test]
+fn revealing_metadata() {
+    let mut deps = setup_test();
+
+    let secret = Metadata {
+        name: Some("you found the secret room".to_string()),
+        image: Some("ipfs://QmYwAPJzv5CZsnA625s3Xf2nemtYgPpHdWEz79ojWnPbdG".to_string()),
+        ..Default::default()
+    };
+    let reveal = Reveal {
+        placeholder: Metadata {
+            name: Some("mystery tea".to_string()),
+            ..Default::default()
+        },
+        time: 12000,
+        hash: metadata_hash(&secret).unwrap(),
+    };
+
+    // the final metadata must not be given away before the reveal
+    {
+        let err = execute::create_tea(
+            deps.as_mut(),
+            utils::mock_env_at_timestamp(10000),
+            mock_info("creator", &[]),
+            Tea {
+                metadata: secret.clone(),
+                reveal: Some(reveal.clone()),
+                ..mock_tea()
+            },
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::MetadataBeforeReveal);
+    }
+
+    let tea = Tea {
+        metadata: Metadata::default(),
+        reveal: Some(reveal.clone()),
+        ..mock_tea()
+    };
+    create_tea(deps.as_mut(), &tea);
+
+    // the placeholder is exposed, but not the final metadata
+    {
+        let res = query::tea(deps.as_ref(), 1).unwrap();
+        assert_eq!(res.metadata, Metadata::default());
+        assert_eq!(res.reveal, Some(reveal.clone()));
+    }
+
+    // metadata pending a reveal cannot be edited
+    {
+        let err = execute::edit_tea(
+            deps.as_mut(),
+            mock_info("larry", &[]),
+            1,
+            secret.clone(),
+            false,
+        )
+        .unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::RevealPending {
+                id: 1,
+            },
+        );
+    }
+
+    // non-manager cannot reveal
+    {
+        let err = execute::reveal(
+            deps.as_mut(),
+            utils::mock_env_at_timestamp(12000),
+            mock_info("jake", &[]),
+            1,
+            secret.clone(),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::NotManager);
+    }
+
+    // cannot reveal before the reveal time
+    {
+        let err = execute::reveal(
+            deps.as_mut(),
+            utils::mock_env_at_timestamp(11999),
+            mock_info("larry", &[]),
+            1,
+            secret.clone(),
+        )
+        .unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::RevealTooEarly {
+                id: 1,
+                time: 12000,
+            },
+        );
+    }
+
+    // cannot reveal metadata other than the one committed to
+    {
+        let metadata = Metadata {
+            name: Some("not the secret room".to_string()),
+            ..Default::default()
+        };
+        let err = execute::reveal(
+            deps.as_mut(),
+            utils::mock_env_at_timestamp(12000),
+            mock_info("larry", &[]),
+            1,
+            metadata.clone(),
+        )
+        .unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::RevealHashMismatch {
+                expected: reveal.hash.clone(),
+                found: metadata_hash(&metadata).unwrap(),
+            },
+        );
+    }
+
+    // manager reveals the final metadata
+    {
+        let res = execute::reveal(
+            deps.as_mut(),
+            utils::mock_env_at_timestamp(12000),
+            mock_info("larry", &[]),
+            1,
+            secret.clone(),
+        )
+        .unwrap();
+        assert_eq!(
+            res.attributes,
+            vec![
+                attr("action", "tea/hub/reveal"),
+                attr("id", "1"),
+                attr("fee", "[]"),
+                attr("metadata_version", "2"),
+            ],
+        );
+
+        let res = query::tea(deps.as_ref(), 1).unwrap();
+        assert_eq!(res.metadata, secret);
+        assert_eq!(res.reveal, None);
+        assert_eq!(res.metadata_version, 2);
+    }
+
+    // cannot reveal twice
+    {
+        let err = execute::reveal(
+            deps.as_mut(),
+            utils::mock_env_at_timestamp(12000),
+            mock_info("larry", &[]),
+            1,
+            secret,
+        )
+        .unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::NoPendingReveal {
+                id: 1,
+            },
+        );
+    }
 }
 
 #[test]
@@ -324,6 +688,8 @@ fn rejecting_invalid_keys() {
                 expiry: None,
                 max_supply: None,
                 current_supply: 0,
+                reveal: None,
+                nft: None,
             },
         )
         .unwrap_err();
@@ -345,6 +711,8 @@ fn rejecting_invalid_keys() {
                 expiry: None,
                 max_supply: None,
                 current_supply: 0,
+                reveal: None,
+                nft: None,
             },
         )
         .unwrap();
@@ -360,3 +728,208 @@ fn rejecting_invalid_keys() {
         assert_eq!(err, ContractError::InvalidPubkey);
     }
 }
+
+#[test]
+fn rejecting_invalid_burn_inputs() {
+    let mut deps = setup_test();
+
+    create_tea(deps.as_mut(), &mock_tea());
+
+    let create = |deps: DepsMut, inputs: Vec<(u64, u64)>| {
+        execute::create_tea(
+            deps,
+            utils::mock_env_at_timestamp(10000),
+            mock_info("creator", &[]),
+            Tea {
+                rule: MintRule::ByBurn {
+                    inputs,
+                },
+                ..mock_tea()
+            },
+        )
+    };
+
+    let cases = [
+        (vec![], "no inputs"),
+        (vec![(1, 2), (1, 3)], "tea 1 is listed twice"),
+        (vec![(1, 0)], "tea 1 has a count of 0"),
+        (vec![(1, 2), (69, 1)], "tea 69 does not exist"),
+        (vec![(1, 31)], "31 instances exceed the maximum of 30"),
+    ];
+    for (inputs, reason) in cases {
+        let err = create(deps.as_mut(), inputs).unwrap_err();
+        assert_eq!(err, ContractError::invalid_burn_inputs(reason));
+    }
+
+    // a tea forged from five instances of tea 1
+    create(deps.as_mut(), vec![(1, 5)]).unwrap();
+
+    let res = query::tea_by_rule(deps.as_ref(), "by_burn".to_string(), None, None).unwrap();
+    assert_eq!(res.tea.len(), 1);
+    assert_eq!(
+        res.tea[0].rule,
+        MintRule::ByBurn {
+            inputs: vec![(1, 5)],
+        },
+    );
+}
+
+#[test]
+fn creating_tea_on_dedicated_collection() {
+    let mut deps = setup_test();
+
+    // "jake_nft" is a proper collection of jake's; the others have the wrong code, minter or
+    // creator
+    deps.querier.update_wasm(|query| match query {
+        WasmQuery::ContractInfo {
+            contract_addr,
+        } => {
+            let mut info = ContractInfoResponse::default();
+            info.code_id = match contract_addr.as_str() {
+                "forged" => 2,
+                _ => 1,
+            };
+            SystemResult::Ok(ContractResult::Ok(to_json_binary(&info).unwrap()))
+        },
+        WasmQuery::Smart {
+            contract_addr,
+            msg,
+        } => {
+            let (minter, creator) = match contract_addr.as_str() {
+                "jake_nft" | "forged" => (MOCK_CONTRACT_ADDR, "jake"),
+                "bad_minter" => ("jake", "jake"),
+                "bad_creator" => (MOCK_CONTRACT_ADDR, "larry"),
+                _ => panic!("[mock]: unsupported contract: {}", contract_addr),
+            };
+            let res = match from_json(msg).unwrap() {
+                tea::nft::QueryMsg::Minter {} => to_json_binary(&MinterResponse {
+                    minter: Some(minter.to_string()),
+                }),
+                tea::nft::QueryMsg::CollectionInfo {} => to_json_binary(&CollectionInfoResponse {
+                    creator: creator.to_string(),
+                    description: "badges of jake's conference".to_string(),
+                    image: "https://jake.conference/logo.png".to_string(),
+                    external_link: None,
+                    explicit_content: None,
+                    start_trading_time: None,
+                    residual_info: None,
+                }),
+                msg => panic!("[mock]: unsupported nft query: {:?}", msg),
+            };
+            SystemResult::Ok(ContractResult::Ok(res.unwrap()))
+        },
+        _ => panic!("[mock]: unsupported wasm query: {:?}", query),
+    });
+
+    let register = |deps: DepsMut, sender: &str, nft: &str| {
+        execute::register_collection(deps, mock_env(), Addr::unchecked(sender), nft)
+    };
+
+    // the hub's shared nft contract cannot be registered
+    {
+        let err = register(deps.as_mut(), "jake", "nft").unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::NftAlreadyUsed {
+                nft: "nft".to_string(),
+            },
+        );
+    }
+
+    // the collection must run the code of the hub's nft contract, have the hub as its minter and
+    // the sender as its creator
+    {
+        let err = register(deps.as_mut(), "jake", "forged").unwrap_err();
+        assert_eq!(err, ContractError::invalid_collection("forged", "not a tea-nft contract"));
+
+        let err = register(deps.as_mut(), "jake", "bad_minter").unwrap_err();
+        assert_eq!(err, ContractError::invalid_collection("bad_minter", "minter is not the hub"));
+
+        let err = register(deps.as_mut(), "jake", "bad_creator").unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::invalid_collection("bad_creator", "creator is not the sender"),
+        );
+
+        let err = register(deps.as_mut(), "larry", "jake_nft").unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::invalid_collection("jake_nft", "creator is not the sender"),
+        );
+    }
+
+    // properly register
+    {
+        let res = register(deps.as_mut(), "jake", "jake_nft").unwrap();
+        assert_eq!(
+            res.attributes,
+            vec![
+                attr("action", "tea/hub/register_collection"),
+                attr("nft", "jake_nft"),
+                attr("organizer", "jake"),
+            ],
+        );
+
+        let res = query::collection(deps.as_ref(), "jake_nft".to_string()).unwrap();
+        assert_eq!(
+            res,
+            CollectionResponse {
+                nft: "jake_nft".to_string(),
+                organizer: "jake".to_string(),
+            },
+        );
+    }
+
+    // a collection cannot be registered twice, nor become the shared nft contract
+    {
+        let err = register(deps.as_mut(), "jake", "jake_nft").unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::NftAlreadyUsed {
+                nft: "jake_nft".to_string(),
+            },
+        );
+
+        let err =
+            execute::update_nft(deps.as_mut(), mock_env(), Addr::unchecked("larry"), "jake_nft")
+                .unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::NftAlreadyUsed {
+                nft: "jake_nft".to_string(),
+            },
+        );
+    }
+
+    let tea = Tea {
+        nft: Some(Addr::unchecked("jake_nft")),
+        ..mock_tea()
+    };
+
+    // only the organizer who registered the collection can create tea on it
+    {
+        let err = execute::create_tea(
+            deps.as_mut(),
+            utils::mock_env_at_timestamp(10000),
+            mock_info("larry", &[]),
+            tea.clone(),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::NotCollectionOrganizer);
+    }
+
+    // properly create
+    {
+        execute::create_tea(
+            deps.as_mut(),
+            utils::mock_env_at_timestamp(10000),
+            mock_info("jake", &[]),
+            tea.clone(),
+        )
+        .unwrap();
+
+        let res = query::tea(deps.as_ref(), 1).unwrap();
+        assert_eq!(res.nft, Some("jake_nft".to_string()));
+        assert_eq!(res, (1, tea).into());
+    }
+}
@@ -1,11 +1,45 @@
-use cosmwasm_std::testing::mock_dependencies;
-use cosmwasm_std::{attr, Addr, Decimal};
+use cosmwasm_std::testing::{mock_dependencies, mock_env};
+use cosmwasm_std::{
+    attr, to_json_binary, Addr, Binary, Decimal, Reply, SubMsgResponse, SubMsgResult, WasmMsg,
+};
+use terp_sdk::SubMsg;
 
+use tea::hub::{InstantiateNft, PendingNft, PreviousNftResponse};
 use tea::FeeRate;
 
+use tea_hub::contract;
 use tea_hub::error::ContractError;
-use tea_hub::state::{TEA_COUNT, NFT, DEVELOPER};
-use tea_hub::execute;
+use tea_hub::helpers::NFT_REPLY_ID;
+use tea_hub::state::{TEA_COUNT, NFT, DEVELOPER, PENDING_NFT};
+use tea_hub::{execute, query};
+
+mod utils;
+
+fn mock_fee_rate() -> FeeRate {
+    FeeRate {
+        metadata: Decimal::from_ratio(10u128, 1u128),
+        key: Decimal::from_ratio(2u128, 1u128),
+    }
+}
+
+fn mock_collection_info() -> terp721::CollectionInfo<terp721::ResidualInfoResponse> {
+    terp721::CollectionInfo {
+        creator: "larry".to_string(),
+        description: "this is a test".to_string(),
+        image: "https://larry.engineer/logo.png".to_string(),
+        external_link: None,
+        explicit_content: None,
+        start_trading_time: None,
+        residual_info: None,
+    }
+}
+
+/// Protobuf encoding of a `MsgInstantiateContractResponse` carrying only the contract address
+fn instantiate_response_data(contract_addr: &str) -> Binary {
+    let mut data = vec![0x0a, contract_addr.len() as u8];
+    data.extend(contract_addr.as_bytes());
+    data.into()
+}
 
 #[test]
 fn instantiating() {
@@ -13,11 +47,10 @@ fn instantiating() {
 
     let res = execute::init(
         deps.as_mut(),
+        mock_env(),
         Addr::unchecked("larry"),
-        FeeRate {
-            metadata: Decimal::from_ratio(10u128, 1u128),
-            key: Decimal::from_ratio(2u128, 1u128),
-        },
+        mock_fee_rate(),
+        None,
     )
     .unwrap();
     assert_eq!(res.messages, vec![]);
@@ -27,6 +60,97 @@ fn instantiating() {
     assert_eq!(tea_count, 0);
 }
 
+#[test]
+fn instantiating_with_nft() {
+    let mut deps = mock_dependencies();
+
+    // the hub instantiates the nft contract, with itself as the minter
+    {
+        let res = execute::init(
+            deps.as_mut(),
+            mock_env(),
+            Addr::unchecked("larry"),
+            mock_fee_rate(),
+            Some(InstantiateNft {
+                code_id: 69,
+                api_url: "https://tea-api.larry.engineer/metadata".to_string(),
+                collection_info: mock_collection_info(),
+            }),
+        )
+        .unwrap();
+        assert_eq!(
+            res.messages,
+            vec![SubMsg::reply_on_success(
+                WasmMsg::Instantiate {
+                    admin: Some("larry".to_string()),
+                    code_id: 69,
+                    msg: to_json_binary(&tea::nft::InstantiateMsg {
+                        hub: mock_env().contract.address.to_string(),
+                        api_url: "https://tea-api.larry.engineer/metadata".to_string(),
+                        collection_info: mock_collection_info(),
+                        name: None,
+                        symbol: None,
+                    })
+                    .unwrap(),
+                    funds: vec![],
+                    label: "tea-nft".to_string(),
+                },
+                NFT_REPLY_ID,
+            )],
+        );
+
+        let opt = NFT.may_load(deps.as_ref().storage).unwrap();
+        assert!(opt.is_none());
+    }
+
+    // the nft contract's address is recorded from the reply
+    {
+        let reply = Reply {
+            id: NFT_REPLY_ID,
+            result: SubMsgResult::Ok(SubMsgResponse {
+                events: vec![],
+                data: Some(instantiate_response_data("nft")),
+            }),
+        };
+        let res = contract::reply(deps.as_mut(), mock_env(), reply.clone()).unwrap();
+        assert_eq!(
+            res.attributes,
+            vec![attr("action", "tea/hub/nft_instantiated"), attr("nft", "nft")],
+        );
+
+        let nft = NFT.load(deps.as_ref().storage).unwrap();
+        assert_eq!(nft, Addr::unchecked("nft"));
+
+        let err = contract::reply(deps.as_mut(), mock_env(), reply).unwrap_err();
+        assert_eq!(err, ContractError::DoubleInit);
+    }
+
+    // the nft contract can then no longer be set manually
+    {
+        let err = execute::set_nft(deps.as_mut(), Addr::unchecked("larry"), "nft").unwrap_err();
+        assert_eq!(err, ContractError::DoubleInit);
+    }
+}
+
+#[test]
+fn rejecting_malformed_nft_reply() {
+    let mut deps = mock_dependencies();
+
+    let err = contract::reply(
+        deps.as_mut(),
+        mock_env(),
+        Reply {
+            id: NFT_REPLY_ID,
+            result: SubMsgResult::Ok(SubMsgResponse {
+                events: vec![],
+                data: None,
+            }),
+        },
+    )
+    .unwrap_err();
+    assert_eq!(err.code(), "parse_reply");
+}
+
 #[test]
 fn setting_nft() {
     let mut deps = mock_dependencies();
@@ -64,3 +188,202 @@ fn setting_nft() {
         assert_eq!(err, ContractError::DoubleInit);
     }
 }
+
+#[test]
+fn updating_nft() {
+    let mut deps = mock_dependencies();
+
+    DEVELOPER.save(deps.as_mut().storage, &Addr::unchecked("larry")).unwrap();
+    NFT.save(deps.as_mut().storage, &Addr::unchecked("nft")).unwrap();
+
+    // non-developer cannot update nft
+    {
+        let err = execute::update_nft(
+            deps.as_mut(),
+            utils::mock_env_at_timestamp(10000),
+            Addr::unchecked("jake"),
+            "nft2",
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::NotDeveloper);
+    }
+
+    // cannot update to the current nft
+    {
+        let err = execute::update_nft(
+            deps.as_mut(),
+            utils::mock_env_at_timestamp(10000),
+            Addr::unchecked("larry"),
+            "nft",
+        )
+        .unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::NftAlreadyUsed {
+                nft: "nft".to_string(),
+            },
+        );
+    }
+
+    // without a delay, the update is applied immediately
+    {
+        let res = execute::update_nft(
+            deps.as_mut(),
+            utils::mock_env_at_timestamp(10000),
+            Addr::unchecked("larry"),
+            "nft2",
+        )
+        .unwrap();
+        assert_eq!(
+            res.attributes,
+            vec![
+                attr("action", "tea/hub/update_nft"),
+                attr("nft", "nft2"),
+                attr("time", "10000"),
+                attr("previous_nft", "nft"),
+            ],
+        );
+
+        let nft = NFT.load(deps.as_ref().storage).unwrap();
+        assert_eq!(nft, Addr::unchecked("nft2"));
+    }
+
+    // a previous nft cannot be reused
+    {
+        let err = execute::update_nft(
+            deps.as_mut(),
+            utils::mock_env_at_timestamp(10000),
+            Addr::unchecked("larry"),
+            "nft",
+        )
+        .unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::NftAlreadyUsed {
+                nft: "nft".to_string(),
+            },
+        );
+    }
+
+    // with a delay, the update is scheduled
+    {
+        execute::set_nft_update_delay(deps.as_mut(), 86400).unwrap();
+
+        let res = execute::update_nft(
+            deps.as_mut(),
+            utils::mock_env_at_timestamp(20000),
+            Addr::unchecked("larry"),
+            "nft3",
+        )
+        .unwrap();
+        assert_eq!(
+            res.attributes,
+            vec![
+                attr("action", "tea/hub/update_nft"),
+                attr("nft", "nft3"),
+                attr("time", "106400"),
+            ],
+        );
+
+        let nft = NFT.load(deps.as_ref().storage).unwrap();
+        assert_eq!(nft, Addr::unchecked("nft2"));
+
+        let pending = PENDING_NFT.load(deps.as_ref().storage).unwrap();
+        assert_eq!(
+            pending,
+            PendingNft {
+                nft: Addr::unchecked("nft3"),
+                time: 106400,
+            },
+        );
+    }
+
+    // the update cannot be applied before the delay has passed
+    {
+        let err = execute::apply_nft_update(deps.as_mut(), utils::mock_env_at_timestamp(106399))
+            .unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::NftUpdateTooEarly {
+                time: 106400,
+            },
+        );
+    }
+
+    // only the developer can cancel the update
+    {
+        let err = execute::cancel_nft_update(deps.as_mut(), Addr::unchecked("jake")).unwrap_err();
+        assert_eq!(err, ContractError::NotDeveloper);
+
+        let res = execute::cancel_nft_update(deps.as_mut(), Addr::unchecked("larry")).unwrap();
+        assert_eq!(
+            res.attributes,
+            vec![attr("action", "tea/hub/cancel_nft_update"), attr("nft", "nft3")],
+        );
+
+        let err = execute::apply_nft_update(deps.as_mut(), utils::mock_env_at_timestamp(106400))
+            .unwrap_err();
+        assert_eq!(err, ContractError::NoPendingNftUpdate);
+    }
+
+    // anyone can apply the update once the delay has passed
+    {
+        execute::update_nft(
+            deps.as_mut(),
+            utils::mock_env_at_timestamp(20000),
+            Addr::unchecked("larry"),
+            "nft3",
+        )
+        .unwrap();
+
+        let res =
+            execute::apply_nft_update(deps.as_mut(), utils::mock_env_at_timestamp(106400)).unwrap();
+        assert_eq!(
+            res.attributes,
+            vec![
+                attr("action", "tea/hub/apply_nft_update"),
+                attr("nft", "nft3"),
+                attr("previous_nft", "nft2"),
+            ],
+        );
+
+        let nft = NFT.load(deps.as_ref().storage).unwrap();
+        assert_eq!(nft, Addr::unchecked("nft3"));
+
+        let opt = PENDING_NFT.may_load(deps.as_ref().storage).unwrap();
+        assert!(opt.is_none());
+
+        let res = query::previous_nfts(deps.as_ref(), None, None).unwrap();
+        assert_eq!(
+            res.nfts,
+            vec![
+                PreviousNftResponse {
+                    nft: "nft".to_string(),
+                    replaced_at: 10000,
+                },
+                PreviousNftResponse {
+                    nft: "nft2".to_string(),
+                    replaced_at: 106400,
+                },
+            ],
+        );
+    }
+
+    // burns are accepted from the nft contracts of all generations
+    {
+        for nft in ["nft", "nft2", "nft3"] {
+            execute::record_burn(deps.as_mut(), Addr::unchecked(nft), 1, 1, "jake".to_string())
+                .unwrap();
+        }
+
+        let err = execute::record_burn(
+            deps.as_mut(),
+            Addr::unchecked("jake"),
+            1,
+            1,
+            "jake".to_string(),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::NotNft);
+    }
+}
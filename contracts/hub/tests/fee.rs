@@ -2,18 +2,22 @@ use std::collections::BTreeSet;
 
 use cosmwasm_std::testing::{mock_dependencies, mock_info, MockApi, MockQuerier, MockStorage};
 use cosmwasm_std::{
-    coins, to_json_binary, Addr, BankMsg, Decimal, DepsMut, Empty, Event, OwnedDeps, SubMsg, Uint128,
+    attr, coin, coins, to_json_binary, Addr, BankMsg, Coin, Decimal, DepsMut, Empty, Order,
+    OwnedDeps, StdError, SubMsg, Uint128,
 };
 use cw_utils::PaymentError;
 use k256::ecdsa::VerifyingKey;
 use terp_fee::FeeError;
 use terp_metadata::{Metadata, Trait};
-use terp_sdk::{ Response, NATIVE_FEE_DENOM, create_fund_community_pool_msg};
+use terp_sdk::{Response, NATIVE_FEE_DENOM};
 
 use tea_hub::error::ContractError;
+use tea_hub::helpers::metadata_hash;
 use tea_hub::{execute, query};
+use tea_hub::metadata::{load_tea, save_tea};
 use tea_hub::state::*;
-use tea::{Tea, MintRule, FeeRate};
+use tea::hub::{FeeAction, FeeMultiplierResponse};
+use tea::{Tea, BountyRate, MintRule, FeeRate};
 
 mod utils;
 
@@ -36,32 +40,6 @@ fn setup_test() -> OwnedDeps<MockStorage, MockApi, MockQuerier, Empty> {
     deps
 }
 
-fn assert_correct_terp_fee_output(res: &Response, fee_amount: u128) {
-    let dev_amount = fee_amount * 50 / 100;
-    let burn_amount = fee_amount * 50 / 100;
- 
-    assert_eq!(
-        res.messages,
-        vec![
-            SubMsg::new(BankMsg::Burn {
-                amount: coins(burn_amount, NATIVE_FEE_DENOM),
-            }),
-            SubMsg::new(BankMsg::Send {
-                to_address: "larry".to_string(),
-                amount: coins(dev_amount, NATIVE_FEE_DENOM),
-            }),
-            // SubMsg::new(create_fund_community_pool_msg(coins(dist_amount, NATIVE_FEE_DENOM)))
-        ]
-    );
-    assert_eq!(
-        res.events,
-        vec![Event::new("fair-burn")
-            .add_attribute("burn_amount", burn_amount.to_string())
-            .add_attribute("dev", "larry")
-            .add_attribute("dev_amount", dev_amount.to_string())]
-    );
-}
-
 #[test]
 fn tea_creation_fee() {
     let mut deps = setup_test();
@@ -74,6 +52,8 @@ fn tea_creation_fee() {
         expiry: None,
         max_supply: None,
         current_supply: 0,
+        reveal: None,
+        nft: None,
     };
 
     let mut create = |amount: u128, denom: &str| -> Result<Response, ContractError> {
@@ -85,8 +65,8 @@ fn tea_creation_fee() {
         )
     };
 
-    let bytes = to_json_binary(&mock_tea).unwrap();
-    let fee_amount = (Uint128::from(bytes.len() as u128) * mock_fee_rate().metadata).u128();
+    let bytes = utils::tea_bytes(&mock_tea);
+    let fee_amount = (Uint128::from(bytes) * mock_fee_rate().metadata).u128();
 
     // try create without sending a fee, should fail
     {
@@ -114,7 +94,20 @@ fn tea_creation_fee() {
     // try create with correct amount and denom, should succeed
     {
         let res = create(fee_amount, NATIVE_FEE_DENOM).unwrap();
-        assert_correct_terp_fee_output(&res, fee_amount);
+        assert_eq!(res.messages, vec![]);
+    }
+
+    // the fee is held as a deposit
+    {
+        let res = query::deposit(deps.as_ref(), 1).unwrap();
+        assert_eq!(res.metadata_bytes, bytes as u64);
+        assert_eq!(res.metadata_deposit, coins(fee_amount, NATIVE_FEE_DENOM));
+    }
+
+    // the fee should have been recorded
+    {
+        let res = query::stats(deps.as_ref()).unwrap();
+        assert_eq!(res.fees_collected, coins(fee_amount, NATIVE_FEE_DENOM));
     }
 }
 
@@ -164,9 +157,11 @@ fn tea_editing_fee() {
         expiry: None,
         max_supply: None,
         current_supply: 0,
+        reveal: None,
+        nft: None,
     };
 
-    ALL_TEA.save(deps.as_mut().storage, 1, &mock_tea).unwrap();
+    save_tea(deps.as_mut().storage, 1, &mock_tea).unwrap();
 
     // can't use closure here due to borrowing
     fn edit(deps: DepsMut, metadata: &Metadata, amount: u128) -> Result<Response, ContractError> {
@@ -175,6 +170,7 @@ fn tea_editing_fee() {
             mock_info("manager", &coins(amount, NATIVE_FEE_DENOM)),
             1,
             metadata.clone(),
+            false,
         )
     }
 
@@ -185,12 +181,12 @@ fn tea_editing_fee() {
         let res = edit(deps.as_mut(), &metadata, 0).unwrap();
         assert_eq!(res.messages, vec![]);
 
-        let tea = ALL_TEA.load(deps.as_ref().storage, 1).unwrap();
+        let tea = load_tea(deps.as_ref().storage, 1).unwrap();
         assert_eq!(tea.metadata, metadata);
     }
 
     // reset tea
-    ALL_TEA.save(deps.as_mut().storage, 1, &mock_tea).unwrap();
+    save_tea(deps.as_mut().storage, 1, &mock_tea).unwrap();
 
     // calculate the expected fee amount
     let old_bytes = to_json_binary(&old_metadata).unwrap().len() as u128;
@@ -208,7 +204,147 @@ fn tea_editing_fee() {
     // send sufficient fee, should succeed
     {
         let res = edit(deps.as_mut(), &new_metadata, fee_amount).unwrap();
-        assert_correct_terp_fee_output(&res, fee_amount);
+        assert_eq!(res.messages, vec![]);
+
+        let res = query::deposit(deps.as_ref(), 1).unwrap();
+        assert_eq!(res.metadata_bytes, (new_bytes - old_bytes) as u64);
+        assert_eq!(res.metadata_deposit, coins(fee_amount, NATIVE_FEE_DENOM));
+    }
+
+    // shrinking the metadata back refunds the deposit to the manager
+    {
+        let res = edit(deps.as_mut(), &old_metadata, 0).unwrap();
+        assert_eq!(
+            res.messages,
+            vec![SubMsg::new(BankMsg::Send {
+                to_address: "manager".to_string(),
+                amount: coins(fee_amount, NATIVE_FEE_DENOM),
+            })],
+        );
+
+        let res = query::deposit(deps.as_ref(), 1).unwrap();
+        assert_eq!(res.metadata_bytes, 0);
+        assert_eq!(res.metadata_deposit, vec![]);
+    }
+}
+
+#[test]
+fn deduplicating_metadata() {
+    let mut deps = setup_test();
+
+    let metadata = Metadata {
+        name: Some("day 1".to_string()),
+        description: Some("attended the first day of the conference".to_string()),
+        ..Default::default()
+    };
+
+    let mock_tea = Tea {
+        manager: Addr::unchecked("manager"),
+        metadata: metadata.clone(),
+        transferrable: false,
+        rule: MintRule::ByKeys,
+        expiry: None,
+        max_supply: None,
+        current_supply: 0,
+        reveal: None,
+        nft: None,
+    };
+
+    let hash = metadata_hash(&metadata).unwrap();
+    let metadata_bytes = to_json_binary(&metadata).unwrap().len() as u128;
+    let fee_amount = utils::tea_bytes(&mock_tea) * 10;
+    let record_fee = fee_amount - metadata_bytes * 10;
+
+    let create = |deps: DepsMut, manager: &str, amount: u128| {
+        execute::create_tea(
+            deps,
+            utils::mock_env_at_timestamp(10000),
+            mock_info("creator", &coins(amount, NATIVE_FEE_DENOM)),
+            Tea {
+                manager: Addr::unchecked(manager),
+                ..mock_tea.clone()
+            },
+        )
+    };
+
+    // the first tea pays for its record and its metadata
+    {
+        create(deps.as_mut(), "manager", fee_amount).unwrap();
+
+        let res = query::deposit(deps.as_ref(), 1).unwrap();
+        assert_eq!(res.metadata_deposit, coins(fee_amount, NATIVE_FEE_DENOM));
+    }
+
+    // a tea with identical metadata only pays for its record, and shares the stored metadata
+    {
+        let err = create(deps.as_mut(), "curator", record_fee - 1).unwrap_err();
+        assert_eq!(err, FeeError::InsufficientFee(record_fee, record_fee - 1).into());
+
+        create(deps.as_mut(), "curator", record_fee).unwrap();
+
+        let res = query::deposit(deps.as_ref(), 2).unwrap();
+        assert_eq!(res.metadata_deposit, coins(record_fee, NATIVE_FEE_DENOM));
+
+        assert_eq!(METADATA_REFS.load(deps.as_ref().storage, &hash).unwrap(), 2);
+        assert_eq!(METADATA.keys(deps.as_ref().storage, None, None, Order::Ascending).count(), 1);
+
+        let stored_tea = ALL_TEA.load(deps.as_ref().storage, 2).unwrap();
+        assert_eq!(stored_tea.metadata, hash);
+
+        let res = query::tea(deps.as_ref(), 2).unwrap();
+        assert_eq!(res.metadata, metadata);
+    }
+
+    // editing one of the tea does not free the metadata still used by the other, so nothing is
+    // refunded, while the new metadata is charged for
+    let default_fee = to_json_binary(&Metadata::default()).unwrap().len() as u128 * 10;
+    {
+        let res = execute::edit_tea(
+            deps.as_mut(),
+            mock_info("manager", &coins(default_fee, NATIVE_FEE_DENOM)),
+            1,
+            Metadata::default(),
+            false,
+        )
+        .unwrap();
+        assert_eq!(res.messages, vec![]);
+
+        assert_eq!(METADATA_REFS.load(deps.as_ref().storage, &hash).unwrap(), 1);
+
+        let res = query::tea(deps.as_ref(), 2).unwrap();
+        assert_eq!(res.metadata, metadata);
+    }
+
+    // once no tea references the metadata, it is removed from storage, and the tea that paid for
+    // it is refunded, whichever tea releases it
+    {
+        let res = execute::edit_tea(
+            deps.as_mut(),
+            mock_info("curator", &[]),
+            2,
+            Metadata::default(),
+            false,
+        )
+        .unwrap();
+        assert_eq!(
+            res.messages,
+            vec![SubMsg::new(BankMsg::Send {
+                to_address: "manager".to_string(),
+                amount: coins(metadata_bytes * 10, NATIVE_FEE_DENOM),
+            })]
+        );
+
+        let res = query::deposit(deps.as_ref(), 1).unwrap();
+        assert_eq!(res.metadata_deposit, coins(record_fee + default_fee, NATIVE_FEE_DENOM));
+
+        let res = query::deposit(deps.as_ref(), 2).unwrap();
+        assert_eq!(res.metadata_deposit, coins(record_fee, NATIVE_FEE_DENOM));
+
+        assert!(!METADATA_REFS.has(deps.as_ref().storage, &hash));
+        assert!(!METADATA.has(deps.as_ref().storage, &hash));
+
+        let default_hash = metadata_hash(&Metadata::default()).unwrap();
+        assert_eq!(METADATA_REFS.load(deps.as_ref().storage, &default_hash).unwrap(), 2);
     }
 }
 
@@ -221,12 +357,14 @@ fn key_adding_fee() {
         metadata: Metadata::default(),
         transferrable: false,
         rule: MintRule::ByKeys,
-        expiry: None,
+        expiry: Some(12345),
         max_supply: None,
         current_supply: 0,
+        reveal: None,
+        nft: None,
     };
 
-    ALL_TEA.save(deps.as_mut().storage, 1, &mock_tea).unwrap();
+    save_tea(deps.as_mut().storage, 1, &mock_tea).unwrap();
 
     let mock_keys = (1..20)
         .map(|_| {
@@ -269,9 +407,492 @@ fn key_adding_fee() {
     // sending sufficient fee
     {
         let res = add(deps.as_mut(), &mock_keys_set, fee_amount).unwrap();
-        assert_correct_terp_fee_output(&res, fee_amount);
+        assert_eq!(res.messages, vec![]);
 
         let res = query::key(deps.as_ref(), 1, &mock_keys[7]);
         assert!(res.whitelisted);
+
+        let res = query::deposit(deps.as_ref(), 1).unwrap();
+        assert_eq!(res.key_bytes, bytes as u64);
+        assert_eq!(res.key_deposit, coins(fee_amount, NATIVE_FEE_DENOM));
+    }
+
+    // once the tea expires, purging keys refunds the deposit to the manager, proportional to the
+    // number of keys purged
+    {
+        let res = execute::purge_keys(
+            deps.as_mut(),
+            utils::mock_env_at_timestamp(99999),
+            mock_info("jake", &[]),
+            1,
+            Some(10),
+        )
+        .unwrap();
+
+        let refund = Uint128::new(fee_amount).multiply_ratio(10u128, 19u128);
+        assert_eq!(
+            res.messages,
+            vec![SubMsg::new(BankMsg::Send {
+                to_address: "manager".to_string(),
+                amount: coins(refund.u128(), NATIVE_FEE_DENOM),
+            })],
+        );
+
+        let res = query::deposit(deps.as_ref(), 1).unwrap();
+        let remaining = Uint128::new(fee_amount) - refund;
+        assert_eq!(res.key_deposit, coins(remaining.u128(), NATIVE_FEE_DENOM));
+    }
+
+    // purging the remaining keys refunds the remaining deposit
+    {
+        let res = execute::purge_keys(
+            deps.as_mut(),
+            utils::mock_env_at_timestamp(99999),
+            mock_info("jake", &[]),
+            1,
+            None,
+        )
+        .unwrap();
+        assert_eq!(res.messages.len(), 1);
+
+        let res = query::deposit(deps.as_ref(), 1).unwrap();
+        assert_eq!(res.key_bytes, 0);
+        assert_eq!(res.key_deposit, vec![]);
+    }
+}
+
+#[test]
+fn funding_bounty_pool() {
+    let mut deps = setup_test();
+
+    // pool share cannot exceed 100%
+    {
+        let err = execute::set_bounty_rate(
+            deps.as_mut(),
+            BountyRate {
+                pool_share: Decimal::percent(101),
+                per_item: Uint128::new(10),
+            },
+        )
+        .unwrap_err();
+        assert_eq!(err, StdError::generic_err("bounty pool share must not exceed 1"));
+    }
+
+    // retain 20% of fees in the bounty pool
+    {
+        let res = execute::set_bounty_rate(
+            deps.as_mut(),
+            BountyRate {
+                pool_share: Decimal::percent(20),
+                per_item: Uint128::new(10),
+            },
+        )
+        .unwrap();
+        assert_eq!(
+            res.attributes,
+            vec![
+                attr("action", "tea/hub/set_bounty_rate"),
+                attr("pool_share", "0.2"),
+                attr("per_item", "10"),
+            ],
+        );
+    }
+
+    let mock_tea = Tea {
+        manager: Addr::unchecked("manager"),
+        metadata: Metadata::default(),
+        transferrable: false,
+        rule: MintRule::ByKeys,
+        expiry: None,
+        max_supply: None,
+        current_supply: 0,
+        reveal: None,
+        nft: None,
+    };
+
+    let bytes = utils::tea_bytes(&mock_tea);
+    let fee_amount = (Uint128::from(bytes) * mock_fee_rate().metadata).u128();
+    let pool_amount = fee_amount * 20 / 100;
+
+    // the full fee must be paid, but only the part not retained in the pool is fair-burned
+    {
+        let res = execute::create_tea(
+            deps.as_mut(),
+            utils::mock_env_at_timestamp(10000),
+            mock_info("creator", &coins(fee_amount, NATIVE_FEE_DENOM)),
+            mock_tea,
+        )
+        .unwrap();
+        assert_eq!(res.messages, vec![]);
+
+        // the pool's share is not part of the refundable deposit, but credits the tea's bounties
+        let res = query::deposit(deps.as_ref(), 1).unwrap();
+        assert_eq!(res.metadata_deposit, coins(fee_amount - pool_amount, NATIVE_FEE_DENOM));
+        assert_eq!(res.bounty_credit, Uint128::new(pool_amount));
+
+        let cfg = query::config(deps.as_ref()).unwrap();
+        assert_eq!(cfg.bounty_pool, Uint128::new(pool_amount));
+
+        // the full fee is recorded as collected
+        let res = query::stats(deps.as_ref()).unwrap();
+        assert_eq!(res.fees_collected, coins(fee_amount, NATIVE_FEE_DENOM));
+    }
+}
+
+#[test]
+fn paying_fee_in_other_denoms() {
+    let mut deps = setup_test();
+
+    // cannot set the uthiol fee rate this way
+    {
+        let err = execute::set_denom_fee_rate(
+            deps.as_mut(),
+            NATIVE_FEE_DENOM.to_string(),
+            Some(mock_fee_rate()),
+        )
+        .unwrap_err();
+        assert_eq!(err, StdError::generic_err("use `set_fee_rate` to set the fee rate in uthiol"));
+    }
+
+    // accept uatom at 5 uatom per byte of metadata
+    {
+        let res = execute::set_denom_fee_rate(
+            deps.as_mut(),
+            "uatom".to_string(),
+            Some(FeeRate {
+                metadata: Decimal::from_ratio(5u128, 1u128),
+                key: Decimal::one(),
+            }),
+        )
+        .unwrap();
+        assert_eq!(
+            res.attributes,
+            vec![
+                attr("action", "tea/hub/set_denom_fee_rate"),
+                attr("denom", "uatom"),
+                attr("metadata_fee_rate", "5"),
+                attr("key_fee_rate", "1"),
+            ],
+        );
+
+        let cfg = query::config(deps.as_ref()).unwrap();
+        assert_eq!(cfg.denom_fee_rates.keys().collect::<Vec<_>>(), vec!["uatom"]);
+    }
+
+    let mock_tea = Tea {
+        manager: Addr::unchecked("manager"),
+        metadata: Metadata::default(),
+        transferrable: false,
+        rule: MintRule::ByKeys,
+        expiry: None,
+        max_supply: None,
+        current_supply: 0,
+        reveal: None,
+        nft: None,
+    };
+
+    let bytes = utils::tea_bytes(&mock_tea);
+
+    let create = |deps: DepsMut, funds: &[Coin]| {
+        execute::create_tea(
+            deps,
+            utils::mock_env_at_timestamp(10000),
+            mock_info("creator", funds),
+            mock_tea.clone(),
+        )
+    };
+
+    // the fee is charged at the rate of the denom paid in
+    {
+        let err = create(deps.as_mut(), &coins(bytes * 5 - 1, "uatom")).unwrap_err();
+        assert_eq!(err, FeeError::InsufficientFee(bytes * 5, bytes * 5 - 1).into());
+
+        create(deps.as_mut(), &coins(bytes * 5, "uatom")).unwrap();
+
+        let res = query::deposit(deps.as_ref(), 1).unwrap();
+        assert_eq!(res.metadata_deposit, coins(bytes * 5, "uatom"));
+    }
+
+    // cannot pay in more than one denom at once
+    {
+        let funds = [coin(bytes * 5, "uatom"), coin(bytes * 10, NATIVE_FEE_DENOM)];
+        let err = create(deps.as_mut(), &funds).unwrap_err();
+        assert_eq!(err, FeeError::from(PaymentError::MultipleDenoms {}).into());
+    }
+
+    // once no longer accepted, the denom is rejected
+    {
+        execute::set_denom_fee_rate(deps.as_mut(), "uatom".to_string(), None).unwrap();
+
+        let err = create(deps.as_mut(), &coins(bytes * 5, "uatom")).unwrap_err();
+        assert_eq!(err, FeeError::from(PaymentError::ExtraDenom("uatom".into())).into());
+    }
+}
+
+#[test]
+fn estimating_fee() {
+    let mut deps = setup_test();
+
+    DENOM_FEE_RATES
+        .save(
+            deps.as_mut().storage,
+            "uatom",
+            &FeeRate {
+                metadata: Decimal::from_ratio(5u128, 1u128),
+                key: Decimal::one(),
+            },
+        )
+        .unwrap();
+
+    let metadata = Metadata {
+        name: Some("first-tea".to_string()),
+        ..Default::default()
+    };
+
+    // the estimate for creating a tea should be exactly what create_tea charges
+    {
+        let res = query::estimate_fee(
+            deps.as_ref(),
+            FeeAction::CreateTea {
+                manager: "manager".to_string(),
+                metadata: metadata.clone(),
+                transferrable: true,
+                rule: MintRule::ByKeys,
+                expiry: None,
+                max_supply: None,
+                reveal: None,
+                nft: None,
+            },
+            None,
+        )
+        .unwrap();
+
+        let fee = res.fees[0].clone();
+        assert_eq!(fee.denom, NATIVE_FEE_DENOM);
+
+        let tea = Tea {
+            manager: Addr::unchecked("manager"),
+            metadata: metadata.clone(),
+            transferrable: true,
+            rule: MintRule::ByKeys,
+            expiry: None,
+            max_supply: None,
+            current_supply: 0,
+            reveal: None,
+            nft: None,
+        };
+
+        // paying one less than the estimate fails, while paying the estimate succeeds
+        let err = execute::create_tea(
+            deps.as_mut(),
+            utils::mock_env_at_timestamp(10000),
+            mock_info("creator", &coins(fee.amount.u128() - 1, NATIVE_FEE_DENOM)),
+            tea.clone(),
+        )
+        .unwrap_err();
+        assert_eq!(err, FeeError::InsufficientFee(fee.amount.u128(), fee.amount.u128() - 1).into());
+
+        execute::create_tea(
+            deps.as_mut(),
+            utils::mock_env_at_timestamp(10000),
+            mock_info("creator", &[fee]),
+            tea,
+        )
+        .unwrap();
+
+        // the fee in other denoms is quoted at their own rates
+        assert_eq!(res.fees[1].denom, "uatom");
+        assert_eq!(res.fees[1].amount, res.fees[0].amount.multiply_ratio(5u128, 10u128));
+    }
+
+    // editing the metadata into something smaller requires no fee
+    {
+        let res = query::estimate_fee(
+            deps.as_ref(),
+            FeeAction::EditTea {
+                id: 1,
+                metadata: Metadata::default(),
+                retain_previous: false,
+            },
+            None,
+        )
+        .unwrap();
+        assert_eq!(res.fees, vec![coin(0, NATIVE_FEE_DENOM), coin(0, "uatom")]);
+    }
+
+    // retaining the previous metadata in the history is charged for as well, so its blob being
+    // freed no longer offsets the new blob
+    {
+        let bytes = to_json_binary(&Metadata::default()).unwrap().len() as u128;
+
+        let res = query::estimate_fee(
+            deps.as_ref(),
+            FeeAction::EditTea {
+                id: 1,
+                metadata: Metadata::default(),
+                retain_previous: true,
+            },
+            None,
+        )
+        .unwrap();
+        assert_eq!(res.fees[0], coin(bytes * 10, NATIVE_FEE_DENOM));
+    }
+
+    // adding keys is charged at the key rate
+    {
+        let keys = BTreeSet::from(["1234abcd".to_string()]);
+        let bytes = to_json_binary(&keys).unwrap().len() as u128;
+
+        let res = query::estimate_fee(
+            deps.as_ref(),
+            FeeAction::AddKeys {
+                id: 1,
+                keys,
+            },
+            None,
+        )
+        .unwrap();
+        assert_eq!(res.fees, vec![coin(bytes * 2, NATIVE_FEE_DENOM), coin(bytes, "uatom")]);
+    }
+}
+
+#[test]
+fn discounting_fee() {
+    let mut deps = setup_test();
+
+    // multiplier cannot exceed 1
+    {
+        let err = execute::set_fee_multiplier(
+            deps.as_mut(),
+            "university".to_string(),
+            Some(Decimal::percent(150)),
+        )
+        .unwrap_err();
+        assert_eq!(err, StdError::generic_err("fee multiplier must not exceed 1"));
+    }
+
+    // the university pays half, the nonprofit is exempt
+    {
+        let res = execute::set_fee_multiplier(
+            deps.as_mut(),
+            "university".to_string(),
+            Some(Decimal::percent(50)),
+        )
+        .unwrap();
+        assert_eq!(
+            res.attributes,
+            vec![
+                attr("action", "tea/hub/set_fee_multiplier"),
+                attr("address", "university"),
+                attr("multiplier", "0.5"),
+            ],
+        );
+
+        // the developer can do the same as governance
+        let err = execute::set_fee_multiplier_by_developer(
+            deps.as_mut(),
+            Addr::unchecked("jake"),
+            "nonprofit".to_string(),
+            Some(Decimal::zero()),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::NotDeveloper);
+
+        execute::set_fee_multiplier_by_developer(
+            deps.as_mut(),
+            Addr::unchecked("larry"),
+            "nonprofit".to_string(),
+            Some(Decimal::zero()),
+        )
+        .unwrap();
+
+        let res = query::fee_multipliers(deps.as_ref(), None, None).unwrap();
+        assert_eq!(
+            res.multipliers,
+            vec![
+                FeeMultiplierResponse {
+                    address: "nonprofit".to_string(),
+                    multiplier: Decimal::zero(),
+                },
+                FeeMultiplierResponse {
+                    address: "university".to_string(),
+                    multiplier: Decimal::percent(50),
+                },
+            ],
+        );
+
+        let start_after = Some("nonprofit".to_string());
+        let res = query::fee_multipliers(deps.as_ref(), start_after, None).unwrap();
+        assert_eq!(res.multipliers.len(), 1);
+    }
+
+    let mock_tea = Tea {
+        manager: Addr::unchecked("manager"),
+        metadata: Metadata::default(),
+        transferrable: false,
+        rule: MintRule::ByKeys,
+        expiry: None,
+        max_supply: None,
+        current_supply: 0,
+        reveal: None,
+        nft: None,
+    };
+
+    let bytes = utils::tea_bytes(&mock_tea);
+    let fee_amount = (Uint128::from(bytes) * mock_fee_rate().metadata).u128();
+
+    let create = |deps: DepsMut, creator: &str, amount: u128| {
+        execute::create_tea(
+            deps,
+            utils::mock_env_at_timestamp(10000),
+            mock_info(creator, &coins(amount, NATIVE_FEE_DENOM)),
+            mock_tea.clone(),
+        )
+    };
+
+    // the university is charged half of the fee, which the fee estimate reflects
+    {
+        let res = query::estimate_fee(
+            deps.as_ref(),
+            FeeAction::CreateTea {
+                manager: "manager".to_string(),
+                metadata: Metadata::default(),
+                transferrable: false,
+                rule: MintRule::ByKeys,
+                expiry: None,
+                max_supply: None,
+                reveal: None,
+                nft: None,
+            },
+            Some("university".to_string()),
+        )
+        .unwrap();
+        assert_eq!(res.fees, coins(fee_amount / 2, NATIVE_FEE_DENOM));
+
+        let err = create(deps.as_mut(), "university", fee_amount / 2 - 1).unwrap_err();
+        assert_eq!(err, FeeError::InsufficientFee(fee_amount / 2, fee_amount / 2 - 1).into());
+
+        create(deps.as_mut(), "university", fee_amount / 2).unwrap();
+    }
+
+    // the nonprofit pays nothing
+    {
+        let res = create(deps.as_mut(), "nonprofit", 0).unwrap();
+        assert_eq!(res.messages, vec![]);
+
+        let res = query::deposit(deps.as_ref(), 2).unwrap();
+        assert_eq!(res.metadata_deposit, vec![]);
+    }
+
+    // once removed from the registry, the full fee is charged again. the metadata is already
+    // stored, so only the tea's record is charged for
+    {
+        execute::set_fee_multiplier(deps.as_mut(), "university".to_string(), None).unwrap();
+
+        let metadata_bytes = to_json_binary(&mock_tea.metadata).unwrap().len() as u128;
+        let record_fee = fee_amount - metadata_bytes * 10;
+
+        let err = create(deps.as_mut(), "university", record_fee / 2).unwrap_err();
+        assert_eq!(err, FeeError::InsufficientFee(record_fee, record_fee / 2).into());
     }
 }
@@ -0,0 +1,254 @@
+use cosmwasm_std::testing::{mock_dependencies, mock_info, MockApi, MockQuerier, MockStorage};
+use cosmwasm_std::{attr, coins, to_json_binary, Addr, BankMsg, Decimal, Empty, OwnedDeps};
+use terp_fee::FeeError;
+use terp_metadata::Metadata;
+use terp_sdk::{SubMsg, NATIVE_FEE_DENOM};
+
+use tea_hub::error::ContractError;
+use tea_hub::metadata::save_tea;
+use tea_hub::state::*;
+use tea_hub::{execute, query};
+use tea::hub::{FeeAction, SeriesResponse};
+use tea::{FeeRate, MintRule, Series, Tea};
+
+mod utils;
+
+fn setup_test() -> OwnedDeps<MockStorage, MockApi, MockQuerier, Empty> {
+    let mut deps = mock_dependencies();
+
+    DEVELOPER.save(deps.as_mut().storage, &Addr::unchecked("developer")).unwrap();
+    FEE_RATE
+        .save(
+            deps.as_mut().storage,
+            &FeeRate {
+                metadata: Decimal::from_ratio(10u128, 1u128),
+                key: Decimal::zero(),
+            },
+        )
+        .unwrap();
+
+    // tea 1 and 2 are managed by larry, tea 3 by jake
+    for (id, manager) in [(1, "larry"), (2, "larry"), (3, "jake")] {
+        save_tea(
+            deps.as_mut().storage,
+            id,
+            &Tea {
+                manager: Addr::unchecked(manager),
+                metadata: Metadata::default(),
+                transferrable: true,
+                rule: MintRule::ByKeys,
+                expiry: None,
+                max_supply: None,
+                current_supply: 0,
+                reveal: None,
+                nft: None,
+            },
+        )
+        .unwrap();
+    }
+
+    deps
+}
+
+/// Messages fair-burning a fee: half is burned, and the other half sent to the developer
+fn fair_burn_msgs(fee: u128) -> Vec<SubMsg> {
+    vec![
+        SubMsg::new(BankMsg::Burn {
+            amount: coins(fee / 2, NATIVE_FEE_DENOM),
+        }),
+        SubMsg::new(BankMsg::Send {
+            to_address: "developer".to_string(),
+            amount: coins(fee - fee / 2, NATIVE_FEE_DENOM),
+        }),
+    ]
+}
+
+fn mock_series() -> Series {
+    Series {
+        manager: Addr::unchecked("larry"),
+        metadata: Metadata {
+            name: Some("Cosmoverse 2026".to_string()),
+            ..Default::default()
+        },
+        tea: vec![],
+    }
+}
+
+#[test]
+fn grouping_tea_into_series() {
+    let mut deps = setup_test();
+
+    let bytes = to_json_binary(&mock_series()).unwrap().len() as u128;
+
+    // the series is charged a storage fee, as estimated
+    {
+        let res = query::estimate_fee(
+            deps.as_ref(),
+            FeeAction::CreateSeries {
+                manager: "larry".to_string(),
+                metadata: mock_series().metadata,
+            },
+            None,
+        )
+        .unwrap();
+        assert_eq!(res.fees, coins(bytes * 10, NATIVE_FEE_DENOM));
+
+        let err = execute::create_series(deps.as_mut(), mock_info("larry", &[]), mock_series())
+            .unwrap_err();
+        assert_eq!(err, FeeError::InsufficientFee(bytes * 10, 0).into());
+
+        let res = execute::create_series(
+            deps.as_mut(),
+            mock_info("larry", &coins(bytes * 10, NATIVE_FEE_DENOM)),
+            mock_series(),
+        )
+        .unwrap();
+        assert_eq!(res.messages, fair_burn_msgs(bytes * 10));
+        assert_eq!(
+            res.attributes,
+            vec![
+                attr("action", "tea/hub/create_series"),
+                attr("id", "1"),
+                attr("fee", format!("{}{}", bytes * 10, NATIVE_FEE_DENOM)),
+            ],
+        );
+    }
+
+    // the series' metadata is validated the same way as a tea's
+    {
+        let err = execute::create_series(
+            deps.as_mut(),
+            mock_info("larry", &[]),
+            Series {
+                metadata: Metadata {
+                    image: Some("http://example.com/image.png".to_string()),
+                    ..Default::default()
+                },
+                ..mock_series()
+            },
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::disallowed_url_scheme("image", "http"));
+    }
+
+    // only the series' manager can add tea to it
+    {
+        let err = execute::add_to_series(deps.as_mut(), mock_info("jake", &[]), 1, vec![3])
+            .unwrap_err();
+        assert_eq!(err, ContractError::NotSeriesManager);
+    }
+
+    // the series' manager cannot add tea managed by someone else
+    {
+        let err = execute::add_to_series(deps.as_mut(), mock_info("larry", &[]), 1, vec![1, 3])
+            .unwrap_err();
+        assert_eq!(err, ContractError::NotManager);
+    }
+
+    // a tea cannot appear in the series twice
+    {
+        let err = execute::add_to_series(deps.as_mut(), mock_info("larry", &[]), 1, vec![2, 2])
+            .unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::AlreadyInSeries {
+                id: 2,
+                series: 1,
+            },
+        );
+    }
+
+    // the manager adds their tea to the series, paying for the series growing
+    {
+        let new_bytes = to_json_binary(&Series {
+            tea: vec![2, 1],
+            ..mock_series()
+        })
+        .unwrap()
+        .len() as u128;
+        let fee = (new_bytes - bytes) * 10;
+
+        let res = query::estimate_fee(
+            deps.as_ref(),
+            FeeAction::AddToSeries {
+                id: 1,
+                tea: vec![2, 1],
+            },
+            None,
+        )
+        .unwrap();
+        assert_eq!(res.fees, coins(fee, NATIVE_FEE_DENOM));
+
+        let res = execute::add_to_series(
+            deps.as_mut(),
+            mock_info("larry", &coins(fee, NATIVE_FEE_DENOM)),
+            1,
+            vec![2, 1],
+        )
+        .unwrap();
+        assert_eq!(res.messages, fair_burn_msgs(fee));
+        assert_eq!(
+            res.attributes,
+            vec![
+                attr("action", "tea/hub/add_to_series"),
+                attr("id", "1"),
+                attr("fee", format!("{}{}", fee, NATIVE_FEE_DENOM)),
+                attr("tea_added", "2"),
+            ],
+        );
+
+        let res = query::series(deps.as_ref(), 1).unwrap();
+        assert_eq!(
+            res,
+            SeriesResponse {
+                id: 1,
+                manager: "larry".to_string(),
+                metadata: mock_series().metadata,
+                tea: vec![2, 1],
+            },
+        );
+
+        let res = query::tea(deps.as_ref(), 1).unwrap();
+        assert_eq!(res.series, Some(1));
+
+        let res = query::tea(deps.as_ref(), 3).unwrap();
+        assert_eq!(res.series, None);
+    }
+
+    // a tea belongs to at most one series
+    {
+        let series = Series {
+            manager: Addr::unchecked("larry"),
+            metadata: Metadata::default(),
+            tea: vec![],
+        };
+        let fee = to_json_binary(&series).unwrap().len() as u128 * 10;
+        execute::create_series(
+            deps.as_mut(),
+            mock_info("larry", &coins(fee, NATIVE_FEE_DENOM)),
+            series,
+        )
+        .unwrap();
+
+        let err = execute::add_to_series(deps.as_mut(), mock_info("larry", &[]), 2, vec![1])
+            .unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::AlreadyInSeries {
+                id: 1,
+                series: 1,
+            },
+        );
+    }
+
+    // enumerate all series
+    {
+        let res = query::all_series(deps.as_ref(), None, None).unwrap();
+        assert_eq!(res.series.iter().map(|series| series.id).collect::<Vec<_>>(), vec![1, 2]);
+
+        let res = query::all_series(deps.as_ref(), Some(1), None).unwrap();
+        assert_eq!(res.series.len(), 1);
+        assert_eq!(res.series[0].id, 2);
+        assert!(res.series[0].tea.is_empty());
+    }
+}
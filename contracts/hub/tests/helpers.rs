@@ -1,11 +1,12 @@
 use cosmwasm_std::testing::mock_dependencies;
 use cosmwasm_std::Addr;
 use k256::ecdsa::VerifyingKey;
-use terp_metadata::Metadata;
+use terp_metadata::{Metadata, Trait};
 
 use tea_hub::error::ContractError;
 use tea_hub::helpers::*;
 use tea_hub::state::{KEYS, OWNERS};
+use tea::hub::TeaStatus;
 use tea::{Tea, MintRule};
 
 mod utils;
@@ -19,6 +20,8 @@ fn mock_tea(rule: Option<MintRule>, expiry: Option<u64>, max_supply: Option<u64>
         expiry,
         max_supply,
         current_supply: 99,
+        reveal: None,
+        nft: None,
     }
 }
 
@@ -80,6 +83,21 @@ fn asserting_availability_max_supply() {
     assert_eq!(assert_unavailable(&tea, &env.block), Ok(()));
 }
 
+#[test]
+fn evaluating_status() {
+    let mut tea = mock_tea(None, Some(10000), Some(100));
+
+    let env = utils::mock_env_at_timestamp(9999);
+    assert_eq!(tea_status(&tea, &env.block), TeaStatus::Active);
+
+    tea.current_supply = 100;
+    assert_eq!(tea_status(&tea, &env.block), TeaStatus::SoldOut);
+
+    // when both the deadline and the max supply are reached, the tea is considered expired
+    let env = utils::mock_env_at_timestamp(10001);
+    assert_eq!(tea_status(&tea, &env.block), TeaStatus::Expired);
+}
+
 #[test]
 fn asserting_eligible() {
     let mut deps = mock_dependencies();
@@ -210,3 +228,137 @@ fn asserting_can_mint_by_keys() {
         );
     }
 }
+
+#[test]
+fn asserting_valid_metadata() {
+    let metadata = Metadata {
+        image: Some("ipfs://QmYwAPJzv5CZsnA625s3Xf2nemtYgPpHdWEz79ojWnPbdG".to_string()),
+        external_url: Some("https://terp.network/events/1".to_string()),
+        animation_url: Some("ar://bNbA3TEQVL60xlgCcqdz4ZPHFZ711cZ3hmkpGttDt_U".to_string()),
+        name: Some("Terp Network Meetup".to_string()),
+        description: Some("Thanks for coming!".to_string()),
+        attributes: Some(vec![Trait {
+            display_type: None,
+            trait_type: "city".to_string(),
+            value: "Denver".to_string(),
+        }]),
+        ..Default::default()
+    };
+
+    // well-formed metadata
+    {
+        assert_eq!(assert_valid_metadata(&metadata), Ok(()));
+        assert_eq!(assert_valid_metadata(&Metadata::default()), Ok(()));
+    }
+
+    // url that cannot be parsed
+    {
+        let metadata = Metadata {
+            image: Some("not a url".to_string()),
+            ..metadata.clone()
+        };
+        assert_eq!(
+            assert_valid_metadata(&metadata),
+            Err(ContractError::invalid_url("image", url::ParseError::RelativeUrlWithoutBase)),
+        );
+    }
+
+    // url with a scheme that is not allowed
+    {
+        let metadata = Metadata {
+            external_url: Some("javascript:alert(1)".to_string()),
+            ..metadata.clone()
+        };
+        assert_eq!(
+            assert_valid_metadata(&metadata),
+            Err(ContractError::disallowed_url_scheme("external_url", "javascript")),
+        );
+    }
+
+    // plain http is not allowed either
+    {
+        let metadata = Metadata {
+            animation_url: Some("http://terp.network/video.mp4".to_string()),
+            ..metadata.clone()
+        };
+        assert_eq!(
+            assert_valid_metadata(&metadata),
+            Err(ContractError::disallowed_url_scheme("animation_url", "http")),
+        );
+    }
+
+    // text fields that are too long
+    {
+        let metadata = Metadata {
+            name: Some("a".repeat(MAX_NAME_LEN + 1)),
+            ..metadata.clone()
+        };
+        assert_eq!(
+            assert_valid_metadata(&metadata),
+            Err(ContractError::metadata_too_long("name", MAX_NAME_LEN + 1, MAX_NAME_LEN)),
+        );
+    }
+
+    {
+        let metadata = Metadata {
+            description: Some("a".repeat(MAX_DESCRIPTION_LEN + 1)),
+            ..metadata.clone()
+        };
+        assert_eq!(
+            assert_valid_metadata(&metadata),
+            Err(ContractError::metadata_too_long(
+                "description",
+                MAX_DESCRIPTION_LEN + 1,
+                MAX_DESCRIPTION_LEN,
+            )),
+        );
+    }
+
+    // too many traits
+    {
+        let metadata = Metadata {
+            attributes: Some(vec![metadata.attributes.clone().unwrap()[0].clone(); MAX_TRAITS + 1]),
+            ..metadata.clone()
+        };
+        assert_eq!(
+            assert_valid_metadata(&metadata),
+            Err(ContractError::metadata_too_long("attributes", MAX_TRAITS + 1, MAX_TRAITS)),
+        );
+    }
+
+    // trait that is too long
+    {
+        let metadata = Metadata {
+            attributes: Some(vec![Trait {
+                display_type: None,
+                trait_type: "city".to_string(),
+                value: "a".repeat(MAX_TRAIT_LEN + 1),
+            }]),
+            ..metadata.clone()
+        };
+        assert_eq!(
+            assert_valid_metadata(&metadata),
+            Err(ContractError::metadata_too_long(
+                "attributes.value",
+                MAX_TRAIT_LEN + 1,
+                MAX_TRAIT_LEN,
+            )),
+        );
+    }
+
+    // traits added by the nft contract are reserved
+    for trait_type in RESERVED_TRAITS {
+        let metadata = Metadata {
+            attributes: Some(vec![Trait {
+                display_type: None,
+                trait_type: trait_type.to_string(),
+                value: "69".to_string(),
+            }]),
+            ..metadata.clone()
+        };
+        assert_eq!(
+            assert_valid_metadata(&metadata),
+            Err(ContractError::reserved_trait(trait_type)),
+        );
+    }
+}
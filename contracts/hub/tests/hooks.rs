@@ -0,0 +1,411 @@
+use cosmwasm_std::testing::{mock_dependencies, mock_env, MockApi, MockQuerier, MockStorage};
+use cosmwasm_std::{
+    attr, to_json_binary, Addr, Empty, OwnedDeps, Reply, SubMsgResponse, SubMsgResult, WasmMsg,
+};
+use terp_metadata::Metadata;
+use terp_sdk::SubMsg;
+
+use tea_hub::contract;
+use tea_hub::error::ContractError;
+use tea_hub::hooks::{HOOK_REPLY_ID, MAX_HOOKS};
+use tea_hub::metadata::save_tea;
+use tea_hub::state::*;
+use tea_hub::{execute, query};
+use tea::hook::{TeaHookExecuteMsg, TeaHookMsg};
+use tea::hub::HookResponse;
+use tea::{MintRule, Tea};
+
+mod utils;
+
+fn setup_test() -> OwnedDeps<MockStorage, MockApi, MockQuerier, Empty> {
+    let mut deps = mock_dependencies();
+
+    DEVELOPER.save(deps.as_mut().storage, &Addr::unchecked("developer")).unwrap();
+    NFT.save(deps.as_mut().storage, &Addr::unchecked("nft")).unwrap();
+
+    for id in [1, 2] {
+        save_tea(
+            deps.as_mut().storage,
+            id,
+            &Tea {
+                manager: Addr::unchecked("larry"),
+                metadata: Metadata::default(),
+                transferrable: true,
+                rule: MintRule::ByMinter("larry".to_string()),
+                expiry: None,
+                max_supply: None,
+                current_supply: 0,
+                reveal: None,
+                nft: None,
+            },
+        )
+        .unwrap();
+    }
+
+    deps
+}
+
+/// The submessage delivering an event to a hook, as dispatched by the Hub
+fn hook_msg(contract: &str, msg: TeaHookMsg, fatal: bool) -> SubMsg {
+    let wasm_msg = WasmMsg::Execute {
+        contract_addr: contract.to_string(),
+        msg: to_json_binary(&TeaHookExecuteMsg::TeaHook(msg)).unwrap(),
+        funds: vec![],
+    };
+    if fatal {
+        SubMsg::new(wasm_msg)
+    } else {
+        SubMsg::reply_on_error(wasm_msg, HOOK_REPLY_ID)
+    }
+}
+
+#[test]
+fn registering_hooks() {
+    let mut deps = setup_test();
+
+    // only the developer can register hooks for all tea
+    {
+        let err = execute::add_hook(
+            deps.as_mut(),
+            Addr::unchecked("larry"),
+            "points".to_string(),
+            None,
+            false,
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::NotDeveloper);
+    }
+
+    // only the manager can register hooks for their tea
+    {
+        let err = execute::add_hook(
+            deps.as_mut(),
+            Addr::unchecked("developer"),
+            "rewards".to_string(),
+            Some(1),
+            true,
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::NotManager);
+    }
+
+    // properly register hooks
+    {
+        let res = execute::add_hook(
+            deps.as_mut(),
+            Addr::unchecked("developer"),
+            "points".to_string(),
+            None,
+            true,
+        )
+        .unwrap();
+        assert_eq!(
+            res.attributes,
+            vec![
+                attr("action", "tea/hub/add_hook"),
+                attr("id", "undefined"),
+                attr("contract", "points"),
+                attr("fatal", "true"),
+            ],
+        );
+
+        execute::add_hook(
+            deps.as_mut(),
+            Addr::unchecked("larry"),
+            "rewards".to_string(),
+            Some(1),
+            true,
+        )
+        .unwrap();
+    }
+
+    // registering a hook again updates whether it is fatal
+    {
+        execute::add_hook(
+            deps.as_mut(),
+            Addr::unchecked("developer"),
+            "points".to_string(),
+            None,
+            false,
+        )
+        .unwrap();
+
+        let res = query::hooks(deps.as_ref(), None).unwrap();
+        assert_eq!(
+            res.hooks,
+            vec![HookResponse {
+                contract: "points".to_string(),
+                fatal: false,
+            }],
+        );
+
+        let res = query::hooks(deps.as_ref(), Some(1)).unwrap();
+        assert_eq!(
+            res.hooks,
+            vec![HookResponse {
+                contract: "rewards".to_string(),
+                fatal: true,
+            }],
+        );
+
+        let res = query::hooks(deps.as_ref(), Some(2)).unwrap();
+        assert!(res.hooks.is_empty());
+    }
+
+    // the number of hooks is capped
+    {
+        for idx in 1..MAX_HOOKS {
+            execute::add_hook(
+                deps.as_mut(),
+                Addr::unchecked("developer"),
+                format!("hook{}", idx),
+                None,
+                false,
+            )
+            .unwrap();
+        }
+
+        let err = execute::add_hook(
+            deps.as_mut(),
+            Addr::unchecked("developer"),
+            "one_too_many".to_string(),
+            None,
+            false,
+        )
+        .unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::TooManyHooks {
+                max: MAX_HOOKS,
+            },
+        );
+
+        execute::add_hook(
+            deps.as_mut(),
+            Addr::unchecked("developer"),
+            "points".to_string(),
+            None,
+            true,
+        )
+        .unwrap();
+    }
+
+    // unregister hooks
+    {
+        let err = execute::remove_hook(
+            deps.as_mut(),
+            Addr::unchecked("larry"),
+            "points".to_string(),
+            Some(1),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::hook_not_found("points"));
+
+        let res = execute::remove_hook(
+            deps.as_mut(),
+            Addr::unchecked("larry"),
+            "rewards".to_string(),
+            Some(1),
+        )
+        .unwrap();
+        assert_eq!(
+            res.attributes,
+            vec![
+                attr("action", "tea/hub/remove_hook"),
+                attr("id", "1"),
+                attr("contract", "rewards"),
+            ],
+        );
+
+        let res = query::hooks(deps.as_ref(), Some(1)).unwrap();
+        assert!(res.hooks.is_empty());
+    }
+}
+
+#[test]
+fn dispatching_hooks() {
+    let mut deps = setup_test();
+
+    execute::add_hook(
+        deps.as_mut(),
+        Addr::unchecked("developer"),
+        "points".to_string(),
+        None,
+        false,
+    )
+    .unwrap();
+    execute::add_hook(
+        deps.as_mut(),
+        Addr::unchecked("larry"),
+        "rewards".to_string(),
+        Some(1),
+        true,
+    )
+    .unwrap();
+
+    // each instance minted is delivered to the hooks of all tea, then to those of the tea
+    {
+        let res = execute::mint_by_minter(
+            deps.as_mut(),
+            utils::mock_env_at_timestamp(10000),
+            1,
+            utils::btreeset(&["alice", "bob"]),
+            Addr::unchecked("larry"),
+        )
+        .unwrap();
+
+        let minted = |serial: u64, owner: &str| TeaHookMsg::Minted {
+            id: 1,
+            serial,
+            owner: owner.to_string(),
+        };
+        assert_eq!(
+            res.messages[2..],
+            [
+                hook_msg("points", minted(1, "alice"), false),
+                hook_msg("rewards", minted(1, "alice"), true),
+                hook_msg("points", minted(2, "bob"), false),
+                hook_msg("rewards", minted(2, "bob"), true),
+            ],
+        );
+    }
+
+    // tea without hooks of their own are only delivered to the hooks of all tea
+    {
+        let res = execute::mint_by_minter(
+            deps.as_mut(),
+            utils::mock_env_at_timestamp(10000),
+            2,
+            utils::btreeset(&["alice"]),
+            Addr::unchecked("larry"),
+        )
+        .unwrap();
+        assert_eq!(
+            res.messages[1..],
+            [hook_msg(
+                "points",
+                TeaHookMsg::Minted {
+                    id: 2,
+                    serial: 1,
+                    owner: "alice".to_string(),
+                },
+                false,
+            )],
+        );
+    }
+
+    // burns reported by the NFT contract are delivered to the hooks, whose failures never fail
+    // the burn
+    {
+        let res = execute::record_burn(
+            deps.as_mut(),
+            Addr::unchecked("nft"),
+            1,
+            1,
+            "alice".to_string(),
+        )
+        .unwrap();
+
+        let burned = TeaHookMsg::Burned {
+            id: 1,
+            serial: 1,
+            owner: "alice".to_string(),
+        };
+        assert_eq!(
+            res.messages,
+            vec![hook_msg("points", burned.clone(), false), hook_msg("rewards", burned, false)],
+        );
+    }
+
+    // only the NFT contract can report transfers
+    {
+        let err = execute::record_transfer(
+            deps.as_mut(),
+            Addr::unchecked("bob"),
+            1,
+            2,
+            "bob".to_string(),
+            "alice".to_string(),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::NotNft);
+    }
+
+    // transfers reported by the NFT contract are delivered to the hooks, whose failures never
+    // fail the transfer
+    {
+        let res = execute::record_transfer(
+            deps.as_mut(),
+            Addr::unchecked("nft"),
+            1,
+            2,
+            "bob".to_string(),
+            "alice".to_string(),
+        )
+        .unwrap();
+
+        let transferred = TeaHookMsg::Transferred {
+            id: 1,
+            serial: 2,
+            from: "bob".to_string(),
+            to: "alice".to_string(),
+        };
+        assert_eq!(
+            res.messages,
+            vec![
+                hook_msg("points", transferred.clone(), false),
+                hook_msg("rewards", transferred, false),
+            ],
+        );
+        assert_eq!(
+            res.attributes,
+            vec![
+                attr("action", "tea/hub/record_transfer"),
+                attr("id", "1"),
+                attr("serial", "2"),
+                attr("from", "bob"),
+                attr("to", "alice"),
+            ],
+        );
+    }
+}
+
+#[test]
+fn handling_hook_failures() {
+    let mut deps = setup_test();
+
+    // the failure of a hook whose failures are ignored is only reported
+    {
+        let res = contract::reply(
+            deps.as_mut(),
+            mock_env(),
+            Reply {
+                id: HOOK_REPLY_ID,
+                result: SubMsgResult::Err("out of points".to_string()),
+            },
+        )
+        .unwrap();
+        assert!(res.messages.is_empty());
+        assert_eq!(
+            res.attributes,
+            vec![attr("action", "tea/hub/hook_failed"), attr("error", "out of points")],
+        );
+    }
+
+    // replies of unknown ids are rejected
+    {
+        let err = contract::reply(
+            deps.as_mut(),
+            mock_env(),
+            Reply {
+                id: 69,
+                result: SubMsgResult::Ok(SubMsgResponse {
+                    events: vec![],
+                    data: None,
+                }),
+            },
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::InvalidReplyId(69));
+    }
+}
@@ -0,0 +1,373 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use cosmwasm_std::testing::{mock_dependencies, mock_env, mock_info};
+use cosmwasm_std::{attr, to_json_string, Addr, Decimal};
+use cw_storage_plus::Map;
+use terp_metadata::Metadata;
+
+use tea::hub::{ExecuteMsg, MigrateMsg, UpgradeResponse};
+use tea::{FeeRate, MintRule, Tea};
+
+use tea_hub::contract::{self, CONTRACT_NAME, CONTRACT_VERSION};
+use tea_hub::error::ContractError;
+use tea_hub::helpers::metadata_hash;
+use tea_hub::metadata::load_tea;
+use tea_hub::state::{FEE_RATE, KEYS, METADATA_REFS, NFT, OWNERS};
+use tea_hub::{execute, query, upgrades};
+
+fn mock_fee_rate() -> FeeRate {
+    FeeRate {
+        metadata: Decimal::from_ratio(10u128, 1u128),
+        key: Decimal::from_ratio(2u128, 1u128),
+    }
+}
+
+#[test]
+fn planning_upgrades() {
+    let steps = |upgrades: Vec<&upgrades::Upgrade>| {
+        upgrades.into_iter().map(|upgrade| (upgrade.from, upgrade.to)).collect::<Vec<_>>()
+    };
+
+    // v1.1 forgot to bump the version, so hubs deployed before v1.2 report `1.0.0`, which v1.2
+    // recorded as `0.1.0`
+    {
+        let upgrades = upgrades::pending("1.0.0", CONTRACT_VERSION).unwrap();
+        assert_eq!(
+            steps(upgrades),
+            vec![("=1.0.0", "0.1.0"), (">=0.1.0, <0.2.0", "0.2.0")],
+        );
+    }
+
+    // hubs migrated by v1.2 only need the later upgrades
+    {
+        let upgrades = upgrades::pending("0.1.0", CONTRACT_VERSION).unwrap();
+        assert_eq!(steps(upgrades), vec![(">=0.1.0, <0.2.0", "0.2.0")]);
+    }
+
+    // upgrades that go beyond the target version are not applied
+    {
+        let upgrades = upgrades::pending("1.0.0", "0.1.0").unwrap();
+        assert_eq!(steps(upgrades), vec![("=1.0.0", "0.1.0")]);
+    }
+
+    // versions that don't satisfy any upgrade's requirement have nothing to apply
+    {
+        let upgrades = upgrades::pending("0.2.0", "0.3.0").unwrap();
+        assert!(upgrades.is_empty());
+    }
+
+    // cannot downgrade
+    {
+        let err = upgrades::pending("0.3.0", "0.2.0").unwrap_err();
+        assert_eq!(err, ContractError::incorrect_contract_version("<=0.2.0", "0.3.0"));
+
+        let err = upgrades::pending("1.2.0", "0.2.0").unwrap_err();
+        assert_eq!(err, ContractError::incorrect_contract_version("<=0.2.0", "1.2.0"));
+    }
+
+    // versions must be valid semver
+    {
+        let err = upgrades::pending("v1", "1.0.0").unwrap_err();
+        assert!(matches!(err, ContractError::InvalidVersion { version, .. } if version == "v1"));
+    }
+}
+
+#[test]
+fn migrating() {
+    let mut deps = mock_dependencies();
+
+    FEE_RATE.save(deps.as_mut().storage, &mock_fee_rate()).unwrap();
+
+    // wrong contract name
+    {
+        cw2::set_contract_version(deps.as_mut().storage, "crates.io:ngmi", "0.1.0").unwrap();
+
+        let err = contract::migrate(
+            deps.as_mut(),
+            mock_env(),
+            MigrateMsg {
+                fee_rate: None,
+            },
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::incorrect_contract_name(CONTRACT_NAME, "crates.io:ngmi"));
+    }
+
+    // cannot migrate to an older version
+    {
+        cw2::set_contract_version(deps.as_mut().storage, CONTRACT_NAME, "99.0.0").unwrap();
+
+        let err = contract::migrate(
+            deps.as_mut(),
+            mock_env(),
+            MigrateMsg {
+                fee_rate: None,
+            },
+        )
+        .unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::incorrect_contract_version(format!("<={}", CONTRACT_VERSION), "99.0.0"),
+        );
+    }
+
+    // migrate from an older version and update the fee rate
+    {
+        cw2::set_contract_version(deps.as_mut().storage, CONTRACT_NAME, "0.0.1").unwrap();
+
+        let res = query::pending_upgrades(deps.as_ref()).unwrap();
+        assert_eq!(res.current_version, "0.0.1");
+        assert_eq!(res.target_version, CONTRACT_VERSION);
+        assert_eq!(res.upgrades, Vec::<UpgradeResponse>::new());
+
+        let new_fee_rate = FeeRate {
+            metadata: Decimal::from_ratio(20u128, 1u128),
+            key: Decimal::from_ratio(4u128, 1u128),
+        };
+
+        let res = contract::migrate(
+            deps.as_mut(),
+            mock_env(),
+            MigrateMsg {
+                fee_rate: Some(new_fee_rate.clone()),
+            },
+        )
+        .unwrap();
+        assert_eq!(res.messages, vec![]);
+        assert_eq!(
+            res.attributes,
+            vec![
+                attr("action", "tea/hub/migrate"),
+                attr("from_version", "0.0.1"),
+                attr("to_version", CONTRACT_VERSION),
+                attr("upgrades", "0"),
+                attr("metadata_fee_rate", "20"),
+                attr("key_fee_rate", "4"),
+            ],
+        );
+
+        let version = cw2::get_contract_version(deps.as_ref().storage).unwrap();
+        assert_eq!(version.version, CONTRACT_VERSION);
+
+        let fee_rate = FEE_RATE.load(deps.as_ref().storage).unwrap();
+        assert_eq!(fee_rate, new_fee_rate);
+    }
+
+    // migrate a hub deployed before v1.2, which reports `1.0.0`
+    {
+        cw2::set_contract_version(deps.as_mut().storage, CONTRACT_NAME, "1.0.0").unwrap();
+
+        let res = query::pending_upgrades(deps.as_ref()).unwrap();
+        assert_eq!(res.upgrades.len(), 2);
+
+        let res = contract::migrate(
+            deps.as_mut(),
+            mock_env(),
+            MigrateMsg {
+                fee_rate: None,
+            },
+        )
+        .unwrap();
+        assert_eq!(
+            res.attributes,
+            vec![
+                attr("action", "tea/hub/migrate"),
+                attr("from_version", "1.0.0"),
+                attr("to_version", CONTRACT_VERSION),
+                attr("upgrades", "2"),
+            ],
+        );
+
+        let version = cw2::get_contract_version(deps.as_ref().storage).unwrap();
+        assert_eq!(version.version, CONTRACT_VERSION);
+    }
+}
+
+#[test]
+fn backfilling_indexes() {
+    let mut deps = mock_dependencies();
+
+    cw2::set_contract_version(deps.as_mut().storage, CONTRACT_NAME, "0.1.0").unwrap();
+
+    // prior to v0.2, tea were stored in a plain map without indexes
+    let legacy_all_tea: Map<u64, Tea> = Map::new("tea");
+    for id in 1..=3 {
+        legacy_all_tea
+            .save(
+                deps.as_mut().storage,
+                id,
+                &Tea {
+                    manager: Addr::unchecked(if id == 2 { "jake" } else { "larry" }),
+                    metadata: Metadata::default(),
+                    transferrable: true,
+                    rule: MintRule::ByKeys,
+                    expiry: None,
+                    max_supply: None,
+                    current_supply: if id == 2 { 5 } else { 0 },
+                    reveal: None,
+                    nft: None,
+                },
+            )
+            .unwrap();
+    }
+
+    OWNERS.insert(deps.as_mut().storage, (1, "jake")).unwrap();
+    OWNERS.insert(deps.as_mut().storage, (3, "jake")).unwrap();
+    KEYS.insert(deps.as_mut().storage, (3, "1234abcd")).unwrap();
+    KEYS.insert(deps.as_mut().storage, (3, "4321dcba")).unwrap();
+
+    let res = query::tea_by_manager(deps.as_ref(), "larry".to_string(), None, None).unwrap();
+    assert!(res.tea.is_empty());
+
+    let res = query::pending_upgrades(deps.as_ref()).unwrap();
+    assert_eq!(
+        res.upgrades,
+        vec![UpgradeResponse {
+            from: ">=0.1.0, <0.2.0".to_string(),
+            to: "0.2.0".to_string(),
+        }],
+    );
+
+    contract::migrate(
+        deps.as_mut(),
+        mock_env(),
+        MigrateMsg {
+            fee_rate: None,
+        },
+    )
+    .unwrap();
+
+    // the state is backfilled in batches, and the hub cannot be used until it is complete
+    {
+        let err = contract::execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("larry", &[]),
+            ExecuteMsg::LockMetadata {
+                id: 1,
+            },
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::BackfillPending);
+
+        // 3 tea, 2 owners and 2 keys to process
+        for complete in ["false", "false", "true"] {
+            let res = contract::execute(
+                deps.as_mut(),
+                mock_env(),
+                mock_info("anyone", &[]),
+                ExecuteMsg::Backfill {
+                    limit: Some(3),
+                },
+            )
+            .unwrap();
+            assert_eq!(
+                res.attributes,
+                vec![attr("action", "tea/hub/backfill"), attr("complete", complete)],
+            );
+        }
+
+        let err = contract::execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("anyone", &[]),
+            ExecuteMsg::Backfill {
+                limit: None,
+            },
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::NoPendingBackfill);
+    }
+
+    let res = query::tea_by_manager(deps.as_ref(), "larry".to_string(), None, None).unwrap();
+    assert_eq!(res.tea.iter().map(|tea| tea.id).collect::<Vec<_>>(), vec![1, 3]);
+
+    let res = query::tea_by_rule(deps.as_ref(), "by_keys".to_string(), None, None).unwrap();
+    assert_eq!(res.tea.len(), 3);
+
+    let tea = load_tea(deps.as_ref().storage, 2).unwrap();
+    assert_eq!(tea.manager, Addr::unchecked("jake"));
+    assert_eq!(tea.metadata, Metadata::default());
+
+    // the three tea share the same metadata, which is now stored once
+    let hash = metadata_hash(&Metadata::default()).unwrap();
+    assert_eq!(METADATA_REFS.load(deps.as_ref().storage, &hash).unwrap(), 3);
+
+    let res = query::claimed_by(deps.as_ref(), "jake".to_string(), None, None).unwrap();
+    assert_eq!(res.ids, vec![1, 3]);
+
+    let res = query::tea_stats(deps.as_ref(), 3).unwrap();
+    assert_eq!(res.owners, 1);
+    assert_eq!(res.keys_remaining, 2);
+
+    let res = query::tea_stats(deps.as_ref(), 2).unwrap();
+    assert_eq!(res.mints, BTreeMap::from([("by_keys".to_string(), 5)]));
+}
+
+#[test]
+fn migrating_legacy_hub() {
+    let mut deps = mock_dependencies();
+
+    // a hub deployed before v1.2 reports `1.0.0`, and holds tea in the legacy format, with their
+    // metadata inline and without the fields added since
+    cw2::set_contract_version(deps.as_mut().storage, CONTRACT_NAME, "1.0.0").unwrap();
+    FEE_RATE.save(deps.as_mut().storage, &mock_fee_rate()).unwrap();
+    NFT.save(deps.as_mut().storage, &Addr::unchecked("nft")).unwrap();
+
+    let metadata = Metadata {
+        name: Some("legacy-tea".to_string()),
+        ..Default::default()
+    };
+    let legacy_all_tea: Map<u64, Tea> = Map::new("tea");
+    for id in 1..=2 {
+        let record = format!(
+            concat!(
+                r#"{{"manager":"larry","metadata":{},"transferrable":true,"rule":{},"#,
+                r#""expiry":null,"max_supply":null,"current_supply":1}}"#,
+            ),
+            to_json_string(&metadata).unwrap(),
+            to_json_string(&MintRule::ByMinter("larry".to_string())).unwrap(),
+        );
+        deps.as_mut().storage.set(&legacy_all_tea.key(id), record.as_bytes());
+    }
+    OWNERS.insert(deps.as_mut().storage, (1, "jake")).unwrap();
+
+    contract::migrate(
+        deps.as_mut(),
+        mock_env(),
+        MigrateMsg {
+            fee_rate: None,
+        },
+    )
+    .unwrap();
+
+    execute::backfill(deps.as_mut(), None).unwrap();
+
+    let version = cw2::get_contract_version(deps.as_ref().storage).unwrap();
+    assert_eq!(version.version, CONTRACT_VERSION);
+
+    let res = query::tea(deps.as_ref(), 1).unwrap();
+    assert_eq!(res.metadata, metadata);
+    assert_eq!(res.current_supply, 1);
+    assert_eq!(res.reveal, None);
+    assert_eq!(res.nft, None);
+
+    let res = query::all_tea(deps.as_ref(), mock_env(), None, None, None, None).unwrap();
+    assert_eq!(res.tea.iter().map(|tea| tea.id).collect::<Vec<_>>(), vec![1, 2]);
+
+    let res = query::claimed_by(deps.as_ref(), "jake".to_string(), None, None).unwrap();
+    assert_eq!(res.ids, vec![1]);
+
+    // the migrated tea can be minted
+    execute::mint_by_minter(
+        deps.as_mut(),
+        mock_env(),
+        2,
+        BTreeSet::from(["jake".to_string()]),
+        Addr::unchecked("larry"),
+    )
+    .unwrap();
+
+    let tea = load_tea(deps.as_ref().storage, 2).unwrap();
+    assert_eq!(tea.current_supply, 2);
+}
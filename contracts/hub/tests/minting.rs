@@ -1,17 +1,27 @@
 use cosmwasm_std::testing::{mock_dependencies, MockApi, MockQuerier, MockStorage};
-use cosmwasm_std::{attr, to_json_binary, Addr, Empty, OwnedDeps, StdResult, Storage, SubMsg, WasmMsg};
+use cosmwasm_std::{
+    attr, from_json, to_json_binary, Addr, ContractResult, DepsMut, Empty, OwnedDeps, StdResult,
+    Storage, SubMsg, SystemResult, WasmMsg, WasmQuery,
+};
 use k256::ecdsa::{SigningKey, VerifyingKey};
 // use terp721_base::msg::ExecuteMsg::Mint;
 use terp_metadata::Metadata;
 
 use tea_hub::error::ContractError;
 use tea_hub::helpers::{message, token_id};
+use tea_hub::metadata::{load_tea, save_tea};
 use tea_hub::state::*;
 use tea_hub::{execute, query};
+use tea::hub::CanMintResponse;
+use tea::nft::OwnerOfResponse;
 use tea::{Tea, MintRule};
 
 mod utils;
 
+// a valid secp256k1 pubkey that is not whitelisted for any tea
+const KEY_NOT_WHITELISTED: &str =
+    "026f476708bd8fcc8a58bae717ee6922cdefd7917492dbc1a4c2f96d22ba30e470";
+
 /// Return the mock privkey, its corresponding pubkey, and the pubkey in hex encoding
 fn mock_keys() -> (SigningKey, VerifyingKey, String) {
     let privkey = utils::mock_privkey();
@@ -43,42 +53,41 @@ fn setup_test() -> OwnedDeps<MockStorage, MockApi, MockQuerier, Empty> {
         expiry: Some(12345),
         max_supply: Some(100),
         current_supply: 98,
+        reveal: None,
+        nft: None,
     };
 
     let (_, _, pubkey_str) = mock_keys();
 
-    ALL_TEA
-        .save(
-            deps.as_mut().storage,
-            1,
-            &Tea {
-                rule: MintRule::ByMinter("larry".to_string()),
-                ..default_tea.clone()
-            },
-        )
-        .unwrap();
-
-    ALL_TEA
-        .save(
-            deps.as_mut().storage,
-            2,
-            &Tea {
-                rule: MintRule::ByKey(pubkey_str.clone()),
-                ..default_tea.clone()
-            },
-        )
-        .unwrap();
-
-    ALL_TEA
-        .save(
-            deps.as_mut().storage,
-            3,
-            &Tea {
-                rule: MintRule::ByKeys,
-                ..default_tea
-            },
-        )
-        .unwrap();
+    save_tea(
+        deps.as_mut().storage,
+        1,
+        &Tea {
+            rule: MintRule::ByMinter("larry".to_string()),
+            ..default_tea.clone()
+        },
+    )
+    .unwrap();
+
+    save_tea(
+        deps.as_mut().storage,
+        2,
+        &Tea {
+            rule: MintRule::ByKey(pubkey_str.clone()),
+            ..default_tea.clone()
+        },
+    )
+    .unwrap();
+
+    save_tea(
+        deps.as_mut().storage,
+        3,
+        &Tea {
+            rule: MintRule::ByKeys,
+            ..default_tea
+        },
+    )
+    .unwrap();
 
     KEYS.insert(deps.as_mut().storage, (3, &pubkey_str)).unwrap();
 
@@ -182,6 +191,14 @@ fn minting_by_minter() {
                 attr("amount", "2"),
             ],
         );
+
+        // the recipients are not recorded as owners, since the minter may mint to them again, but
+        // can be looked up in the reverse index
+        let res = query::owners(deps.as_ref(), 1, None, None).unwrap();
+        assert!(res.owners.is_empty());
+
+        let res = query::claimed_by(deps.as_ref(), "pumpkin".to_string(), None, None).unwrap();
+        assert_eq!(res.ids, vec![1]);
     }
 }
 
@@ -273,12 +290,15 @@ fn minting_by_key() {
         );
 
         // current supply should have been updated
-        let tea = ALL_TEA.load(deps.as_ref().storage, 2).unwrap();
+        let tea = load_tea(deps.as_ref().storage, 2).unwrap();
         assert_eq!(tea.current_supply, 99);
 
         // larry should be marked as already received
         let res = query::owner(deps.as_ref(), 2, "larry");
         assert!(res.claimed);
+
+        let res = query::claimed_by(deps.as_ref(), "larry".to_string(), None, None).unwrap();
+        assert_eq!(res.ids, vec![2]);
     }
 
     // attempt to mint to the same user
@@ -419,13 +439,16 @@ fn minting_by_keys() {
         );
 
         // current supply should have been updated
-        let tea = ALL_TEA.load(deps.as_ref().storage, 3).unwrap();
+        let tea = load_tea(deps.as_ref().storage, 3).unwrap();
         assert_eq!(tea.current_supply, 99);
 
         // larry should be marked as already received
         let res = query::owner(deps.as_ref(), 3, "larry");
         assert!(res.claimed);
 
+        let res = query::claimed_by(deps.as_ref(), "larry".to_string(), None, None).unwrap();
+        assert_eq!(res.ids, vec![3]);
+
         // the pubkey should be removed from the whitelist
         let res = query::key(deps.as_ref(), 3, &pubkey_str);
         assert!(!res.whitelisted);
@@ -492,3 +515,381 @@ fn minting_by_keys() {
         assert_eq!(err, ContractError::SoldOut);
     }
 }
+
+#[test]
+fn minting_by_burn() {
+    let mut deps = setup_test();
+
+    // tea 4 is forged by burning two instances of tea 1 and one of tea 2
+    save_tea(
+        deps.as_mut().storage,
+        4,
+        &Tea {
+            manager: Addr::unchecked("larry"),
+            metadata: Metadata::default(),
+            transferrable: true,
+            rule: MintRule::ByBurn {
+                inputs: vec![(1, 2), (2, 1)],
+            },
+            expiry: None,
+            max_supply: None,
+            current_supply: 0,
+            reveal: None,
+            nft: None,
+        },
+    )
+    .unwrap();
+
+    // jake owns all tokens but 1|3, which is owned by larry
+    deps.querier.update_wasm(|query| match query {
+        WasmQuery::Smart {
+            contract_addr,
+            msg,
+        } if contract_addr == "nft" => {
+            let owner = match from_json(msg).unwrap() {
+                tea::nft::QueryMsg::OwnerOf {
+                    token_id,
+                    ..
+                } if token_id == "1|3" => "larry",
+                tea::nft::QueryMsg::OwnerOf {
+                    ..
+                } => "jake",
+                msg => panic!("[mock]: unsupported nft query: {:?}", msg),
+            };
+            let res = OwnerOfResponse {
+                owner: owner.to_string(),
+                approvals: vec![],
+            };
+            SystemResult::Ok(ContractResult::Ok(to_json_binary(&res).unwrap()))
+        },
+        _ => panic!("[mock]: unsupported wasm query: {:?}", query),
+    });
+
+    let mint = |deps: DepsMut, id: u64, token_ids: &[&str]| {
+        execute::mint_by_burn(
+            deps,
+            utils::mock_env_at_timestamp(10000),
+            id,
+            utils::btreeset(token_ids),
+            Addr::unchecked("jake"),
+        )
+    };
+
+    // attempt to mint a tea of a different rule
+    {
+        let err = mint(deps.as_mut(), 1, &["1|1", "1|2", "2|1"]).unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::wrong_mint_rule("by_burn", &MintRule::ByMinter("larry".to_string())),
+        );
+    }
+
+    // attempt to burn too few instances of an input
+    {
+        let err = mint(deps.as_mut(), 4, &["1|1", "2|1"]).unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::WrongBurnInputs {
+                id: 1,
+                expected: 2,
+                found: 1,
+            },
+        );
+    }
+
+    // attempt to burn a tea that is not an input
+    {
+        let err = mint(deps.as_mut(), 4, &["1|1", "1|2", "2|1", "3|1"]).unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::WrongBurnInputs {
+                id: 3,
+                expected: 0,
+                found: 1,
+            },
+        );
+    }
+
+    // attempt to burn a token owned by someone else
+    {
+        let err = mint(deps.as_mut(), 4, &["1|1", "1|3", "2|1"]).unwrap_err();
+        assert_eq!(err, ContractError::not_token_owner("1|3", "jake"));
+    }
+
+    // properly mint
+    {
+        let res = mint(deps.as_mut(), 4, &["1|1", "1|2", "2|1"]).unwrap();
+
+        let mut expected = ["1|1", "1|2", "2|1"]
+            .into_iter()
+            .map(|token_id| {
+                SubMsg::new(WasmMsg::Execute {
+                    contract_addr: "nft".to_string(),
+                    msg: to_json_binary(&terp721::ExecuteMsg::<Empty, Empty>::Burn {
+                        token_id: token_id.to_string(),
+                    })
+                    .unwrap(),
+                    funds: vec![],
+                })
+            })
+            .collect::<Vec<_>>();
+        expected.push(SubMsg::new(WasmMsg::Execute {
+            contract_addr: "nft".to_string(),
+            msg: to_json_binary(&terp721::ExecuteMsg::<_, Empty>::Mint {
+                token_id: "4|1".to_string(),
+                owner: "jake".to_string(),
+                token_uri: None,
+                extension: None::<Empty>,
+            })
+            .unwrap(),
+            funds: vec![],
+        }));
+        assert_eq!(res.messages, expected);
+        assert_eq!(
+            res.attributes,
+            vec![
+                attr("action", "tea/hub/mint_by_burn"),
+                attr("id", "4"),
+                attr("serial", "1"),
+                attr("recipient", "jake"),
+                attr("burned", "3"),
+            ],
+        );
+
+        let tea = load_tea(deps.as_ref().storage, 4).unwrap();
+        assert_eq!(tea.current_supply, 1);
+
+        let res = query::owner(deps.as_ref(), 4, "jake");
+        assert!(res.claimed);
+    }
+
+    // attempt to mint to the same user again
+    {
+        let err = mint(deps.as_mut(), 4, &["1|4", "1|5", "2|2"]).unwrap_err();
+        assert_eq!(err, ContractError::already_claimed(4, "jake"));
+    }
+}
+
+#[test]
+fn querying_can_mint() {
+    let mut deps = setup_test();
+
+    let (privkey, _, pubkey_str) = mock_keys();
+
+    let can_mint = |deps: &OwnedDeps<_, _, _>, timestamp, id, pubkey: Option<&str>| {
+        let signature = utils::sign(&privkey, &message(id, "larry"));
+        query::can_mint(
+            deps.as_ref(),
+            utils::mock_env_at_timestamp(timestamp),
+            id,
+            "larry".to_string(),
+            pubkey.map(String::from),
+            signature,
+        )
+        .unwrap()
+    };
+
+    let ok = CanMintResponse {
+        ok: true,
+        reason: None,
+    };
+    let not_ok = |reason: &str| CanMintResponse {
+        ok: false,
+        reason: Some(reason.to_string()),
+    };
+
+    // by key
+    assert_eq!(can_mint(&deps, 10000, 2, None), ok);
+    assert_eq!(can_mint(&deps, 99999, 2, None), not_ok("expired"));
+
+    // by keys
+    assert_eq!(can_mint(&deps, 10000, 3, Some(&pubkey_str)), ok);
+    assert_eq!(can_mint(&deps, 10000, 3, None), not_ok("key_does_not_exist"));
+    assert_eq!(can_mint(&deps, 10000, 3, Some(KEY_NOT_WHITELISTED)), not_ok("key_does_not_exist"));
+
+    // by minter, which can't be claimed with a signature
+    assert_eq!(can_mint(&deps, 10000, 1, None), not_ok("wrong_mint_rule"));
+
+    // a tea that doesn't exist
+    assert_eq!(can_mint(&deps, 10000, 69, None), not_ok("not_found"));
+
+    // a signature for another user
+    {
+        let signature = utils::sign(&privkey, &message(2, "jake"));
+        let res = query::can_mint(
+            deps.as_ref(),
+            utils::mock_env_at_timestamp(10000),
+            2,
+            "larry".to_string(),
+            None,
+            signature,
+        )
+        .unwrap();
+        assert_eq!(res, not_ok("invalid_signature"));
+    }
+
+    // the query doesn't modify state, so the tea can still be minted
+    execute::mint_by_keys(
+        deps.as_mut(),
+        utils::mock_env_at_timestamp(10000),
+        3,
+        "larry".to_string(),
+        pubkey_str.clone(),
+        utils::sign(&privkey, &message(3, "larry")),
+    )
+    .unwrap();
+
+    assert_eq!(can_mint(&deps, 10000, 3, Some(&pubkey_str)), not_ok("already_claimed"));
+
+    set_tea_supply(deps.as_mut().storage, 2, 100);
+    assert_eq!(can_mint(&deps, 10000, 2, None), not_ok("sold_out"));
+}
+
+#[test]
+fn minting_on_dedicated_collection() {
+    let mut deps = setup_test();
+
+    // tea 4 is minted on jake's collection, and tea 5 on the shared one by burning an instance of
+    // each of tea 1 and 4
+    save_tea(
+        deps.as_mut().storage,
+        4,
+        &Tea {
+            manager: Addr::unchecked("jake"),
+            metadata: Metadata::default(),
+            transferrable: true,
+            rule: MintRule::ByMinter("jake".to_string()),
+            expiry: None,
+            max_supply: None,
+            current_supply: 0,
+            reveal: None,
+            nft: Some(Addr::unchecked("jake_nft")),
+        },
+    )
+    .unwrap();
+    save_tea(
+        deps.as_mut().storage,
+        5,
+        &Tea {
+            manager: Addr::unchecked("larry"),
+            metadata: Metadata::default(),
+            transferrable: true,
+            rule: MintRule::ByBurn {
+                inputs: vec![(1, 1), (4, 1)],
+            },
+            expiry: None,
+            max_supply: None,
+            current_supply: 0,
+            reveal: None,
+            nft: None,
+        },
+    )
+    .unwrap();
+
+    // each contract only holds instances of the tea minted on it, all owned by jake
+    deps.querier.update_wasm(|query| match query {
+        WasmQuery::Smart {
+            contract_addr,
+            msg,
+        } => {
+            let token_id = match from_json(msg).unwrap() {
+                tea::nft::QueryMsg::OwnerOf {
+                    token_id,
+                    ..
+                } => token_id,
+                msg => panic!("[mock]: unsupported nft query: {:?}", msg),
+            };
+            let held = match contract_addr.as_str() {
+                "nft" => !token_id.starts_with("4|"),
+                "jake_nft" => token_id.starts_with("4|"),
+                _ => panic!("[mock]: unsupported contract: {}", contract_addr),
+            };
+            if !held {
+                return SystemResult::Ok(ContractResult::Err("token not found".to_string()));
+            }
+            let res = OwnerOfResponse {
+                owner: "jake".to_string(),
+                approvals: vec![],
+            };
+            SystemResult::Ok(ContractResult::Ok(to_json_binary(&res).unwrap()))
+        },
+        _ => panic!("[mock]: unsupported wasm query: {:?}", query),
+    });
+
+    let mint_msg = |nft: &str, token_id: &str| {
+        SubMsg::new(WasmMsg::Execute {
+            contract_addr: nft.to_string(),
+            msg: to_json_binary(&terp721::ExecuteMsg::<_, Empty>::Mint {
+                token_id: token_id.to_string(),
+                owner: "jake".to_string(),
+                token_uri: None,
+                extension: None::<Empty>,
+            })
+            .unwrap(),
+            funds: vec![],
+        })
+    };
+    let burn_msg = |nft: &str, token_id: &str| {
+        SubMsg::new(WasmMsg::Execute {
+            contract_addr: nft.to_string(),
+            msg: to_json_binary(&terp721::ExecuteMsg::<Empty, Empty>::Burn {
+                token_id: token_id.to_string(),
+            })
+            .unwrap(),
+            funds: vec![],
+        })
+    };
+
+    // instances of tea 4 are minted on its collection
+    {
+        let res = execute::mint_by_minter(
+            deps.as_mut(),
+            utils::mock_env_at_timestamp(10000),
+            4,
+            utils::btreeset(&["jake"]),
+            Addr::unchecked("jake"),
+        )
+        .unwrap();
+        assert_eq!(res.messages, vec![mint_msg("jake_nft", "4|1")]);
+    }
+
+    // each input is burned on the contract holding it
+    {
+        let res = execute::mint_by_burn(
+            deps.as_mut(),
+            utils::mock_env_at_timestamp(10000),
+            5,
+            utils::btreeset(&["1|1", "4|1"]),
+            Addr::unchecked("jake"),
+        )
+        .unwrap();
+        assert_eq!(
+            res.messages,
+            vec![burn_msg("nft", "1|1"), burn_msg("jake_nft", "4|1"), mint_msg("nft", "5|1")],
+        );
+    }
+
+    // burns of tea 4 are only accepted from its collection, and those of other tea only from the
+    // shared nft contract
+    {
+        execute::record_burn(deps.as_mut(), Addr::unchecked("jake_nft"), 4, 1, "jake".to_string())
+            .unwrap();
+        execute::record_burn(deps.as_mut(), Addr::unchecked("nft"), 1, 1, "jake".to_string())
+            .unwrap();
+
+        let err =
+            execute::record_burn(deps.as_mut(), Addr::unchecked("nft"), 4, 1, "jake".to_string())
+                .unwrap_err();
+        assert_eq!(err, ContractError::NotNft);
+
+        let err = execute::record_burn(
+            deps.as_mut(),
+            Addr::unchecked("jake_nft"),
+            1,
+            1,
+            "jake".to_string(),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::NotNft);
+    }
+}
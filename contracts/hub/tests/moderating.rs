@@ -0,0 +1,193 @@
+use cosmwasm_std::testing::{mock_dependencies, MockApi, MockQuerier, MockStorage};
+use cosmwasm_std::{attr, Addr, Empty, OwnedDeps, StdError};
+use k256::ecdsa::VerifyingKey;
+use terp_metadata::Metadata;
+
+use tea_hub::error::ContractError;
+use tea_hub::helpers::message;
+use tea_hub::metadata::save_tea;
+use tea_hub::state::*;
+use tea_hub::{execute, query};
+use tea::hub::{CanMintResponse, FlagResponse};
+use tea::{Tea, MintRule};
+
+mod utils;
+
+fn setup_test() -> OwnedDeps<MockStorage, MockApi, MockQuerier, Empty> {
+    let mut deps = mock_dependencies();
+
+    DEVELOPER.save(deps.as_mut().storage, &Addr::unchecked("developer")).unwrap();
+    NFT.save(deps.as_mut().storage, &Addr::unchecked("nft")).unwrap();
+
+    let privkey = utils::mock_privkey();
+    let pubkey_str = hex::encode(VerifyingKey::from(&privkey).to_bytes());
+
+    let default_tea = Tea {
+        manager: Addr::unchecked("larry"),
+        metadata: Metadata::default(),
+        transferrable: true,
+        rule: MintRule::ByMinter("larry".to_string()),
+        expiry: None,
+        max_supply: None,
+        current_supply: 0,
+        reveal: None,
+        nft: None,
+    };
+
+    save_tea(deps.as_mut().storage, 1, &default_tea).unwrap();
+
+    save_tea(
+        deps.as_mut().storage,
+        2,
+        &Tea {
+            rule: MintRule::ByKey(pubkey_str),
+            ..default_tea
+        },
+    )
+    .unwrap();
+
+    deps
+}
+
+#[test]
+fn flagging_tea() {
+    let mut deps = setup_test();
+
+    // non-developer cannot flag
+    {
+        let err = execute::flag_tea_by_developer(
+            deps.as_mut(),
+            Addr::unchecked("larry"),
+            1,
+            "offensive image".to_string(),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::NotDeveloper);
+    }
+
+    // cannot flag a tea that does not exist
+    {
+        let err = execute::flag_tea_by_developer(
+            deps.as_mut(),
+            Addr::unchecked("developer"),
+            69,
+            "offensive image".to_string(),
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::Std(StdError::NotFound { .. })));
+    }
+
+    // developer flags a tea
+    {
+        let res = execute::flag_tea_by_developer(
+            deps.as_mut(),
+            Addr::unchecked("developer"),
+            1,
+            "offensive image".to_string(),
+        )
+        .unwrap();
+        assert_eq!(
+            res.attributes,
+            vec![
+                attr("action", "tea/hub/flag_tea"),
+                attr("id", "1"),
+                attr("reason", "offensive image"),
+            ],
+        );
+
+        let res = query::tea(deps.as_ref(), 1).unwrap();
+        assert_eq!(res.flag_reason, Some("offensive image".to_string()));
+
+        let res = query::tea(deps.as_ref(), 2).unwrap();
+        assert_eq!(res.flag_reason, None);
+    }
+
+    // flagged tea can no longer be minted
+    {
+        let err = execute::mint_by_minter(
+            deps.as_mut(),
+            utils::mock_env_at_timestamp(10000),
+            1,
+            utils::btreeset(&["jake"]),
+            Addr::unchecked("larry"),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::flagged(1, "offensive image"));
+    }
+
+    // governance flags another tea
+    {
+        execute::flag_tea(deps.as_mut(), 2, "impersonation".to_string()).unwrap();
+
+        let res = query::flagged_tea(deps.as_ref(), None, None).unwrap();
+        assert_eq!(
+            res.tea,
+            vec![
+                FlagResponse {
+                    id: 1,
+                    reason: "offensive image".to_string(),
+                },
+                FlagResponse {
+                    id: 2,
+                    reason: "impersonation".to_string(),
+                },
+            ],
+        );
+
+        let res = query::flagged_tea(deps.as_ref(), Some(1), None).unwrap();
+        assert_eq!(res.tea.len(), 1);
+        assert_eq!(res.tea[0].id, 2);
+    }
+
+    // the dry-run reports the flag as the reason the tea cannot be minted
+    let signature = utils::sign(&utils::mock_privkey(), &message(2, "jake"));
+    {
+        let res = query::can_mint(
+            deps.as_ref(),
+            utils::mock_env_at_timestamp(10000),
+            2,
+            "jake".to_string(),
+            None,
+            signature.clone(),
+        )
+        .unwrap();
+        assert_eq!(
+            res,
+            CanMintResponse {
+                ok: false,
+                reason: Some("flagged".to_string()),
+            },
+        );
+    }
+
+    // once unflagged, the tea can be minted again
+    {
+        let err = execute::unflag_tea_by_developer(deps.as_mut(), Addr::unchecked("larry"), 2)
+            .unwrap_err();
+        assert_eq!(err, ContractError::NotDeveloper);
+
+        let res =
+            execute::unflag_tea_by_developer(deps.as_mut(), Addr::unchecked("developer"), 2)
+                .unwrap();
+        assert_eq!(
+            res.attributes,
+            vec![
+                attr("action", "tea/hub/unflag_tea"),
+                attr("id", "2"),
+            ],
+        );
+
+        execute::mint_by_key(
+            deps.as_mut(),
+            utils::mock_env_at_timestamp(10000),
+            2,
+            "jake".to_string(),
+            signature,
+        )
+        .unwrap();
+
+        let res = query::flagged_tea(deps.as_ref(), None, None).unwrap();
+        assert_eq!(res.tea.len(), 1);
+        assert_eq!(res.tea[0].id, 1);
+    }
+}
@@ -1,18 +1,20 @@
-use cosmwasm_std::testing::{mock_dependencies,  MockApi, MockQuerier, MockStorage};
-use cosmwasm_std::{attr, Addr, Empty, OwnedDeps};
+use cosmwasm_std::testing::{mock_dependencies, mock_info, MockApi, MockQuerier, MockStorage};
+use cosmwasm_std::{attr, coins, Addr, BankMsg, Decimal, Empty, OwnedDeps, SubMsg, Uint128};
 use terp_metadata::Metadata;
+use terp_sdk::NATIVE_FEE_DENOM;
 
 use tea_hub::error::ContractError;
+use tea_hub::metadata::save_tea;
 use tea_hub::state::*;
 use tea_hub::{execute, query};
-use tea::{Tea, MintRule};
+use tea::{Tea, BountyRate, Deposit, MintRule};
 
 mod utils;
 
 fn setup_test() -> OwnedDeps<MockStorage, MockApi, MockQuerier, Empty> {
     let mut deps = mock_dependencies();
 
-    ALL_TEA.save(
+    save_tea(
         deps.as_mut().storage,
         1,
         &Tea {
@@ -23,6 +25,8 @@ fn setup_test() -> OwnedDeps<MockStorage, MockApi, MockQuerier, Empty> {
             expiry: Some(12345),
             max_supply: Some(100),
             current_supply: 2,
+            reveal: None,
+            nft: None,
         },
     )
     .unwrap();
@@ -30,8 +34,10 @@ fn setup_test() -> OwnedDeps<MockStorage, MockApi, MockQuerier, Empty> {
     KEYS.insert(deps.as_mut().storage, (1, "1234abcd")).unwrap();
     KEYS.insert(deps.as_mut().storage, (1, "4321dcba")).unwrap();
 
-    OWNERS.insert(deps.as_mut().storage, (1, "jake")).unwrap();
-    OWNERS.insert(deps.as_mut().storage, (1, "pumpkin")).unwrap();
+    for user in ["jake", "pumpkin"] {
+        OWNERS.insert(deps.as_mut().storage, (1, user)).unwrap();
+        CLAIMED.insert(deps.as_mut().storage, (user, 1)).unwrap();
+    }
 
     deps
 }
@@ -45,6 +51,7 @@ fn purging_keys() {
         let err = execute::purge_keys(
             deps.as_mut(),
             utils::mock_env_at_timestamp(10000),
+            mock_info("jake", &[]),
             1,
             None,
         )
@@ -57,6 +64,7 @@ fn purging_keys() {
         let res = execute::purge_keys(
             deps.as_mut(),
             utils::mock_env_at_timestamp(99999),
+            mock_info("jake", &[]),
             1,
             None,
         )
@@ -68,6 +76,7 @@ fn purging_keys() {
                 attr("action", "tea/hub/purge_keys"),
                 attr("id", "1"),
                 attr("keys_purged", "2"),
+                attr("bounty", "0"),
             ],
         );
 
@@ -80,6 +89,7 @@ fn purging_keys() {
         let res = execute::purge_keys(
             deps.as_mut(),
             utils::mock_env_at_timestamp(99999),
+            mock_info("jake", &[]),
             1,
             None,
         )
@@ -91,6 +101,7 @@ fn purging_keys() {
                 attr("action", "tea/hub/purge_keys"),
                 attr("id", "1"),
                 attr("keys_purged", "0"), // no-op
+                attr("bounty", "0"),
             ],
         );
     }
@@ -105,6 +116,7 @@ fn purging_owners() {
         let err = execute::purge_owners(
             deps.as_mut(),
             utils::mock_env_at_timestamp(10000),
+            mock_info("jake", &[]),
             1,
             None,
         )
@@ -117,6 +129,7 @@ fn purging_owners() {
         let res = execute::purge_owners(
             deps.as_mut(),
             utils::mock_env_at_timestamp(99999),
+            mock_info("jake", &[]),
             1,
             None,
         )
@@ -128,11 +141,16 @@ fn purging_owners() {
                 attr("action", "tea/hub/purge_owners"),
                 attr("id", "1"),
                 attr("owners_purged", "2"),
+                attr("bounty", "0"),
             ],
         );
 
         let res = query::owners(deps.as_ref(), 1, None, None).unwrap();
         assert_eq!(res.owners.len(), 0);
+
+        // the reverse index should also have been purged
+        let res = query::claimed_by(deps.as_ref(), "jake".to_string(), None, None).unwrap();
+        assert!(res.ids.is_empty());
     }
 
     // purging again should result in no-op
@@ -140,6 +158,7 @@ fn purging_owners() {
         let res = execute::purge_owners(
             deps.as_mut(),
             utils::mock_env_at_timestamp(99999),
+            mock_info("jake", &[]),
             1,
             None,
         )
@@ -151,7 +170,252 @@ fn purging_owners() {
                 attr("action", "tea/hub/purge_owners"),
                 attr("id", "1"),
                 attr("owners_purged", "0"), // no-op
+                attr("bounty", "0"),
+            ],
+        );
+    }
+}
+#[test]
+fn purging_expired() {
+    let mut deps = setup_test();
+
+    // tea 2 never expires, so it remains available
+    // tea 3 has expired at the same time as tea 1
+    let teas = [
+        (2, None, vec![]),
+        (3, Some(12345), vec!["jake", "doge", "larry"]),
+    ];
+    for (id, expiry, owners) in teas {
+        save_tea(
+            deps.as_mut().storage,
+            id,
+            &Tea {
+                manager: Addr::unchecked("larry"),
+                metadata: Metadata::default(),
+                transferrable: true,
+                rule: MintRule::ByKeys,
+                expiry,
+                max_supply: None,
+                current_supply: owners.len() as u64,
+                reveal: None,
+                nft: None,
+            },
+        )
+        .unwrap();
+
+        for user in owners {
+            OWNERS.insert(deps.as_mut().storage, (id, user)).unwrap();
+            CLAIMED.insert(deps.as_mut().storage, (user, id)).unwrap();
+        }
+    }
+    KEYS.insert(deps.as_mut().storage, (2, "abcd1234")).unwrap();
+    TEA_COUNT.save(deps.as_mut().storage, &3).unwrap();
+
+    // the budget runs out while purging tea 1; the cursor stays at tea 1
+    {
+        let res = execute::purge_expired(
+            deps.as_mut(),
+            utils::mock_env_at_timestamp(99999),
+            mock_info("jake", &[]),
+            Some(3),
+        )
+        .unwrap();
+        assert_eq!(res.messages, vec![]);
+        assert_eq!(
+            res.attributes,
+            vec![
+                attr("action", "tea/hub/purge_expired"),
+                attr("keys_purged", "2"),
+                attr("owners_purged", "1"),
+                attr("cursor", "1"),
+                attr("bounty", "0"),
+            ],
+        );
+
+        let res = query::owners(deps.as_ref(), 1, None, None).unwrap();
+        assert_eq!(res.owners, vec!["pumpkin".to_string()]);
+    }
+
+    // the next call finishes tea 1, skips tea 2 which is still available, and purges tea 3.
+    // having visited all tea, the cursor wraps around to the first tea
+    {
+        let res = execute::purge_expired(
+            deps.as_mut(),
+            utils::mock_env_at_timestamp(99999),
+            mock_info("jake", &[]),
+            None,
+        )
+        .unwrap();
+        assert_eq!(
+            res.attributes,
+            vec![
+                attr("action", "tea/hub/purge_expired"),
+                attr("keys_purged", "0"),
+                attr("owners_purged", "4"),
+                attr("cursor", "1"),
+                attr("bounty", "0"),
             ],
         );
+
+        for id in [1, 3] {
+            let res = query::owners(deps.as_ref(), id, None, None).unwrap();
+            assert!(res.owners.is_empty());
+        }
+
+        let res = query::claimed_by(deps.as_ref(), "jake".to_string(), None, None).unwrap();
+        assert!(res.ids.is_empty());
+
+        let res = query::keys(deps.as_ref(), 2, None, None).unwrap();
+        assert_eq!(res.keys, vec!["abcd1234".to_string()]);
+
+        let res = query::stats(deps.as_ref()).unwrap();
+        assert_eq!(res.keys_purged, 2);
+        assert_eq!(res.owners_purged, 5);
     }
-}
\ No newline at end of file
+
+    // a single call may remove more items than the limit of `PurgeKeys` and `PurgeOwners`
+    {
+        for i in 0..50 {
+            let user = format!("user{}", i);
+            OWNERS.insert(deps.as_mut().storage, (3, &user)).unwrap();
+        }
+
+        let res = execute::purge_expired(
+            deps.as_mut(),
+            utils::mock_env_at_timestamp(99999),
+            mock_info("jake", &[]),
+            None,
+        )
+        .unwrap();
+        assert_eq!(res.attributes[2], attr("owners_purged", "50"));
+    }
+}
+
+#[test]
+fn paying_bounty() {
+    let mut deps = setup_test();
+
+    // 10 uthiol is paid per item purged. the 2 keys of tea 1 have a deposit of 50 uthiol, and the
+    // fees of tea 1 only put 10 uthiol into the pool
+    BOUNTY_RATE
+        .save(
+            deps.as_mut().storage,
+            &BountyRate {
+                pool_share: Decimal::percent(20),
+                per_item: Uint128::new(10),
+            },
+        )
+        .unwrap();
+    BOUNTY_POOL.save(deps.as_mut().storage, &Uint128::new(50)).unwrap();
+    DEPOSITS
+        .save(
+            deps.as_mut().storage,
+            1,
+            &Deposit {
+                key_bytes: 20,
+                key_deposit: coins(50, NATIVE_FEE_DENOM),
+                bounty_credit: Uint128::new(10),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+    KEY_COUNT.save(deps.as_mut().storage, 1, &2).unwrap();
+
+    // purging 2 keys pays 20 uthiol out of their deposit, the rest of which is refunded to the
+    // manager, leaving the pool untouched
+    {
+        let res = execute::purge_keys(
+            deps.as_mut(),
+            utils::mock_env_at_timestamp(99999),
+            mock_info("doge", &[]),
+            1,
+            None,
+        )
+        .unwrap();
+        assert_eq!(
+            res.messages,
+            vec![
+                SubMsg::new(BankMsg::Send {
+                    to_address: "larry".to_string(),
+                    amount: coins(30, NATIVE_FEE_DENOM),
+                }),
+                SubMsg::new(BankMsg::Send {
+                    to_address: "doge".to_string(),
+                    amount: coins(20, NATIVE_FEE_DENOM),
+                }),
+            ],
+        );
+        assert_eq!(res.attributes[3], attr("bounty", "20"));
+
+        let pool = BOUNTY_POOL.load(deps.as_ref().storage).unwrap();
+        assert_eq!(pool, Uint128::new(50));
+
+        let res = query::deposit(deps.as_ref(), 1).unwrap();
+        assert!(res.key_deposit.is_empty());
+    }
+
+    // purging 2 owners should pay 20 uthiol, but only 10 uthiol is left of the tea's credit
+    {
+        let res = execute::purge_owners(
+            deps.as_mut(),
+            utils::mock_env_at_timestamp(99999),
+            mock_info("doge", &[]),
+            1,
+            None,
+        )
+        .unwrap();
+        assert_eq!(
+            res.messages,
+            vec![SubMsg::new(BankMsg::Send {
+                to_address: "doge".to_string(),
+                amount: coins(10, NATIVE_FEE_DENOM),
+            })],
+        );
+        assert_eq!(res.attributes[3], attr("bounty", "10"));
+
+        let pool = BOUNTY_POOL.load(deps.as_ref().storage).unwrap();
+        assert_eq!(pool, Uint128::new(40));
+
+        let res = query::deposit(deps.as_ref(), 1).unwrap();
+        assert_eq!(res.bounty_credit, Uint128::zero());
+    }
+
+    // purging data of a tea whose fees put nothing into the pool pays no bounty, even though the
+    // pool still holds funds from other tea
+    {
+        save_tea(
+            deps.as_mut().storage,
+            2,
+            &Tea {
+                manager: Addr::unchecked("doge"),
+                metadata: Metadata::default(),
+                transferrable: true,
+                rule: MintRule::ByMinter("doge".to_string()),
+                expiry: Some(12345),
+                max_supply: None,
+                current_supply: 3,
+                reveal: None,
+                nft: None,
+            },
+        )
+        .unwrap();
+        for user in ["alice", "bob", "charlie"] {
+            OWNERS.insert(deps.as_mut().storage, (2, user)).unwrap();
+        }
+
+        let res = execute::purge_owners(
+            deps.as_mut(),
+            utils::mock_env_at_timestamp(99999),
+            mock_info("doge", &[]),
+            2,
+            None,
+        )
+        .unwrap();
+        assert_eq!(res.messages, vec![]);
+        assert_eq!(res.attributes[2], attr("owners_purged", "3"));
+        assert_eq!(res.attributes[3], attr("bounty", "0"));
+
+        let pool = BOUNTY_POOL.load(deps.as_ref().storage).unwrap();
+        assert_eq!(pool, Uint128::new(40));
+    }
+}
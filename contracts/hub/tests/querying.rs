@@ -0,0 +1,313 @@
+use cosmwasm_std::testing::{mock_dependencies, MockApi, MockQuerier, MockStorage};
+use cosmwasm_std::{attr, Addr, Empty, OwnedDeps};
+use terp_metadata::Metadata;
+
+use tea_hub::error::ContractError;
+use tea_hub::{execute, query};
+use tea_hub::metadata::save_tea;
+use tea_hub::state::*;
+use tea::hub::{IssuerResponse, OrderBy, TeaStatus};
+use tea::{Tea, MintRule};
+
+mod utils;
+
+fn mock_tea(manager: &str, rule: MintRule) -> Tea {
+    Tea {
+        manager: Addr::unchecked(manager),
+        metadata: Metadata::default(),
+        transferrable: true,
+        rule,
+        expiry: Some(12345),
+        max_supply: Some(100),
+        current_supply: 0,
+        reveal: None,
+        nft: None,
+    }
+}
+
+fn setup_test() -> OwnedDeps<MockStorage, MockApi, MockQuerier, Empty> {
+    let mut deps = mock_dependencies();
+
+    DEVELOPER.save(deps.as_mut().storage, &Addr::unchecked("developer")).unwrap();
+
+    let all_tea = [
+        mock_tea("larry", MintRule::ByKeys),
+        mock_tea("jake", MintRule::by_minter("jake")),
+        mock_tea("larry", MintRule::by_minter("pumpkin")),
+        mock_tea("jake", MintRule::ByKeys),
+        mock_tea("larry", MintRule::by_key("1234abcd")),
+    ];
+
+    for (idx, tea) in all_tea.iter().enumerate() {
+        save_tea(deps.as_mut().storage, idx as u64 + 1, tea).unwrap();
+    }
+
+    deps
+}
+
+fn ids(res: tea::hub::AllTeaResponse) -> Vec<u64> {
+    res.tea.into_iter().map(|tea| tea.id).collect()
+}
+
+#[test]
+fn querying_tea_by_manager() {
+    let deps = setup_test();
+
+    let res = query::tea_by_manager(deps.as_ref(), "larry".to_string(), None, None).unwrap();
+    assert_eq!(ids(res), vec![1, 3, 5]);
+
+    let res = query::tea_by_manager(deps.as_ref(), "larry".to_string(), Some(1), Some(1)).unwrap();
+    assert_eq!(ids(res), vec![3]);
+
+    let res = query::tea_by_manager(deps.as_ref(), "jake".to_string(), None, None).unwrap();
+    assert_eq!(ids(res), vec![2, 4]);
+
+    let res = query::tea_by_manager(deps.as_ref(), "pumpkin".to_string(), None, None).unwrap();
+    assert_eq!(ids(res), Vec::<u64>::new());
+}
+
+#[test]
+fn querying_tea_by_rule() {
+    let mut deps = setup_test();
+
+    let res = query::tea_by_rule(deps.as_ref(), "by_minter".to_string(), None, None).unwrap();
+    assert_eq!(ids(res), vec![2, 3]);
+
+    let res = query::tea_by_rule(deps.as_ref(), "by_keys".to_string(), Some(1), None).unwrap();
+    assert_eq!(ids(res), vec![4]);
+
+    let res = query::tea_by_rule(deps.as_ref(), "by_key".to_string(), None, None).unwrap();
+    assert_eq!(ids(res), vec![5]);
+
+    // indexes are updated along with the tea
+    save_tea(deps.as_mut().storage, 5, &mock_tea("jake", MintRule::ByKeys)).unwrap();
+
+    let res = query::tea_by_rule(deps.as_ref(), "by_key".to_string(), None, None).unwrap();
+    assert_eq!(ids(res), Vec::<u64>::new());
+
+    let res = query::tea_by_manager(deps.as_ref(), "jake".to_string(), None, None).unwrap();
+    assert_eq!(ids(res), vec![2, 4, 5]);
+}
+
+#[test]
+fn querying_claimed_by() {
+    let mut deps = setup_test();
+
+    for (id, user) in [(1, "jake"), (2, "jake"), (2, "pumpkin"), (4, "jake")] {
+        OWNERS.insert(deps.as_mut().storage, (id, user)).unwrap();
+        CLAIMED.insert(deps.as_mut().storage, (user, id)).unwrap();
+    }
+
+    let res = query::claimed_by(deps.as_ref(), "jake".to_string(), None, None).unwrap();
+    assert_eq!(res.user, "jake");
+    assert_eq!(res.ids, vec![1, 2, 4]);
+
+    let res = query::claimed_by(deps.as_ref(), "jake".to_string(), Some(1), Some(1)).unwrap();
+    assert_eq!(res.ids, vec![2]);
+
+    let res = query::claimed_by(deps.as_ref(), "pumpkin".to_string(), None, None).unwrap();
+    assert_eq!(res.ids, vec![2]);
+
+    let res = query::claimed_by(deps.as_ref(), "larry".to_string(), None, None).unwrap();
+    assert!(res.ids.is_empty());
+}
+
+#[test]
+fn querying_all_tea() {
+    let mut deps = setup_test();
+
+    // tea 2 is sold out, tea 4 never expires
+    ALL_TEA
+        .update(deps.as_mut().storage, 2, |tea| -> cosmwasm_std::StdResult<_> {
+            let mut tea = tea.unwrap();
+            tea.current_supply = 100;
+            Ok(tea)
+        })
+        .unwrap();
+    ALL_TEA
+        .update(deps.as_mut().storage, 4, |tea| -> cosmwasm_std::StdResult<_> {
+            let mut tea = tea.unwrap();
+            tea.expiry = None;
+            Ok(tea)
+        })
+        .unwrap();
+
+    let before_expiry = utils::mock_env_at_timestamp(10000);
+    let after_expiry = utils::mock_env_at_timestamp(99999);
+
+    // ascending, no filter
+    {
+        let res = query::all_tea(deps.as_ref(), before_expiry.clone(), None, None, Some(2), None)
+            .unwrap();
+        assert_eq!(res.next, Some(2));
+        assert_eq!(ids(res), vec![1, 2]);
+
+        let res =
+            query::all_tea(deps.as_ref(), before_expiry.clone(), None, Some(2), Some(2), None)
+                .unwrap();
+        assert_eq!(ids(res), vec![3, 4]);
+    }
+
+    // descending, using the cursor as the upper bound
+    {
+        let res = query::all_tea(
+            deps.as_ref(),
+            before_expiry.clone(),
+            None,
+            None,
+            Some(2),
+            Some(OrderBy::Descending),
+        )
+        .unwrap();
+        assert_eq!(ids(res), vec![5, 4]);
+
+        let res = query::all_tea(
+            deps.as_ref(),
+            before_expiry.clone(),
+            None,
+            Some(4),
+            Some(2),
+            Some(OrderBy::Descending),
+        )
+        .unwrap();
+        assert_eq!(ids(res), vec![3, 2]);
+    }
+
+    // filter by status
+    {
+        let status = Some(TeaStatus::Active);
+        let res = query::all_tea(
+            deps.as_ref(),
+            before_expiry.clone(),
+            status,
+            None,
+            None,
+            Some(OrderBy::Descending),
+        )
+        .unwrap();
+        assert_eq!(ids(res), vec![5, 4, 3, 1]);
+
+        let status = Some(TeaStatus::SoldOut);
+        let res = query::all_tea(deps.as_ref(), before_expiry, status, None, None, None).unwrap();
+        assert_eq!(ids(res), vec![2]);
+
+        let status = Some(TeaStatus::Active);
+        let res =
+            query::all_tea(deps.as_ref(), after_expiry.clone(), status, None, None, None).unwrap();
+        assert_eq!(ids(res), vec![4]);
+
+        let status = Some(TeaStatus::Expired);
+        let res =
+            query::all_tea(deps.as_ref(), after_expiry.clone(), status, Some(1), Some(2), None)
+                .unwrap();
+        assert_eq!(res.next, Some(3));
+        assert_eq!(ids(res), vec![2, 3]);
+    }
+
+    // a filter matching few tea stops after going through `MAX_SCAN` tea, returning the cursor
+    // to resume from
+    {
+        let last_id = 5 + query::MAX_SCAN as u64 + 1;
+        for id in 6..=last_id {
+            let mut tea = mock_tea("larry", MintRule::ByKeys);
+            if id == last_id {
+                tea.expiry = None;
+            }
+            save_tea(deps.as_mut().storage, id, &tea).unwrap();
+        }
+
+        let status = Some(TeaStatus::Active);
+        let res =
+            query::all_tea(deps.as_ref(), after_expiry.clone(), status, None, None, None).unwrap();
+        assert_eq!(res.next, Some(query::MAX_SCAN as u64));
+        assert_eq!(ids(res), vec![4]);
+
+        let start_after = Some(query::MAX_SCAN as u64);
+        let res =
+            query::all_tea(deps.as_ref(), after_expiry, status, start_after, None, None).unwrap();
+        assert_eq!(res.next, None);
+        assert_eq!(ids(res), vec![last_id]);
+    }
+}
+
+#[test]
+fn verifying_issuers() {
+    let mut deps = setup_test();
+
+    // no issuer is verified to begin with
+    {
+        let res = query::tea(deps.as_ref(), 1).unwrap();
+        assert!(!res.issuer_verified);
+    }
+
+    // non-developer cannot verify an issuer
+    {
+        let err = execute::set_issuer_verified_by_developer(
+            deps.as_mut(),
+            Addr::unchecked("larry"),
+            "larry".to_string(),
+            true,
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::NotDeveloper);
+    }
+
+    // developer verifies an issuer
+    {
+        let res = execute::set_issuer_verified_by_developer(
+            deps.as_mut(),
+            Addr::unchecked("developer"),
+            "larry".to_string(),
+            true,
+        )
+        .unwrap();
+        assert_eq!(
+            res.attributes,
+            vec![
+                attr("action", "tea/hub/set_issuer_verified"),
+                attr("address", "larry"),
+                attr("verified", "true"),
+            ],
+        );
+
+        let res = query::issuer(deps.as_ref(), "larry".to_string()).unwrap();
+        assert_eq!(
+            res,
+            IssuerResponse {
+                address: "larry".to_string(),
+                verified: true,
+            },
+        );
+    }
+
+    // governance verifies another issuer
+    {
+        execute::set_issuer_verified(deps.as_mut(), "jake".to_string(), true).unwrap();
+
+        let res = query::verified_issuers(deps.as_ref(), None, None).unwrap();
+        assert_eq!(res.issuers, vec!["jake".to_string(), "larry".to_string()]);
+
+        let res = query::verified_issuers(deps.as_ref(), Some("jake".to_string()), None).unwrap();
+        assert_eq!(res.issuers, vec!["larry".to_string()]);
+    }
+
+    // tea responses reflect the managers' status
+    {
+        let res = query::tea(deps.as_ref(), 1).unwrap();
+        assert!(res.issuer_verified);
+
+        let res = query::tea_by_manager(deps.as_ref(), "jake".to_string(), None, None).unwrap();
+        assert!(res.tea.iter().all(|tea| tea.issuer_verified));
+    }
+
+    // once an issuer is removed from the registry, its existing tea are no longer marked verified
+    {
+        execute::set_issuer_verified(deps.as_mut(), "larry".to_string(), false).unwrap();
+
+        let res = query::tea(deps.as_ref(), 1).unwrap();
+        assert!(!res.issuer_verified);
+
+        let res = query::verified_issuers(deps.as_ref(), None, None).unwrap();
+        assert_eq!(res.issuers, vec!["jake".to_string()]);
+    }
+}
@@ -0,0 +1,277 @@
+use cosmwasm_std::testing::{mock_dependencies, mock_info, MockApi, MockQuerier, MockStorage};
+use cosmwasm_std::{attr, Addr, DepsMut, Empty, OwnedDeps};
+use terp_metadata::Metadata;
+
+use tea_hub::error::ContractError;
+use tea_hub::helpers::{MAX_CATEGORY_LEN, MAX_WEIGHT};
+use tea_hub::metadata::save_tea;
+use tea_hub::state::*;
+use tea_hub::{execute, query};
+use tea::hub::ScoreResponse;
+use tea::{MintRule, Tea, Weight};
+
+mod utils;
+
+fn setup_test() -> OwnedDeps<MockStorage, MockApi, MockQuerier, Empty> {
+    let mut deps = mock_dependencies();
+
+    DEVELOPER.save(deps.as_mut().storage, &Addr::unchecked("developer")).unwrap();
+    NFT.save(deps.as_mut().storage, &Addr::unchecked("nft")).unwrap();
+    VERIFIED_ISSUERS.insert(deps.as_mut().storage, &Addr::unchecked("larry")).unwrap();
+
+    // tea 1 to 3 are managed by a verified issuer, tea 4 isn't
+    for (id, manager) in [(1, "larry"), (2, "larry"), (3, "larry"), (4, "jake")] {
+        save_tea(
+            deps.as_mut().storage,
+            id,
+            &Tea {
+                manager: Addr::unchecked(manager),
+                metadata: Metadata::default(),
+                transferrable: true,
+                rule: MintRule::ByMinter(manager.to_string()),
+                expiry: None,
+                max_supply: None,
+                current_supply: 0,
+                reveal: None,
+                nft: None,
+            },
+        )
+        .unwrap();
+    }
+
+    deps
+}
+
+fn weight(weight: u64, category: &str) -> Weight {
+    Weight {
+        weight,
+        category: category.to_string(),
+    }
+}
+
+fn mint(deps: DepsMut, id: u64, owners: &[&str]) {
+    let minter = if id == 4 {
+        "jake"
+    } else {
+        "larry"
+    };
+    execute::mint_by_minter(
+        deps,
+        utils::mock_env_at_timestamp(10000),
+        id,
+        utils::btreeset(owners),
+        Addr::unchecked(minter),
+    )
+    .unwrap();
+}
+
+fn score(user: &str, category: Option<&str>, score: u64) -> ScoreResponse {
+    ScoreResponse {
+        user: user.to_string(),
+        category: category.map(String::from),
+        score,
+    }
+}
+
+#[test]
+fn setting_weights() {
+    let mut deps = setup_test();
+
+    // only the manager can set the weight
+    {
+        let err = execute::set_weight(
+            deps.as_mut(),
+            mock_info("jake", &[]),
+            1,
+            weight(10, "governance"),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::NotManager);
+    }
+
+    // the category must be a short identifier
+    {
+        let long = "a".repeat(MAX_CATEGORY_LEN + 1);
+        for category in ["", "Governance", "governance!", &long] {
+            let err = execute::set_weight(
+                deps.as_mut(),
+                mock_info("larry", &[]),
+                1,
+                weight(10, category),
+            )
+            .unwrap_err();
+            assert_eq!(err, ContractError::invalid_category(category, MAX_CATEGORY_LEN));
+        }
+    }
+
+    // the weight is capped
+    {
+        let err = execute::set_weight(
+            deps.as_mut(),
+            mock_info("larry", &[]),
+            1,
+            weight(MAX_WEIGHT + 1, "governance"),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::weight_too_high(MAX_WEIGHT + 1, MAX_WEIGHT));
+    }
+
+    // properly set the weight
+    {
+        let res = execute::set_weight(
+            deps.as_mut(),
+            mock_info("larry", &[]),
+            1,
+            weight(10, "governance"),
+        )
+        .unwrap();
+        assert_eq!(
+            res.attributes,
+            vec![
+                attr("action", "tea/hub/set_weight"),
+                attr("id", "1"),
+                attr("weight", "10"),
+                attr("category", "governance"),
+                attr("global", "true"),
+            ],
+        );
+
+        let res = query::tea(deps.as_ref(), 1).unwrap();
+        assert_eq!(res.weight, Some(weight(10, "governance")));
+
+        let res = query::tea(deps.as_ref(), 2).unwrap();
+        assert_eq!(res.weight, None);
+    }
+
+    // the weight can no longer be changed once an instance has been minted
+    {
+        mint(deps.as_mut(), 1, &["alice"]);
+
+        let err = execute::set_weight(
+            deps.as_mut(),
+            mock_info("larry", &[]),
+            1,
+            weight(20, "governance"),
+        )
+        .unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::WeightLocked {
+                id: 1,
+            },
+        );
+    }
+}
+
+#[test]
+fn scoring_holders() {
+    let mut deps = setup_test();
+
+    // tea 1 and 2 are weighted in different categories, tea 3 is not weighted
+    execute::set_weight(deps.as_mut(), mock_info("larry", &[]), 1, weight(10, "governance"))
+        .unwrap();
+    execute::set_weight(deps.as_mut(), mock_info("larry", &[]), 2, weight(3, "education"))
+        .unwrap();
+
+    mint(deps.as_mut(), 1, &["alice", "bob"]);
+    mint(deps.as_mut(), 2, &["alice", "charlie"]);
+    mint(deps.as_mut(), 3, &["charlie"]);
+
+    // scores are the sum of the weights of the instances held, in total and by category
+    {
+        let cases = [
+            ("alice", None, 13),
+            ("alice", Some("governance"), 10),
+            ("alice", Some("education"), 3),
+            ("bob", None, 10),
+            ("bob", Some("education"), 0),
+            ("charlie", None, 3),
+            ("dave", None, 0),
+        ];
+        for (user, category, expected) in cases {
+            let res = query::score(deps.as_ref(), user.to_string(), category.map(String::from))
+                .unwrap();
+            assert_eq!(res, score(user, category, expected));
+        }
+    }
+
+    // users are ranked by score, in descending order
+    {
+        let res = query::leaderboard(deps.as_ref(), None, None, None).unwrap();
+        assert_eq!(
+            res.scores,
+            vec![score("alice", None, 13), score("bob", None, 10), score("charlie", None, 3)],
+        );
+
+        let res = query::leaderboard(deps.as_ref(), None, Some((10, "bob".to_string())), None)
+            .unwrap();
+        assert_eq!(res.scores, vec![score("charlie", None, 3)]);
+
+        let res = query::leaderboard(deps.as_ref(), Some("governance".to_string()), None, Some(1))
+            .unwrap();
+        assert_eq!(res.scores, vec![score("bob", Some("governance"), 10)]);
+    }
+
+    // burning an instance deducts its weight from the owner's score
+    {
+        execute::record_burn(deps.as_mut(), Addr::unchecked("nft"), 1, 1, "alice".to_string())
+            .unwrap();
+
+        let res = query::score(deps.as_ref(), "alice".to_string(), None).unwrap();
+        assert_eq!(res.score, 3);
+
+        let res = query::leaderboard(deps.as_ref(), Some("governance".to_string()), None, None)
+            .unwrap();
+        assert_eq!(res.scores, vec![score("bob", Some("governance"), 10)]);
+    }
+
+    // transferring an instance moves its weight from the sender to the recipient
+    {
+        execute::record_transfer(
+            deps.as_mut(),
+            Addr::unchecked("nft"),
+            2,
+            2,
+            "charlie".to_string(),
+            "bob".to_string(),
+        )
+        .unwrap();
+
+        let res = query::leaderboard(deps.as_ref(), None, None, None).unwrap();
+        assert_eq!(res.scores, vec![score("bob", None, 13), score("alice", None, 3)]);
+
+        let res = query::leaderboard(deps.as_ref(), Some("education".to_string()), None, None)
+            .unwrap();
+        assert_eq!(
+            res.scores,
+            vec![score("bob", Some("education"), 3), score("alice", Some("education"), 3)],
+        );
+    }
+
+    // the weight of a tea whose manager isn't verified only counts within its category
+    {
+        let res = execute::set_weight(
+            deps.as_mut(),
+            mock_info("jake", &[]),
+            4,
+            weight(MAX_WEIGHT, "governance"),
+        )
+        .unwrap();
+        assert!(res.attributes.contains(&attr("global", "false")));
+
+        mint(deps.as_mut(), 4, &["dave"]);
+
+        let res = query::score(deps.as_ref(), "dave".to_string(), None).unwrap();
+        assert_eq!(res, score("dave", None, 0));
+
+        let res = query::score(deps.as_ref(), "dave".to_string(), Some("governance".to_string()))
+            .unwrap();
+        assert_eq!(res, score("dave", Some("governance"), MAX_WEIGHT));
+
+        execute::record_burn(deps.as_mut(), Addr::unchecked("nft"), 4, 1, "dave".to_string())
+            .unwrap();
+
+        let res = query::leaderboard(deps.as_ref(), None, None, None).unwrap();
+        assert_eq!(res.scores, vec![score("bob", None, 13), score("alice", None, 3)]);
+    }
+}
@@ -0,0 +1,247 @@
+use std::collections::BTreeMap;
+
+use cosmwasm_std::testing::{mock_dependencies, mock_info, MockApi, MockQuerier, MockStorage};
+use cosmwasm_std::{attr, Addr, Decimal, Empty, OwnedDeps};
+use k256::ecdsa::VerifyingKey;
+use terp_metadata::Metadata;
+
+use tea_hub::error::ContractError;
+use tea_hub::helpers::message;
+use tea_hub::state::*;
+use tea_hub::{execute, query};
+use tea::hub::ActiveTeaCountResponse;
+use tea::{Tea, MintRule, FeeRate};
+
+mod utils;
+
+const KEY_1: &str = "026f476708bd8fcc8a58bae717ee6922cdefd7917492dbc1a4c2f96d22ba30e470";
+
+fn setup_test() -> OwnedDeps<MockStorage, MockApi, MockQuerier, Empty> {
+    let mut deps = mock_dependencies();
+
+    DEVELOPER.save(deps.as_mut().storage, &Addr::unchecked("larry")).unwrap();
+    NFT.save(deps.as_mut().storage, &Addr::unchecked("nft")).unwrap();
+    TEA_COUNT.save(deps.as_mut().storage, &0).unwrap();
+    FEE_RATE
+        .save(
+            deps.as_mut().storage,
+            &FeeRate {
+                metadata: Decimal::zero(),
+                key: Decimal::zero(),
+            },
+        )
+        .unwrap();
+
+    // tea 1 can be minted by minter, and sells out after 3 mints
+    // tea 2 can be minted by keys, and never expires
+    for (rule, expiry, max_supply) in [
+        (MintRule::by_minter("larry"), Some(12345), Some(3)),
+        (MintRule::ByKeys, None, None),
+    ] {
+        execute::create_tea(
+            deps.as_mut(),
+            utils::mock_env_at_timestamp(10000),
+            mock_info("creator", &[]),
+            Tea {
+                manager: Addr::unchecked("larry"),
+                metadata: Metadata::default(),
+                transferrable: true,
+                rule,
+                expiry,
+                max_supply,
+                current_supply: 0,
+                reveal: None,
+                nft: None,
+            },
+        )
+        .unwrap();
+    }
+
+    deps
+}
+
+#[test]
+fn tracking_stats() {
+    let mut deps = setup_test();
+
+    let privkey = utils::mock_privkey();
+    let pubkey_str = hex::encode(VerifyingKey::from(&privkey).to_bytes());
+
+    execute::mint_by_minter(
+        deps.as_mut(),
+        utils::mock_env_at_timestamp(10000),
+        1,
+        utils::btreeset(&["jake", "pumpkin", "doge"]),
+        Addr::unchecked("larry"),
+    )
+    .unwrap();
+
+    execute::add_keys(
+        deps.as_mut(),
+        utils::mock_env_at_timestamp(10000),
+        mock_info("larry", &[]),
+        2,
+        utils::btreeset(&[KEY_1, &pubkey_str]),
+    )
+    .unwrap();
+
+    execute::mint_by_keys(
+        deps.as_mut(),
+        utils::mock_env_at_timestamp(10000),
+        2,
+        "jake".to_string(),
+        pubkey_str,
+        utils::sign(&privkey, &message(2, "jake")),
+    )
+    .unwrap();
+
+    // tea 1 has sold out, tea 2 is still active
+    {
+        let res = query::stats(deps.as_ref()).unwrap();
+        assert_eq!(res.tea_created, 2);
+        assert_eq!(res.tea_minted, 4);
+        assert_eq!(res.fees_collected, vec![]);
+        assert_eq!(res.keys_purged, 0);
+        assert_eq!(res.owners_purged, 0);
+
+        let res =
+            query::active_tea_count(deps.as_ref(), utils::mock_env_at_timestamp(10000), None, None)
+                .unwrap();
+        assert_eq!(
+            res,
+            ActiveTeaCountResponse {
+                count: 1,
+                next: None,
+            },
+        );
+
+        // recipients of minter mints are not counted as owners
+        let res = query::tea_stats(deps.as_ref(), 1).unwrap();
+        assert_eq!(res.mints, BTreeMap::from([("by_minter".to_string(), 3)]));
+        assert_eq!(res.owners, 0);
+
+        let res = query::tea_stats(deps.as_ref(), 2).unwrap();
+        assert_eq!(res.mints, BTreeMap::from([("by_keys".to_string(), 1)]));
+        assert_eq!(res.keys_remaining, 1);
+        assert_eq!(res.owners, 1);
+    }
+
+    // purging the owners of tea 1 leaves the recipients of its minter mints alone
+    {
+        execute::purge_owners(
+            deps.as_mut(),
+            utils::mock_env_at_timestamp(10000),
+            mock_info("jake", &[]),
+            1,
+            None,
+        )
+        .unwrap();
+
+        let res = query::stats(deps.as_ref()).unwrap();
+        assert_eq!(res.owners_purged, 0);
+
+        let res = query::claimed_by(deps.as_ref(), "pumpkin".to_string(), None, None).unwrap();
+        assert_eq!(res.ids, vec![1]);
+    }
+
+    // only the nft contract can report burns
+    {
+        let err = execute::record_burn(deps.as_mut(), Addr::unchecked("jake"), 1, 1, "jake".into())
+            .unwrap_err();
+        assert_eq!(err, ContractError::NotNft);
+
+        let res = execute::record_burn(deps.as_mut(), Addr::unchecked("nft"), 1, 1, "jake".into())
+            .unwrap();
+        assert_eq!(
+            res.attributes,
+            vec![
+                attr("action", "tea/hub/record_burn"),
+                attr("id", "1"),
+                attr("serial", "1"),
+                attr("owner", "jake"),
+            ],
+        );
+
+        let res = query::tea_stats(deps.as_ref(), 1).unwrap();
+        assert_eq!(res.burns, 1);
+    }
+
+    // once tea 1 is past its deadline, it no longer counts as active even though it never sold
+    // out; tea 2 never expires
+    {
+        execute::create_tea(
+            deps.as_mut(),
+            utils::mock_env_at_timestamp(10000),
+            mock_info("creator", &[]),
+            Tea {
+                manager: Addr::unchecked("larry"),
+                metadata: Metadata::default(),
+                transferrable: true,
+                rule: MintRule::ByKeys,
+                expiry: Some(12345),
+                max_supply: None,
+                current_supply: 0,
+                reveal: None,
+                nft: None,
+            },
+        )
+        .unwrap();
+
+        let count = |timestamp, start_after, limit| {
+            query::active_tea_count(
+                deps.as_ref(),
+                utils::mock_env_at_timestamp(timestamp),
+                start_after,
+                limit,
+            )
+            .unwrap()
+        };
+
+        assert_eq!(
+            count(12345, None, None),
+            ActiveTeaCountResponse {
+                count: 2,
+                next: None,
+            },
+        );
+        assert_eq!(
+            count(12346, None, None),
+            ActiveTeaCountResponse {
+                count: 1,
+                next: None,
+            },
+        );
+
+        // the count is paginated, resuming after the last tea counted
+        assert_eq!(
+            count(12345, None, Some(1)),
+            ActiveTeaCountResponse {
+                count: 1,
+                next: Some((12345, 3)),
+            },
+        );
+        assert_eq!(
+            count(12345, Some((12345, 3)), Some(1)),
+            ActiveTeaCountResponse {
+                count: 1,
+                next: Some((u64::MAX, 2)),
+            },
+        );
+        assert_eq!(
+            count(12345, Some((u64::MAX, 2)), Some(1)),
+            ActiveTeaCountResponse {
+                count: 0,
+                next: None,
+            },
+        );
+
+        // a cursor left over from an earlier block doesn't count tea that have since expired
+        assert_eq!(
+            count(12346, Some((0, 0)), None),
+            ActiveTeaCountResponse {
+                count: 1,
+                next: None,
+            },
+        );
+    }
+}
@@ -3,10 +3,13 @@
 use std::collections::BTreeSet;
 
 use cosmwasm_std::testing::mock_env;
-use cosmwasm_std::{Env, Timestamp};
+use cosmwasm_std::{to_json_binary, Env, Timestamp};
 use k256::ecdsa::{signature::Signer, Signature, SigningKey};
 use rand::rngs::OsRng;
 
+use tea::Tea;
+use tea_hub::helpers::metadata_hash;
+
 pub const MOCK_PRIVKEY: &str = "0123456789abcdef0123456789abcdef0123456789abcdef0123456789abcdef";
 
 /// Return the private key based on the hex-encoded `MOCK_PRIVKEY`
@@ -38,3 +41,12 @@ pub fn mock_env_at_timestamp(timestamp: u64) -> Env {
     env.block.time = Timestamp::from_seconds(timestamp);
     env
 }
+
+/// Number of bytes a tea takes in storage when its metadata is not shared with any other tea: its
+/// record, which holds the hash of its metadata, plus the metadata blob
+pub fn tea_bytes(tea: &Tea) -> u128 {
+    let record = tea.clone().with_metadata(metadata_hash(&tea.metadata).unwrap());
+    let record_bytes = to_json_binary(&record).unwrap().len();
+    let metadata_bytes = to_json_binary(&tea.metadata).unwrap().len();
+    (record_bytes + metadata_bytes) as u128
+}
@@ -5,7 +5,7 @@ use cosmwasm_schema::{export_schema, export_schema_with_title, remove_schemas, s
 
 use tea::nft::{
     AllNftInfoResponse, ApprovalResponse, ApprovalsResponse, CollectionInfoResponse,
-    ContractInfoResponse, ExecuteMsg, InstantiateMsg, MinterResponse, NftInfoResponse,
+    ContractInfoResponse, ExecuteMsg, InstantiateMsg, MigrateMsg, MinterResponse, NftInfoResponse,
     NumTokensResponse, OperatorsResponse, OwnerOfResponse, QueryMsg, TokensResponse,
 };
 
@@ -17,6 +17,7 @@ fn main() {
 
     export_schema(&schema_for!(InstantiateMsg), &out_dir);
     export_schema(&schema_for!(QueryMsg), &out_dir);
+    export_schema(&schema_for!(MigrateMsg), &out_dir);
 
     export_schema(&schema_for!(ContractInfoResponse), &out_dir);
     export_schema(&schema_for!(NumTokensResponse), &out_dir);
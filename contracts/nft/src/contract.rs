@@ -1,19 +1,35 @@
 use std::any::type_name;
 use std::str::FromStr;
 
-use cosmwasm_std::{Deps, DepsMut, Env, MessageInfo, StdError, StdResult, Storage};
+use cosmwasm_std::{
+    to_json_binary, Deps, DepsMut, Env, MessageInfo, StdError, StdResult, Storage, WasmMsg,
+};
 use cw721::Cw721Query;
 use terp_metadata::{Metadata, Trait};
 use terp_sdk::Response;
 
 use tea::hub::TeaResponse;
-use tea::nft::{AllNftInfoResponse, Extension, InstantiateMsg, NftInfoResponse, MinterResponse};
+use tea::nft::{
+    AllNftInfoResponse, ExecuteMsg, Extension, InstantiateMsg, MigrateMsg, MinterResponse,
+    NftInfoResponse,
+};
 
-use crate::state::API_URL;
+use crate::{
+    state::{API_URL, HUB},
+    upgrades::{self, parse_version},
+};
 
-pub const CONTRACT_NAME: &str = "crates.io:tea-hub";
+pub const CONTRACT_NAME: &str = "crates.io:tea-nft";
 pub const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
 
+/// Earlier deployments of the NFT contract mistakenly recorded the Hub's contract name in cw2.
+/// Contracts carrying this name are accepted for migration, and have their name corrected.
+pub const LEGACY_CONTRACT_NAME: &str = "crates.io:tea-hub";
+
+/// Name and symbol of the Hub's shared collection, used unless a dedicated collection sets its own
+pub const DEFAULT_NAME: &str = "Terp Event Attendance Token";
+pub const DEFAULT_SYMBOL: &str = "B";
+
 #[derive(Default)]
 pub struct NftContract<'a> {
     pub parent: terp721_base::Terp721Contract<'a, Extension>,
@@ -30,20 +46,138 @@ impl<'a> NftContract<'a> {
         cw2::set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
 
         API_URL.save(deps.storage, &msg.api_url)?;
+        HUB.save(deps.storage, &deps.api.addr_validate(&msg.hub)?)?;
 
         self.parent.instantiate(
             deps,
             env,
             info,
             terp721::InstantiateMsg {
-                name: "Terp Event Attendance Token".to_string(),
-                symbol: "B".to_string(),
+                name: msg.name.unwrap_or_else(|| DEFAULT_NAME.to_string()),
+                symbol: msg.symbol.unwrap_or_else(|| DEFAULT_SYMBOL.to_string()),
                 minter: msg.hub,
                 collection_info: msg.collection_info,
             },
         )
     }
 
+    pub fn migrate(
+        &self,
+        mut deps: DepsMut,
+        _env: Env,
+        msg: MigrateMsg,
+    ) -> Result<Response, terp721_base::ContractError> {
+        let cw2::ContractVersion {
+            contract,
+            version,
+        } = cw2::get_contract_version(deps.storage)?;
+
+        if contract != CONTRACT_NAME && contract != LEGACY_CONTRACT_NAME {
+            return Err(terp721_base::ContractError::MigrationError(format!(
+                "incorrect contract name: expecting {}, found {}",
+                CONTRACT_NAME, contract
+            )));
+        }
+
+        // we allow migrating to the same version, in which case only the parameters provided in
+        // the migrate msg are updated, but never to an older version
+        if parse_version(&version)? > parse_version(CONTRACT_VERSION)? {
+            return Err(terp721_base::ContractError::MigrationError(format!(
+                "cannot migrate from version {} to an older version {}",
+                version, CONTRACT_VERSION
+            )));
+        }
+
+        // apply the state upgrades in order, before the parameters provided in the migrate msg
+        let upgrades = upgrades::pending(&version, CONTRACT_VERSION)?;
+        for upgrade in &upgrades {
+            (upgrade.migrate)(deps.branch())?;
+        }
+
+        let mut res = Response::new()
+            .add_attribute("action", "tea/nft/migrate")
+            .add_attribute("from_version", &version)
+            .add_attribute("to_version", CONTRACT_VERSION)
+            .add_attribute("upgrades", upgrades.len().to_string());
+
+        if let Some(api_url) = msg.api_url {
+            API_URL.save(deps.storage, &api_url)?;
+            res = res.add_attribute("api_url", api_url);
+        }
+
+        // the hub is the minter of the NFT collection; the minter role is managed by cw-ownable
+        if let Some(hub) = msg.hub {
+            cw_ownable::initialize_owner(deps.storage, deps.api, Some(&hub))?;
+            HUB.save(deps.storage, &deps.api.addr_validate(&hub)?)?;
+            res = res.add_attribute("hub", hub);
+        }
+
+        cw2::set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+
+        Ok(res)
+    }
+
+    /// Burn a token, and report the burn to the Hub contract so that it can keep track of burns
+    pub fn burn(
+        &self,
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        token_id: String,
+    ) -> Result<Response, terp721_base::ContractError> {
+        let (id, serial) = parse_token_id(&token_id)?;
+        let owner = self.parent.parent.tokens.load(deps.storage, &token_id)?.owner;
+        let hub = self.hub(deps.as_ref())?;
+
+        let res = self.parent.execute(
+            deps,
+            env,
+            info,
+            ExecuteMsg::Burn {
+                token_id,
+            },
+        )?;
+
+        Ok(res.add_message(WasmMsg::Execute {
+            contract_addr: hub,
+            msg: to_json_binary(&tea::hub::ExecuteMsg::RecordBurn {
+                id,
+                serial,
+                owner: owner.into(),
+            })?,
+            funds: vec![],
+        }))
+    }
+
+    /// Transfer or send a token, and report the transfer to the Hub contract so that it can notify
+    /// hooks. `recipient` is the account or contract the token is transferred or sent to.
+    pub fn transfer(
+        &self,
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        token_id: &str,
+        recipient: String,
+        msg: ExecuteMsg,
+    ) -> Result<Response, terp721_base::ContractError> {
+        let (id, serial) = parse_token_id(token_id)?;
+        let owner = self.parent.parent.tokens.load(deps.storage, token_id)?.owner;
+        let hub = self.hub(deps.as_ref())?;
+
+        let res = self.parent.execute(deps, env, info, msg)?;
+
+        Ok(res.add_message(WasmMsg::Execute {
+            contract_addr: hub,
+            msg: to_json_binary(&tea::hub::ExecuteMsg::RecordTransfer {
+                id,
+                serial,
+                from: owner.into(),
+                to: recipient,
+            })?,
+            funds: vec![],
+        }))
+    }
+
     /// Assert that the tea is transferrable
     pub fn assert_transferrable(&self, deps: Deps, token_id: impl ToString) -> StdResult<()> {
         let (id, _) = parse_token_id(&token_id.to_string())?;
@@ -60,9 +194,22 @@ impl<'a> NftContract<'a> {
         let (id, serial) = parse_token_id(&token_id.to_string())?;
         let uri = uri(deps.storage, id, serial)?;
         let tea = self.query_tea(deps, id)?;
+        // flagged tea are redacted, and tea pending a reveal are served with their placeholder
+        let metadata = match (tea.flag_reason, tea.reveal) {
+            (Some(_), _) => redacted_metadata(),
+            (None, Some(reveal)) => reveal.placeholder,
+            (None, None) => tea.metadata,
+        };
         Ok(NftInfoResponse {
             token_uri: Some(uri),
-            extension: prepend_traits(tea.metadata, id, serial),
+            extension: prepend_traits(
+                metadata,
+                id,
+                serial,
+                tea.metadata_version,
+                tea.series,
+                tea.issuer_verified,
+            ),
         })
     }
 
@@ -91,14 +238,22 @@ impl<'a> NftContract<'a> {
     /// a separate copy in each token's extension. This function queries the Hub contract for the
     /// metadata of a given token id.
     fn query_tea(&self, deps: Deps, id: u64) -> StdResult<TeaResponse> {
-        let minter: MinterResponse = self.parent.parent.minter(deps)?;
         deps.querier.query_wasm_smart(
-            &minter.minter.unwrap_or_default(),
+            self.hub(deps)?,
             &tea::hub::QueryMsg::Tea {
                 id,
             },
         )
-    }    
+    }
+
+    /// Address of the Hub contract; see `HUB`
+    fn hub(&self, deps: Deps) -> StdResult<String> {
+        if let Some(hub) = HUB.may_load(deps.storage)? {
+            return Ok(hub.into());
+        }
+        let minter: MinterResponse = self.parent.parent.minter(deps)?;
+        Ok(minter.minter.unwrap_or_default())
+    }
 }
 
 /// URL of an API serving the metadata of the NFT.
@@ -129,8 +284,33 @@ pub fn parse_token_id(token_id: &str) -> StdResult<(u64, u64)> {
     Ok((id, serial))
 }
 
-/// The tea's id and serial are prepended to it's list of traits.
-pub fn prepend_traits(mut metadata: Metadata, id: u64, serial: u64) -> Metadata {
+/// Metadata served in place of that of a tea that has been flagged as abusive. It carries a
+/// `flagged` trait, so that wallets can tell the tea apart from one that has no metadata.
+pub fn redacted_metadata() -> Metadata {
+    Metadata {
+        name: Some("Redacted".to_string()),
+        description: Some("This tea has been flagged and its metadata is hidden.".to_string()),
+        attributes: Some(vec![Trait {
+            display_type: None,
+            trait_type: "flagged".to_string(),
+            value: "true".to_string(),
+        }]),
+        ..Default::default()
+    }
+}
+
+/// The tea's id, serial and metadata version are prepended to it's list of traits, followed by the
+/// id of the series it belongs to, if any, so that wallets can group related tea. If the tea's
+/// manager is a verified issuer, a `verified` trait is prepended as well, so that wallets can
+/// display a checkmark.
+pub fn prepend_traits(
+    mut metadata: Metadata,
+    id: u64,
+    serial: u64,
+    metadata_version: u32,
+    series: Option<u64>,
+    verified: bool,
+) -> Metadata {
     let mut traits = vec![
         Trait {
             display_type: None,
@@ -142,9 +322,30 @@ pub fn prepend_traits(mut metadata: Metadata, id: u64, serial: u64) -> Metadata
             trait_type: "serial".to_string(),
             value: serial.to_string(),
         },
+        Trait {
+            display_type: None,
+            trait_type: "metadata_version".to_string(),
+            value: metadata_version.to_string(),
+        },
     ];
 
-    traits.extend(metadata.attributes.unwrap_or_default().into_iter());
+    if let Some(series) = series {
+        traits.push(Trait {
+            display_type: None,
+            trait_type: "series".to_string(),
+            value: series.to_string(),
+        });
+    }
+
+    if verified {
+        traits.push(Trait {
+            display_type: None,
+            trait_type: "verified".to_string(),
+            value: "true".to_string(),
+        });
+    }
+
+    traits.extend(metadata.attributes.unwrap_or_default());
 
     metadata.attributes = Some(traits);
     metadata
@@ -1,5 +1,6 @@
 pub mod contract;
 pub mod state;
+pub mod upgrades;
 
 
 #[cfg(not(feature = "library"))]
@@ -9,7 +10,7 @@ pub mod entry {
         entry_point, to_json_binary, Binary, Deps, DepsMut, Env, MessageInfo, StdResult,
     };
     use terp721_base::ContractError;
-    use tea::nft::{ExecuteMsg, InstantiateMsg, QueryMsg};
+    use tea::nft::{ExecuteMsg, InstantiateMsg, MigrateMsg, QueryMsg};
 
     use crate::contract::NftContract;
 
@@ -32,21 +33,37 @@ pub mod entry {
         msg: ExecuteMsg,
     ) -> Result<Response, ContractError> {
         let tract = NftContract::default();
-        // Transfers and approvals are only allowed if the tea is transferrable
-        match &msg {
+        // Burns are reported to the Hub
+        if let ExecuteMsg::Burn {
+            token_id,
+        } = msg
+        {
+            return tract.burn(deps, env, info, token_id);
+        }
+        // Transfers and approvals are only allowed if the tea is transferrable, and transfers are
+        // reported to the Hub
+        let transfer = match &msg {
             ExecuteMsg::TransferNft {
+                recipient,
                 token_id,
-                ..
-            } => tract.assert_transferrable(deps.as_ref(), token_id)?,
+            } => Some((token_id.clone(), recipient.clone())),
             ExecuteMsg::SendNft {
+                contract,
                 token_id,
                 ..
-            } => tract.assert_transferrable(deps.as_ref(), token_id)?,
+            } => Some((token_id.clone(), contract.clone())),
             ExecuteMsg::Approve {
                 token_id,
                 ..
-            } => tract.assert_transferrable(deps.as_ref(), token_id)?,
-            _ => (),
+            } => {
+                tract.assert_transferrable(deps.as_ref(), token_id)?;
+                None
+            },
+            _ => None,
+        };
+        if let Some((token_id, recipient)) = transfer {
+            tract.assert_transferrable(deps.as_ref(), &token_id)?;
+            return tract.transfer(deps, env, info, &token_id, recipient, msg);
         }
         tract.parent.execute(deps, env, info, msg)
     }
@@ -68,7 +85,8 @@ pub mod entry {
         }
     }
 
-    // #[entry_point]
-    // pub fn migrate(deps: DepsMut, _env: Env, _msg: Empty) -> StdResult<Response> {
-    // }
+    #[entry_point]
+    pub fn migrate(deps: DepsMut, env: Env, msg: MigrateMsg) -> Result<Response, ContractError> {
+        NftContract::default().migrate(deps, env, msg)
+    }
 }
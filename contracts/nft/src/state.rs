@@ -1,3 +1,11 @@
+use cosmwasm_std::Addr;
 use cw_storage_plus::Item;
 
 pub const API_URL: Item<String> = Item::new("api_url");
+
+/// Address of the Hub contract, which tea metadata is queried from and burns and transfers are
+/// reported to. Kept apart from the minter role, so that a previous generation of the NFT contract
+/// keeps resolving its tokens even once the Hub mints on a newer one and the minter role is handed
+/// off. Contracts instantiated before this was recorded fall back to the minter until migrated,
+/// which backfills it.
+pub const HUB: Item<Addr> = Item::new("hub");
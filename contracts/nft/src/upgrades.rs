@@ -0,0 +1,72 @@
+use cosmwasm_std::{DepsMut, StdResult};
+use semver::{Version, VersionReq};
+use terp721_base::ContractError;
+
+use crate::state::HUB;
+
+/// A transformation of the contract state, bringing the contract from a range of versions to a
+/// newer one. Mirrors the Hub's upgrades.
+#[derive(Debug)]
+pub struct Upgrade {
+    /// Semver requirement the stored contract version must satisfy for this upgrade to be applied
+    pub from: &'static str,
+    /// The contract version once this upgrade has been applied
+    pub to: &'static str,
+    /// The function that transforms the contract state
+    pub migrate: fn(DepsMut) -> StdResult<()>,
+}
+
+/// All state upgrades, in the order they are to be applied.
+///
+/// When a release requires transforming the contract state, declare its `Upgrade` below, and
+/// append it here.
+pub const UPGRADES: &[Upgrade] = &[V0_2];
+
+/// Deployed NFT contracts record `0.1.0`, under the Hub's contract name. They predate `HUB`, which
+/// is backfilled from the minter, the only address they knew the Hub by.
+pub const V0_2: Upgrade = Upgrade {
+    from: ">=0.1.0, <0.2.0",
+    to: "0.2.0",
+    migrate: |deps| {
+        if HUB.may_load(deps.storage)?.is_none() {
+            if let Some(minter) = cw_ownable::get_ownership(deps.storage)?.owner {
+                HUB.save(deps.storage, &minter)?;
+            }
+        }
+        Ok(())
+    },
+};
+
+/// Parse a cw2 contract version string as semver
+pub fn parse_version(version: &str) -> Result<Version, ContractError> {
+    Version::parse(version).map_err(|err| {
+        let msg = format!("invalid version `{}`: {}", version, err);
+        ContractError::MigrationError(msg)
+    })
+}
+
+/// Find the upgrades that need to be applied, in order, to bring the contract from the `current`
+/// version to the `target` version.
+///
+/// An upgrade is selected if the version reached so far satisfies its `from` requirement, and its
+/// `to` version does not go beyond the target.
+pub fn pending(current: &str, target: &str) -> Result<Vec<&'static Upgrade>, ContractError> {
+    let target_version = parse_version(target)?;
+    let mut version = parse_version(current)?;
+
+    let mut upgrades = vec![];
+    for upgrade in UPGRADES {
+        let from = VersionReq::parse(upgrade.from).map_err(|err| {
+            let msg = format!("invalid version requirement `{}`: {}", upgrade.from, err);
+            ContractError::MigrationError(msg)
+        })?;
+        let to = parse_version(upgrade.to)?;
+
+        if from.matches(&version) && to <= target_version {
+            upgrades.push(upgrade);
+            version = to;
+        }
+    }
+
+    Ok(upgrades)
+}
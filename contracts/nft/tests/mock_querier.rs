@@ -1,6 +1,6 @@
 #![allow(dead_code)]
 
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
 use cosmwasm_std::testing::MockQuerier;
 use cosmwasm_std::{
@@ -57,6 +57,10 @@ impl Querier for CustomQuerier {
 pub struct HubQuerier {
     contract_addr: Addr,
     tea: HashMap<u64, Tea>,
+    verified_issuers: HashSet<Addr>,
+    flagged_tea: HashMap<u64, String>,
+    metadata_versions: HashMap<u64, u32>,
+    tea_series: HashMap<u64, u64>,
 }
 
 impl Default for HubQuerier {
@@ -64,6 +68,10 @@ impl Default for HubQuerier {
         HubQuerier {
             contract_addr: Addr::unchecked("hub"),
             tea: HashMap::default(),
+            verified_issuers: HashSet::default(),
+            flagged_tea: HashMap::default(),
+            metadata_versions: HashMap::default(),
+            tea_series: HashMap::default(),
         }
     }
 }
@@ -73,8 +81,24 @@ impl HubQuerier {
         self.tea.insert(id, tea);
     }
 
+    pub fn set_issuer_verified(&mut self, issuer: &str) {
+        self.verified_issuers.insert(Addr::unchecked(issuer));
+    }
+
+    pub fn flag_tea(&mut self, id: u64, reason: &str) {
+        self.flagged_tea.insert(id, reason.to_string());
+    }
+
+    pub fn set_metadata_version(&mut self, id: u64, version: u32) {
+        self.metadata_versions.insert(id, version);
+    }
+
+    pub fn set_tea_series(&mut self, id: u64, series: u64) {
+        self.tea_series.insert(id, series);
+    }
+
     pub fn handle_query(&self, contract_addr: &Addr, msg: hub::QueryMsg) -> QuerierResult {
-        if contract_addr != &self.contract_addr {
+        if *contract_addr != self.contract_addr {
             panic!(
                 "[mock]: made a tea hub query but addresses is incorrect: expected {}, found {}",
                 self.contract_addr, contract_addr
@@ -90,7 +114,17 @@ impl HubQuerier {
                     .get(&id)
                     .cloned()
                     .unwrap_or_else(|| panic!("[mock]: cannot find tea with id {}", id));
-                let res = hub::TeaResponse::from((id, tea));
+                let issuer_verified = self.verified_issuers.contains(&tea.manager);
+                let flag_reason = self.flagged_tea.get(&id).cloned();
+                let metadata_version = self.metadata_versions.get(&id).cloned().unwrap_or(1);
+                let series = self.tea_series.get(&id).cloned();
+                let res = hub::TeaResponse {
+                    issuer_verified,
+                    flag_reason,
+                    metadata_version,
+                    series,
+                    ..hub::TeaResponse::from((id, tea))
+                };
                 Ok(to_json_binary(&res).into()).into()
             },
 
@@ -109,7 +143,10 @@ fn wasm_querier_handler(query: &WasmQuery) -> QuerierResult {
         WasmQuery::ContractInfo {
             contract_addr,
         } if contract_addr == "tea_hub" => {
-            Ok(to_json_binary(&ContractInfoResponse::new(69420, "larry")).into()).into()
+            let mut res = ContractInfoResponse::default();
+            res.code_id = 69420;
+            res.creator = "larry".to_string();
+            Ok(to_json_binary(&res).into()).into()
         },
         _ => panic!("[mock]: unimplemented wasm query: {query:?}"),
     }
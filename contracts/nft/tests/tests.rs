@@ -3,15 +3,21 @@ use std::marker::PhantomData;
 
 use tea_nft::entry;
 use cosmwasm_std::testing::{mock_env, mock_info, MockApi, MockStorage};
-use cosmwasm_std::{Addr, Empty, OwnedDeps, StdError};
+use cosmwasm_std::{
+    attr, to_json_binary, Addr, Decimal, Empty, OwnedDeps, StdError, SubMsg, WasmMsg,
+};
 use cw721::{AllNftInfoResponse, Cw721Query};
 // use terp721_base::msg::ExecuteMsg::Mint;
-use terp721::CollectionInfo;
+use terp721::{CollectionInfo, ResidualInfoResponse};
 use terp_metadata::{Metadata, Trait};
 
-use tea_nft::contract::{parse_token_id, prepend_traits, NftContract};
-use tea::nft::{ExecuteMsg, InstantiateMsg};
-use tea::{Tea, MintRule};
+use tea_nft::contract::{
+    parse_token_id, prepend_traits, redacted_metadata, NftContract, CONTRACT_NAME, CONTRACT_VERSION,
+    LEGACY_CONTRACT_NAME,
+};
+use tea_nft::state::{API_URL, HUB};
+use tea::nft::{ExecuteMsg, InstantiateMsg, MigrateMsg};
+use tea::{Tea, MintRule, Reveal};
 
 mod mock_querier;
 
@@ -47,6 +53,8 @@ fn setup_test() -> OwnedDeps<MockStorage, MockApi, mock_querier::CustomQuerier,
             expiry: None,
             max_supply: None,
             current_supply: 420,
+            reveal: None,
+            nft: None,
         },
     );
 
@@ -60,6 +68,8 @@ fn setup_test() -> OwnedDeps<MockStorage, MockApi, mock_querier::CustomQuerier,
             expiry: None,
             max_supply: None,
             current_supply: 88888,
+            reveal: None,
+            nft: None,
         },
     );
 
@@ -87,6 +97,8 @@ fn setup_test() -> OwnedDeps<MockStorage, MockApi, mock_querier::CustomQuerier,
                     start_trading_time: None,
                     residual_info: None,
                 },
+                name: None,
+                symbol: None,
             },
         )
         .unwrap();
@@ -151,7 +163,7 @@ fn parsing_token_id() {
 
 #[test]
 fn prepending_traits() {
-    let metadata = prepend_traits(mock_metadata(), 69, 420);
+    let metadata = prepend_traits(mock_metadata(), 69, 420, 1, None, false);
     assert_eq!(
         metadata.attributes.unwrap(),
         vec![
@@ -165,6 +177,77 @@ fn prepending_traits() {
                 trait_type: "serial".to_string(),
                 value: "420".to_string(),
             },
+            Trait {
+                display_type: None,
+                trait_type: "metadata_version".to_string(),
+                value: "1".to_string(),
+            },
+            Trait {
+                display_type: None,
+                trait_type: "rarity".to_string(),
+                value: "SSR".to_string(),
+            },
+        ]
+    );
+
+    // tea of a verified issuer additionally get the `verified` trait
+    let metadata = prepend_traits(mock_metadata(), 69, 420, 1, None, true);
+    assert_eq!(
+        metadata.attributes.unwrap(),
+        vec![
+            Trait {
+                display_type: None,
+                trait_type: "id".to_string(),
+                value: "69".to_string(),
+            },
+            Trait {
+                display_type: None,
+                trait_type: "serial".to_string(),
+                value: "420".to_string(),
+            },
+            Trait {
+                display_type: None,
+                trait_type: "metadata_version".to_string(),
+                value: "1".to_string(),
+            },
+            Trait {
+                display_type: None,
+                trait_type: "verified".to_string(),
+                value: "true".to_string(),
+            },
+            Trait {
+                display_type: None,
+                trait_type: "rarity".to_string(),
+                value: "SSR".to_string(),
+            },
+        ]
+    );
+
+    // tea in a series additionally get the `series` trait
+    let metadata = prepend_traits(mock_metadata(), 69, 420, 1, Some(7), false);
+    assert_eq!(
+        metadata.attributes.unwrap(),
+        vec![
+            Trait {
+                display_type: None,
+                trait_type: "id".to_string(),
+                value: "69".to_string(),
+            },
+            Trait {
+                display_type: None,
+                trait_type: "serial".to_string(),
+                value: "420".to_string(),
+            },
+            Trait {
+                display_type: None,
+                trait_type: "metadata_version".to_string(),
+                value: "1".to_string(),
+            },
+            Trait {
+                display_type: None,
+                trait_type: "series".to_string(),
+                value: "7".to_string(),
+            },
             Trait {
                 display_type: None,
                 trait_type: "rarity".to_string(),
@@ -198,6 +281,51 @@ fn instantiating() {
     assert_eq!(owner.owner, "jake");
 }
 
+#[test]
+fn instantiating_dedicated_collection() {
+    let mut deps = OwnedDeps {
+        storage: MockStorage::default(),
+        api: MockApi::default(),
+        querier: mock_querier::CustomQuerier::default(),
+        custom_query_type: PhantomData,
+    };
+    let contract = NftContract::default();
+
+    contract
+        .instantiate(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("tea_hub", &[]),
+            InstantiateMsg {
+                hub: "hub".to_string(),
+                api_url: "https://tea-api.larry.engineer/metadata".to_string(),
+                collection_info: CollectionInfo {
+                    creator: "jake".to_string(),
+                    description: "badges of jake's conference".to_string(),
+                    image: "https://jake.conference/logo.png".to_string(),
+                    external_link: None,
+                    explicit_content: None,
+                    start_trading_time: None,
+                    residual_info: Some(ResidualInfoResponse {
+                        payment_address: "jake".to_string(),
+                        share: Decimal::percent(5),
+                    }),
+                },
+                name: Some("Jake's Conference".to_string()),
+                symbol: Some("JAKE".to_string()),
+            },
+        )
+        .unwrap();
+
+    let info = contract.parent.parent.contract_info(deps.as_ref()).unwrap();
+    assert_eq!(info.name, "Jake's Conference");
+    assert_eq!(info.symbol, "JAKE");
+
+    let info = contract.parent.query_collection_info(deps.as_ref()).unwrap();
+    assert_eq!(info.creator, "jake");
+    assert_eq!(info.residual_info.unwrap().share, Decimal::percent(5));
+}
+
 #[test]
 fn rejecting_transfers() {
     let mut deps = setup_test();
@@ -237,6 +365,178 @@ fn rejecting_transfers() {
     assert_eq!(err.to_string(), "Generic error: tea 420 is not transferrable");
 }
 
+#[test]
+fn burning() {
+    let mut deps = setup_test();
+    let contract = NftContract::default();
+
+    // a non-owner cannot burn the token
+    {
+        let err = entry::execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("pumpkin", &[]),
+            ExecuteMsg::Burn {
+                token_id: "69|420".to_string(),
+            },
+        )
+        .unwrap_err();
+        assert_eq!(err.to_string(), "Caller is not the contract's current owner");
+    }
+
+    // the owner burns the token, and the burn is reported to the hub
+    {
+        let res = entry::execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("jake", &[]),
+            ExecuteMsg::Burn {
+                token_id: "69|420".to_string(),
+            },
+        )
+        .unwrap();
+        assert_eq!(
+            res.messages,
+            vec![SubMsg::new(WasmMsg::Execute {
+                contract_addr: "hub".to_string(),
+                msg: to_json_binary(&tea::hub::ExecuteMsg::RecordBurn {
+                    id: 69,
+                    serial: 420,
+                    owner: "jake".to_string(),
+                })
+                .unwrap(),
+                funds: vec![],
+            })],
+        );
+
+        let err = contract
+            .parent
+            .parent
+            .owner_of(deps.as_ref(), mock_env(), "69|420".to_string(), false)
+            .unwrap_err();
+        assert!(matches!(err, StdError::NotFound { .. }));
+    }
+}
+
+#[test]
+fn transferring() {
+    let mut deps = setup_test();
+
+    // the owner transfers the token, and the transfer is reported to the hub
+    {
+        let res = entry::execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("jake", &[]),
+            ExecuteMsg::TransferNft {
+                recipient: "pumpkin".to_string(),
+                token_id: "69|420".to_string(),
+            },
+        )
+        .unwrap();
+        assert_eq!(
+            res.messages,
+            vec![SubMsg::new(WasmMsg::Execute {
+                contract_addr: "hub".to_string(),
+                msg: to_json_binary(&tea::hub::ExecuteMsg::RecordTransfer {
+                    id: 69,
+                    serial: 420,
+                    from: "jake".to_string(),
+                    to: "pumpkin".to_string(),
+                })
+                .unwrap(),
+                funds: vec![],
+            })],
+        );
+    }
+
+    // sending the token to a contract is reported the same way, after the receive message
+    {
+        let res = entry::execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("pumpkin", &[]),
+            ExecuteMsg::SendNft {
+                contract: "market".to_string(),
+                token_id: "69|420".to_string(),
+                msg: to_json_binary("hello").unwrap(),
+            },
+        )
+        .unwrap();
+        assert_eq!(res.messages.len(), 2);
+        assert_eq!(
+            res.messages[1],
+            SubMsg::new(WasmMsg::Execute {
+                contract_addr: "hub".to_string(),
+                msg: to_json_binary(&tea::hub::ExecuteMsg::RecordTransfer {
+                    id: 69,
+                    serial: 420,
+                    from: "pumpkin".to_string(),
+                    to: "market".to_string(),
+                })
+                .unwrap(),
+                funds: vec![],
+            }),
+        );
+    }
+}
+
+#[test]
+fn reporting_to_hub_of_previous_generation() {
+    let mut deps = setup_test();
+
+    let record_burn = |token_id: &str| {
+        let (id, serial) = parse_token_id(token_id).unwrap();
+        SubMsg::new(WasmMsg::Execute {
+            contract_addr: "hub".to_string(),
+            msg: to_json_binary(&tea::hub::ExecuteMsg::RecordBurn {
+                id,
+                serial,
+                owner: "jake".to_string(),
+            })
+            .unwrap(),
+            funds: vec![],
+        })
+    };
+
+    // once the hub mints on a newer generation and the minter role is handed off, burns are still
+    // reported to, and metadata still queried from, the hub
+    {
+        cw_ownable::initialize_owner(&mut deps.storage, &deps.api, Some("new_minter")).unwrap();
+
+        let res = entry::execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("jake", &[]),
+            ExecuteMsg::Burn {
+                token_id: "69|420".to_string(),
+            },
+        )
+        .unwrap();
+        assert_eq!(res.messages, vec![record_burn("69|420")]);
+
+        let info = NftContract::default().nft_info(deps.as_ref(), "69|420").unwrap();
+        assert_eq!(info.extension, prepend_traits(mock_metadata(), 69, 420, 1, None, false));
+    }
+
+    // contracts instantiated before the hub was recorded fall back to the minter
+    {
+        HUB.remove(deps.as_mut().storage);
+        cw_ownable::initialize_owner(&mut deps.storage, &deps.api, Some("hub")).unwrap();
+
+        let res = entry::execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("jake", &[]),
+            ExecuteMsg::Burn {
+                token_id: "70|420".to_string(),
+            },
+        )
+        .unwrap();
+        assert_eq!(res.messages, vec![record_burn("70|420")]);
+    }
+}
+
 #[test]
 fn querying_nft_info() {
     let deps = setup_test();
@@ -244,7 +544,96 @@ fn querying_nft_info() {
 
     let info = contract.nft_info(deps.as_ref(), "69|420").unwrap();
     assert_eq!(info.token_uri.unwrap(), "https://tea-api.larry.engineer/metadata?id=69&serial=420");
-    assert_eq!(info.extension, prepend_traits(mock_metadata(), 69, 420));
+    assert_eq!(info.extension, prepend_traits(mock_metadata(), 69, 420, 1, None, false));
+}
+
+#[test]
+fn querying_nft_info_of_verified_issuer() {
+    let mut deps = setup_test();
+    let contract = NftContract::default();
+
+    deps.querier.hub.set_issuer_verified("jake");
+
+    let info = contract.nft_info(deps.as_ref(), "69|420").unwrap();
+    assert_eq!(info.extension, prepend_traits(mock_metadata(), 69, 420, 1, None, true));
+}
+
+#[test]
+fn querying_nft_info_of_tea_in_series() {
+    let mut deps = setup_test();
+    let contract = NftContract::default();
+
+    deps.querier.hub.set_tea_series(69, 7);
+
+    let info = contract.nft_info(deps.as_ref(), "69|420").unwrap();
+    assert_eq!(info.extension, prepend_traits(mock_metadata(), 69, 420, 1, Some(7), false));
+}
+
+#[test]
+fn querying_nft_info_of_edited_tea() {
+    let mut deps = setup_test();
+    let contract = NftContract::default();
+
+    deps.querier.hub.set_metadata_version(69, 3);
+
+    let info = contract.nft_info(deps.as_ref(), "69|420").unwrap();
+    assert_eq!(info.extension, prepend_traits(mock_metadata(), 69, 420, 3, None, false));
+}
+
+#[test]
+fn querying_nft_info_pending_reveal() {
+    let mut deps = setup_test();
+    let contract = NftContract::default();
+
+    let placeholder = Metadata {
+        name: Some("Mystery Tea".to_string()),
+        ..Default::default()
+    };
+
+    deps.querier.hub.set_tea(
+        1,
+        Tea {
+            manager: Addr::unchecked("jake"),
+            metadata: Metadata::default(),
+            transferrable: true,
+            rule: MintRule::ByKeys,
+            expiry: None,
+            max_supply: None,
+            current_supply: 1,
+            reveal: Some(Reveal {
+                placeholder: placeholder.clone(),
+                time: 12345,
+                hash: "deadbeef".to_string(),
+            }),
+            nft: None,
+        },
+    );
+
+    let info = contract.nft_info(deps.as_ref(), "1|1").unwrap();
+    assert_eq!(info.extension, prepend_traits(placeholder, 1, 1, 1, None, false));
+}
+
+#[test]
+fn querying_nft_info_of_flagged_tea() {
+    let mut deps = setup_test();
+    let contract = NftContract::default();
+
+    deps.querier.hub.flag_tea(69, "offensive image");
+
+    let info = contract.nft_info(deps.as_ref(), "69|420").unwrap();
+    assert_eq!(info.token_uri.unwrap(), "https://tea-api.larry.engineer/metadata?id=69&serial=420");
+    assert_eq!(info.extension, prepend_traits(redacted_metadata(), 69, 420, 1, None, false));
+    assert_eq!(info.extension.image, None);
+    assert!(info
+        .extension
+        .attributes
+        .unwrap()
+        .iter()
+        .any(|t| t.trait_type == "flagged" && t.value == "true"));
+
+    // other tea are unaffected
+    let info = contract.nft_info(deps.as_ref(), "420|1").unwrap();
+    assert_eq!(info.extension, prepend_traits(mock_metadata(), 420, 1, 1, None, false));
 }
 
 #[test]
@@ -258,5 +647,132 @@ fn querying_all_nft_info() {
     } = contract.all_nft_info(deps.as_ref(), mock_env(), "69|420".to_string(), None).unwrap();
     assert_eq!(access.owner, "jake");
     assert_eq!(info.token_uri.unwrap(), "https://tea-api.larry.engineer/metadata?id=69&serial=420");
-    assert_eq!(info.extension, prepend_traits(mock_metadata(), 69, 420));
+    assert_eq!(info.extension, prepend_traits(mock_metadata(), 69, 420, 1, None, false));
+}
+
+#[test]
+fn migrating() {
+    let mut deps = setup_test();
+    let contract = NftContract::default();
+
+    // wrong contract name
+    {
+        cw2::set_contract_version(deps.as_mut().storage, "crates.io:ngmi", "0.1.0").unwrap();
+
+        let err = entry::migrate(
+            deps.as_mut(),
+            mock_env(),
+            MigrateMsg {
+                api_url: None,
+                hub: None,
+            },
+        )
+        .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Error while migrating: (incorrect contract name: expecting crates.io:tea-nft, found crates.io:ngmi) ",
+        );
+    }
+
+    // cannot migrate to an older version
+    {
+        cw2::set_contract_version(deps.as_mut().storage, CONTRACT_NAME, "99.0.0").unwrap();
+
+        let err = entry::migrate(
+            deps.as_mut(),
+            mock_env(),
+            MigrateMsg {
+                api_url: None,
+                hub: None,
+            },
+        )
+        .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            format!(
+                "Error while migrating: (cannot migrate from version 99.0.0 to an older version {}) ",
+                CONTRACT_VERSION,
+            ),
+        );
+    }
+
+    // migrate a deployed contract, which predates `HUB`, backfilling it from the minter
+    {
+        cw2::set_contract_version(deps.as_mut().storage, LEGACY_CONTRACT_NAME, "0.1.0").unwrap();
+        HUB.remove(deps.as_mut().storage);
+
+        let res = entry::migrate(
+            deps.as_mut(),
+            mock_env(),
+            MigrateMsg {
+                api_url: None,
+                hub: None,
+            },
+        )
+        .unwrap();
+        assert_eq!(
+            res.attributes,
+            vec![
+                attr("action", "tea/nft/migrate"),
+                attr("from_version", "0.1.0"),
+                attr("to_version", CONTRACT_VERSION),
+                attr("upgrades", "1"),
+            ],
+        );
+
+        let hub = HUB.load(deps.as_ref().storage).unwrap();
+        assert_eq!(hub, Addr::unchecked("hub"));
+
+        let version = cw2::get_contract_version(deps.as_ref().storage).unwrap();
+        assert_eq!(version.contract, CONTRACT_NAME);
+        assert_eq!(version.version, CONTRACT_VERSION);
+    }
+
+    // migrate from a contract with the legacy name, updating the api url and the hub
+    {
+        cw2::set_contract_version(deps.as_mut().storage, LEGACY_CONTRACT_NAME, "0.0.1").unwrap();
+
+        let res = entry::migrate(
+            deps.as_mut(),
+            mock_env(),
+            MigrateMsg {
+                api_url: Some("https://tea-api.terp.network/metadata".to_string()),
+                hub: Some("new_hub".to_string()),
+            },
+        )
+        .unwrap();
+        assert!(res.messages.is_empty());
+
+        let version = cw2::get_contract_version(deps.as_ref().storage).unwrap();
+        assert_eq!(version.contract, CONTRACT_NAME);
+        assert_eq!(version.version, CONTRACT_VERSION);
+
+        let api_url = API_URL.load(deps.as_ref().storage).unwrap();
+        assert_eq!(api_url, "https://tea-api.terp.network/metadata");
+
+        let minter = contract.parent.parent.minter(deps.as_ref()).unwrap();
+        assert_eq!(minter.minter, Some("new_hub".to_string()));
+
+        let hub = HUB.load(deps.as_ref().storage).unwrap();
+        assert_eq!(hub, Addr::unchecked("new_hub"));
+    }
+
+    // migrate to the same version without changing any parameter
+    {
+        entry::migrate(
+            deps.as_mut(),
+            mock_env(),
+            MigrateMsg {
+                api_url: None,
+                hub: None,
+            },
+        )
+        .unwrap();
+
+        let api_url = API_URL.load(deps.as_ref().storage).unwrap();
+        assert_eq!(api_url, "https://tea-api.terp.network/metadata");
+
+        let minter = contract.parent.parent.minter(deps.as_ref()).unwrap();
+        assert_eq!(minter.minter, Some("new_hub".to_string()));
+    }
 }
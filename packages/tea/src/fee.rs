@@ -1,8 +1,11 @@
-use cosmwasm_std::Decimal;
+use cosmwasm_std::{Coin, Decimal, Uint128};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
 /// Describes the rate of fees charged for storing data on-chain.
+///
+/// Fees are quoted in uthiol by default. Governance may additionally accept other denoms, each with
+/// its own rates, in which case the rates are quoted in that denom per byte.
 #[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
 pub struct FeeRate {
     /// The fee rate, in uthiol per byte, for storing metadata on-chain
@@ -11,3 +14,42 @@ pub struct FeeRate {
     /// The fee rate, in uthiol per byte, for storing claim keys on-chain
     pub key: Decimal,
 }
+
+/// Describes the bounty paid to accounts that purge stale data from storage.
+///
+/// The bounty for purging keys is paid out of their own deposit. That for purging owners is funded
+/// by retaining a share of the fees collected in a pool held by the Hub, and is limited to the
+/// share the tea's own fees put into the pool; see `Deposit::bounty_credit`. If these run dry,
+/// purging continues to work, but no bounty is paid.
+#[derive(Serialize, Deserialize, Clone, Default, PartialEq, JsonSchema, Debug)]
+pub struct BountyRate {
+    /// The share of each fee, between 0 and 1, that goes into the bounty pool instead of being
+    /// held as a refundable deposit. Only applies to fees paid in uthiol
+    pub pool_share: Decimal,
+
+    /// The bounty, in uthiol, paid for each key or owner purged
+    pub per_item: Uint128,
+}
+
+/// Describes the storage deposit held by the Hub for a tea. The deposit is partially refunded to
+/// the tea's manager when the storage it pays for is freed.
+#[derive(Serialize, Deserialize, Clone, Default, PartialEq, JsonSchema, Debug)]
+pub struct Deposit {
+    /// Number of bytes of metadata paid for
+    pub metadata_bytes: u64,
+
+    /// Coins deposited for metadata. May contain multiple denoms, if fees were paid in different
+    /// accepted denoms
+    pub metadata_deposit: Vec<Coin>,
+
+    /// Number of bytes of whitelisted keys paid for
+    pub key_bytes: u64,
+
+    /// Coins deposited for whitelisted keys
+    pub key_deposit: Vec<Coin>,
+
+    /// The share of the tea's fees, in uthiol, retained in the bounty pool and not yet paid out as
+    /// bounties for purging the tea's data. Bounties for the tea never exceed it.
+    #[serde(default)]
+    pub bounty_credit: Uint128,
+}
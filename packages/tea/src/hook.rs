@@ -0,0 +1,49 @@
+use cosmwasm_std::{to_json_binary, StdResult, WasmMsg};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// An event in the lifecycle of a tea instance, delivered to the hook contracts registered at the
+/// Hub, e.g. reward or points contracts that want to react to claims without polling tx events
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum TeaHookMsg {
+    /// An instance of a tea has been minted, by any of the mint rules
+    Minted {
+        id: u64,
+        serial: u64,
+        owner: String,
+    },
+    /// An instance of a tea has been burned, as reported by the NFT contract
+    Burned {
+        id: u64,
+        serial: u64,
+        /// The account that owned the instance before it was burned
+        owner: String,
+    },
+    /// An instance of a tea has been transferred or sent, as reported by the NFT contract
+    Transferred {
+        id: u64,
+        serial: u64,
+        from: String,
+        to: String,
+    },
+}
+
+/// The message hook contracts receive. A hook contract must accept `{"tea_hook":{...}}` as one of
+/// the variants of its own `ExecuteMsg`.
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum TeaHookExecuteMsg {
+    TeaHook(TeaHookMsg),
+}
+
+impl TeaHookMsg {
+    /// Wrap the event in a message executing the hook contract at `contract_addr`
+    pub fn into_wasm_msg(self, contract_addr: impl Into<String>) -> StdResult<WasmMsg> {
+        Ok(WasmMsg::Execute {
+            contract_addr: contract_addr.into(),
+            msg: to_json_binary(&TeaHookExecuteMsg::TeaHook(self))?,
+            funds: vec![],
+        })
+    }
+}
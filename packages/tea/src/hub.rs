@@ -1,15 +1,37 @@
-use std::collections::BTreeSet;
+use std::collections::{BTreeMap, BTreeSet};
 
+use cosmwasm_std::{Addr, Coin, Decimal, Order, Uint128};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use terp_metadata::Metadata;
 
-use crate::{Tea, FeeRate, MintRule};
+use crate::{Tea, BountyRate, Deposit, FeeRate, MintRule, Reveal, Series, Weight};
 
 #[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
 pub struct InstantiateMsg {
     /// The fee rate charged for when creating or editing tea, quoted in uthiol per byte
     pub fee_rate: FeeRate,
+    /// If provided, the Hub instantiates the NFT contract itself and records its address, so that
+    /// no `SetNft` call is needed. Leave this empty on chains with permissioned deployment.
+    #[serde(default)]
+    pub nft: Option<InstantiateNft>,
+}
+
+/// Parameters for the Hub to instantiate the NFT contract with, along with the Hub's own address
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct InstantiateNft {
+    /// Code id of the tea-nft contract
+    pub code_id: u64,
+    /// URL of an API that serves the Tea's metadata. See `tea::nft::InstantiateMsg`
+    pub api_url: String,
+    /// SG-721 collection info
+    pub collection_info: terp721::CollectionInfo<terp721::ResidualInfoResponse>,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct MigrateMsg {
+    /// If provided, replace the fee rate once all pending state upgrades have been applied
+    pub fee_rate: Option<FeeRate>,
 }
 
 #[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
@@ -17,7 +39,55 @@ pub enum SudoMsg {
     /// Set the fee rate for creating or editing tea. Callable by L1 governance.
     SetFeeRate {
         fee_rate: FeeRate,
-    }
+    },
+
+    /// Accept fees in a denom other than uthiol at the given rates, or if `fee_rate` is None, stop
+    /// accepting fees in this denom. Callable by L1 governance.
+    SetDenomFeeRate {
+        denom: String,
+        fee_rate: Option<FeeRate>,
+    },
+
+    /// Set the share of fees that funds the bounty pool, and the bounty paid from it for purging
+    /// stale data. Callable by L1 governance.
+    SetBountyRate {
+        bounty_rate: BountyRate,
+    },
+
+    /// Apply a multiplier, between 0 and 1, to the fees paid by an organizer, e.g. 0 to exempt
+    /// them from fees, or 0.5 to charge half. If `multiplier` is None, the organizer is removed
+    /// from the registry and pays the full fee. Callable by L1 governance.
+    SetFeeMultiplier {
+        address: String,
+        multiplier: Option<Decimal>,
+    },
+
+    /// Add an issuer to, or remove it from, the registry of verified issuers. Tea managed by a
+    /// verified issuer are marked as such in query responses and NFT metadata. Callable by L1
+    /// governance.
+    SetIssuerVerified {
+        address: String,
+        verified: bool,
+    },
+
+    /// Flag a tea as abusive, e.g. for offensive metadata. A flagged tea can no longer be minted,
+    /// and its NFTs are served with redacted metadata. Callable by L1 governance.
+    FlagTea {
+        id: u64,
+        reason: String,
+    },
+
+    /// Lift the flag on a tea. Callable by L1 governance.
+    UnflagTea {
+        id: u64,
+    },
+
+    /// Set the number of seconds an `UpdateNft` must wait before it can be applied, giving holders
+    /// notice of the NFT contract being replaced. Zero, the default, applies updates immediately.
+    /// Callable by L1 governance.
+    SetNftUpdateDelay {
+        delay: u64,
+    },
 }
 
 #[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
@@ -51,12 +121,66 @@ pub enum ExecuteMsg {
         /// Setting this to None means there is no max supply.
         /// Can only be set once when creating the tea; cannot be changed later.
         max_supply: Option<u64>,
+        /// If provided, the tea's metadata is revealed at a later time, and must be left empty
+        /// until then. See `Reveal`.
+        reveal: Option<Reveal>,
+        /// If provided, the tea's instances are minted on this dedicated collection instead of the
+        /// Hub's shared NFT contract. The collection must have been registered by the sender; see
+        /// `RegisterCollection`. Can only be set when creating the tea; cannot be changed later.
+        #[serde(default)]
+        nft: Option<String>,
     },
 
-    /// Edit the metadata of an existing tea; only the manager can call
+    /// Edit the metadata of an existing tea; only the manager can call, and only if the metadata
+    /// has not been locked, and is not pending a reveal.
+    ///
+    /// Each edit records a new version of the metadata along with its hash. If `retain_previous`
+    /// is true, the full metadata being replaced is kept in the history as well, for which a
+    /// storage fee is charged.
     EditTea {
         id: u64,
         metadata: Metadata,
+        #[serde(default)]
+        retain_previous: bool,
+    },
+
+    /// Reveal the final metadata of a tea that was created with a scheduled reveal. Only the
+    /// manager can call, once the reveal time has been reached. The metadata must match the hash
+    /// committed when the tea was created.
+    Reveal {
+        id: u64,
+        metadata: Metadata,
+    },
+
+    /// Permanently freeze the metadata of a tea, so that holders can be certain it will never
+    /// change. Only the manager can call. This cannot be undone.
+    LockMetadata {
+        id: u64,
+    },
+
+    /// Create a new series grouping related tea, e.g. the tracks of a conference or the modules of
+    /// a course
+    CreateSeries {
+        /// The account that can add tea to the series
+        manager: String,
+        metadata: Metadata,
+    },
+
+    /// Append tea to a series. Only the series' manager can call, and only with tea they also
+    /// manage. A tea can belong to at most one series.
+    AddToSeries {
+        id: u64,
+        tea: Vec<u64>,
+    },
+
+    /// Set the weight a tea contributes to the reputation score of each holder of its instances,
+    /// and the category it counts towards. Only the manager can call, and only before the first
+    /// instance is minted, so that every instance is worth the same. The weight also counts across
+    /// all categories only if the manager is the developer or a verified issuer at this time.
+    SetWeight {
+        id: u64,
+        weight: u64,
+        category: String,
     },
 
     /// For a tea that uses the "by keys" mint rule, invoke this method to whitelist pubkeys.
@@ -70,6 +194,8 @@ pub enum ExecuteMsg {
 
     /// Once a tea has expired or sold out, the whitelisted keys are no longer needed. Invoke this
     /// method to purge these keys from storage in order to reduce the chain's state size.
+    ///
+    /// If a bounty rate is set, the caller is paid a bounty for each key purged.
     PurgeKeys {
         id: u64,
         limit: Option<u32>,
@@ -78,11 +204,23 @@ pub enum ExecuteMsg {
     /// Once a tea has expired or sold out, the list of users who have claimed it is no longer
     /// needed. Invoke this method to purge these user addresses in order to reduce the chain's
     /// state size.
+    ///
+    /// If a bounty rate is set, the caller is paid a bounty for each user address purged.
     PurgeOwners {
         id: u64,
         limit: Option<u32>,
     },
 
+    /// Purge the whitelisted keys and owners of all tea that have expired or sold out, in batches.
+    /// The hub keeps a cursor of the tea it has progressed to, so that repeated calls sweep
+    /// through all tea. `limit` is the total number of items to be removed in this call across
+    /// all tea, and may exceed the maximum limit of `PurgeKeys` and `PurgeOwners`.
+    ///
+    /// If a bounty rate is set, the caller is paid a bounty for each item purged.
+    PurgeExpired {
+        limit: Option<u32>,
+    },
+
     /// For a tea with the "by minter" mint rule, mint new tea to a set of owners.
     /// Can only be invoked by the designated minter.
     MintByMinter {
@@ -108,21 +246,141 @@ pub enum ExecuteMsg {
         signature: String,
     },
 
+    /// For a tea with the "by burn" mint rule, burn the required instances of other tea owned by
+    /// the sender, and mint the tea to the sender. The sender must have approved the Hub as an
+    /// operator of their tokens at the NFT contract.
+    MintByBurn {
+        id: u64,
+        /// Token ids of the instances to be burned, which must match the rule's inputs exactly
+        token_ids: BTreeSet<String>,
+    },
+
     /// During deployment, once the NFT contract has been deployed, the developer informs Hub of the
     /// NFT contract's address.
     ///
     /// Can only be invoked once by the developer.
     ///
-    /// Ideally, on a chain with permissionless contract deployment, we would have the Hub deploy
-    /// the NFT contract, and get its address by parsing the reply. However, this doesn't work on
-    /// chains with permissioned deployment such as Terp Network.
+    /// On chains with permissionless contract deployment, the Hub can instantiate the NFT contract
+    /// itself instead; see `InstantiateMsg::nft`. However, this doesn't work on chains with
+    /// permissioned deployment such as Terp Network.
     SetNft {
         nft: String,
     },
+
+    /// Replace the NFT contract that new instances are minted on, e.g. after tea-nft has been
+    /// redeployed with a new code id. Instances minted on previous NFT contracts remain there, and
+    /// the Hub keeps accepting their burns and transfers.
+    ///
+    /// Can only be invoked by the developer. If an update delay is set, the update is only
+    /// scheduled, to be applied with `ApplyNftUpdate` once the delay has passed; scheduling another
+    /// update replaces the pending one.
+    UpdateNft {
+        nft: String,
+    },
+
+    /// Apply the pending NFT contract update once its delay has passed. Callable by anyone.
+    ApplyNftUpdate {},
+
+    /// Cancel the pending NFT contract update. Can only be invoked by the developer.
+    CancelNftUpdate {},
+
+    /// Register a dedicated tea-nft collection, with its own name, symbol, collection info and
+    /// royalties, on which the sender's tea can then be minted. The collection must be an instance
+    /// of the same code as the Hub's NFT contract, and have the Hub as its minter and the sender as
+    /// its creator. Each collection can only be registered once.
+    RegisterCollection {
+        nft: String,
+    },
+
+    /// Apply a multiplier, between 0 and 1, to the fees paid by an organizer, or if `multiplier`
+    /// is None, remove the organizer from the registry.
+    ///
+    /// Can only be invoked by the developer. L1 governance can do the same via sudo.
+    SetFeeMultiplier {
+        address: String,
+        multiplier: Option<Decimal>,
+    },
+
+    /// Add an issuer to, or remove it from, the registry of verified issuers.
+    ///
+    /// Can only be invoked by the developer. L1 governance can do the same via sudo.
+    SetIssuerVerified {
+        address: String,
+        verified: bool,
+    },
+
+    /// Flag a tea as abusive. A flagged tea can no longer be minted, and its NFTs are served with
+    /// redacted metadata.
+    ///
+    /// Can only be invoked by the developer. L1 governance can do the same via sudo.
+    FlagTea {
+        id: u64,
+        reason: String,
+    },
+
+    /// Lift the flag on a tea.
+    ///
+    /// Can only be invoked by the developer. L1 governance can do the same via sudo.
+    UnflagTea {
+        id: u64,
+    },
+
+    /// Register a contract to receive a `TeaHookMsg` on every mint, burn and transfer of a tea's
+    /// instances. If `id` is None, the hook receives the events of all tea, and can only be
+    /// registered by the developer; otherwise, only those of the given tea, and can only be
+    /// registered by the tea's manager. Registering an existing hook again updates `fatal`.
+    AddHook {
+        contract: String,
+        id: Option<u64>,
+        /// If true, a failure of the hook on a mint fails the whole transaction. Otherwise, the
+        /// failure is ignored and only reported in the `tea/hub/hook_failed` event. Failures on
+        /// burns and transfers are always ignored, so that holders can always burn and move their
+        /// tokens.
+        fatal: bool,
+    },
+
+    /// Unregister a hook contract. Callable by the same account that can register it.
+    RemoveHook {
+        contract: String,
+        id: Option<u64>,
+    },
+
+    /// Invoked by the NFT contract when an instance of a tea is burned, so that the Hub can keep
+    /// track of burns.
+    ///
+    /// Can only be invoked by the NFT contract.
+    RecordBurn {
+        id: u64,
+        serial: u64,
+        /// The account that owned the instance before it was burned
+        owner: String,
+    },
+
+    /// Invoked by the NFT contract when an instance of a tea is transferred or sent, so that the
+    /// Hub can notify hooks.
+    ///
+    /// Can only be invoked by the NFT contract.
+    RecordTransfer {
+        id: u64,
+        serial: u64,
+        from: String,
+        to: String,
+    },
+
+    /// Backfill the state introduced by a migration, in batches. Migrating a hub from before v0.2
+    /// leaves its existing tea, owners and keys to be indexed by this message, which must be
+    /// called until the `complete` attribute is `true`; until then, all other messages fail.
+    /// `limit` is the number of items processed in this call.
+    ///
+    /// Can be invoked by anyone.
+    Backfill {
+        limit: Option<u32>,
+    },
 }
 
 #[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
 #[serde(rename_all = "snake_case")]
+#[allow(clippy::large_enum_variant)]
 pub enum QueryMsg {
     /// The contract's configurations. Returns ConfigResponse
     Config {},
@@ -132,12 +390,63 @@ pub enum QueryMsg {
         id: u64,
     },
 
-    /// Enumerate infos of all tea. Returns AllTeaResponse
+    /// Enumerate infos of all tea. Each call goes through a bounded number of tea, so a page
+    /// filtered by status can come back short; continue from the returned `next` cursor.
+    /// Returns AllTeaResponse
     AllTea {
+        /// If provided, only return tea of this status at the current block
+        status: Option<TeaStatus>,
+        /// In ascending order, return tea with ids greater than this; in descending order, return
+        /// tea with ids smaller than this
+        start_after: Option<u64>,
+        limit: Option<u32>,
+        /// Order in which the tea are enumerated by id. Defaults to ascending
+        order: Option<OrderBy>,
+    },
+
+    /// Enumerate infos of all tea managed by the specified account. Returns AllTeaResponse
+    TeaByManager {
+        manager: String,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+
+    /// Enumerate infos of all tea using the specified kind of mint rule, that is, one of
+    /// `by_minter`, `by_key`, `by_keys` or `by_burn`. Returns AllTeaResponse
+    TeaByRule {
+        rule: String,
         start_after: Option<u64>,
         limit: Option<u32>,
     },
 
+    /// Info about a series. Returns SeriesResponse
+    Series {
+        id: u64,
+    },
+
+    /// Enumerate infos of all series. Returns AllSeriesResponse
+    AllSeries {
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+
+    /// A user's reputation score, i.e. the sum of the weights of the tea instances they hold,
+    /// within a category or if `category` is None, across all categories, where only the weights
+    /// set by the developer or verified issuers count. Returns ScoreResponse
+    Score {
+        user: String,
+        category: Option<String>,
+    },
+
+    /// Enumerate users by reputation score, within a category or if `category` is None, across all
+    /// categories, in descending order of score. Returns LeaderboardResponse
+    Leaderboard {
+        category: Option<String>,
+        /// Return users ranked after this {score, user} pair
+        start_after: Option<(u64, String)>,
+        limit: Option<u32>,
+    },
+
     /// Whether a pubkey can be used to mint a tea. Returns KeyResponse
     Key {
         id: u64,
@@ -163,6 +472,178 @@ pub enum QueryMsg {
         start_after: Option<String>,
         limit: Option<u32>,
     },
+
+    /// List the ids of all tea that the specified user has claimed or been minted by a minter.
+    /// Minter mints made before v0.2 are not included. Returns ClaimedByResponse
+    ClaimedBy {
+        user: String,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+
+    /// Dry-run of `mint_by_key` or `mint_by_keys`, depending on the tea's mint rule: whether the
+    /// owner can currently mint the tea with the given signature, and if not, why.
+    /// Returns CanMintResponse
+    CanMint {
+        id: u64,
+        owner: String,
+        /// Only needed if the tea uses the "by keys" mint rule
+        pubkey: Option<String>,
+        signature: String,
+    },
+
+    /// Statistics of all tea. Returns StatsResponse
+    ///
+    /// The number of active tea is not included: counting it means scanning every open tea at
+    /// query time, which is unbounded and would make this query fail past the gas limit once
+    /// enough tea exist. It is served by the paginated `ActiveTeaCount` query instead.
+    Stats {},
+
+    /// Count the tea that can be minted at the current block, i.e. that have neither sold out nor
+    /// passed their deadline. Since whether a tea is active depends on the block time, the count
+    /// can't be kept as a counter; instead it is paginated, counting up to `limit` tea per call.
+    /// Callers sum the counts, passing `next` as `start_after` until it is None.
+    /// Returns ActiveTeaCountResponse
+    ActiveTeaCount {
+        start_after: Option<(u64, u64)>,
+        limit: Option<u32>,
+    },
+
+    /// Statistics of a single tea. Returns TeaStatsResponse
+    TeaStats {
+        id: u64,
+    },
+
+    /// The storage deposit held for a tea. Returns DepositResponse
+    Deposit {
+        id: u64,
+    },
+
+    /// The fee required for an action, in each of the accepted denoms, computed the same way as
+    /// when the action is executed. Returns EstimateFeeResponse
+    EstimateFee {
+        action: FeeAction,
+        /// If provided, apply the fee multiplier of this account, if any
+        payer: Option<String>,
+    },
+
+    /// Enumerate organizers with a fee multiplier. Returns FeeMultipliersResponse
+    FeeMultipliers {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+
+    /// Whether an account is in the registry of verified issuers. Returns IssuerResponse
+    Issuer {
+        address: String,
+    },
+
+    /// Enumerate verified issuers. Returns IssuersResponse
+    VerifiedIssuers {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+
+    /// Enumerate tea that have been flagged, with the reasons. Returns FlaggedTeaResponse
+    FlaggedTea {
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+
+    /// Versions of a tea's metadata, in ascending order. Returns MetadataHistoryResponse
+    MetadataHistory {
+        id: u64,
+        start_after: Option<u32>,
+        limit: Option<u32>,
+    },
+
+    /// Hook contracts registered for the events of a tea, or if `id` is None, for those of all
+    /// tea. Returns HooksResponse
+    Hooks {
+        id: Option<u64>,
+    },
+
+    /// Enumerate the NFT contracts that have been replaced, on which earlier instances of tea
+    /// remain. Returns PreviousNftsResponse
+    PreviousNfts {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+
+    /// The organizer who registered a dedicated collection. Returns CollectionResponse
+    Collection {
+        nft: String,
+    },
+
+    /// Dry-run of a migration: the state upgrades that would be applied, in order, to bring the
+    /// stored contract version to that of the current code. Returns PendingUpgradesResponse
+    PendingUpgrades {},
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, JsonSchema, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum OrderBy {
+    Ascending,
+    Descending,
+}
+
+impl From<OrderBy> for Order {
+    fn from(order: OrderBy) -> Self {
+        match order {
+            OrderBy::Ascending => Order::Ascending,
+            OrderBy::Descending => Order::Descending,
+        }
+    }
+}
+
+/// Whether a tea can currently be minted, evaluated against the block time in the same way as
+/// minting is
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, JsonSchema, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum TeaStatus {
+    /// Neither the minting deadline nor the max supply has been reached
+    Active,
+    /// The minting deadline has been reached
+    Expired,
+    /// The max supply has been reached, but the minting deadline has not
+    SoldOut,
+}
+
+/// An action that is charged a storage fee. The parameters are those of the corresponding
+/// `ExecuteMsg` variant.
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+#[serde(rename_all = "snake_case")]
+#[allow(clippy::large_enum_variant)]
+pub enum FeeAction {
+    CreateTea {
+        manager: String,
+        metadata: Metadata,
+        transferrable: bool,
+        rule: MintRule,
+        expiry: Option<u64>,
+        max_supply: Option<u64>,
+        reveal: Option<Reveal>,
+        #[serde(default)]
+        nft: Option<String>,
+    },
+    EditTea {
+        id: u64,
+        metadata: Metadata,
+        #[serde(default)]
+        retain_previous: bool,
+    },
+    AddKeys {
+        id: u64,
+        keys: BTreeSet<String>,
+    },
+    CreateSeries {
+        manager: String,
+        metadata: Metadata,
+    },
+    AddToSeries {
+        id: u64,
+        tea: Vec<u64>,
+    },
 }
 
 #[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
@@ -170,7 +651,26 @@ pub struct ConfigResponse {
     pub developer: String,
     pub nft: String,
     pub tea_count: u64,
+    /// Fee rate in uthiol
     pub fee_rate: FeeRate,
+    /// Fee rates in other accepted denoms, keyed by denom
+    pub denom_fee_rates: BTreeMap<String, FeeRate>,
+    pub bounty_rate: BountyRate,
+    /// Amount of uthiol held by the Hub for paying purge bounties
+    pub bounty_pool: Uint128,
+    /// Number of seconds an NFT contract update must wait before it can be applied
+    pub nft_update_delay: u64,
+    /// The scheduled NFT contract update, if any
+    pub pending_nft: Option<PendingNft>,
+}
+
+/// An NFT contract update scheduled by the developer
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct PendingNft {
+    /// Address of the NFT contract to replace the current one with
+    pub nft: Addr,
+    /// The timestamp from which the update can be applied
+    pub time: u64,
 }
 
 #[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
@@ -183,8 +683,28 @@ pub struct TeaResponse {
     pub expiry: Option<u64>,
     pub max_supply: Option<u64>,
     pub current_supply: u64,
+    /// If the metadata is pending a reveal, the placeholder and the reveal time
+    pub reveal: Option<Reveal>,
+    /// Whether the manager is currently in the registry of verified issuers
+    pub issuer_verified: bool,
+    /// If the tea has been flagged as abusive, the reason given
+    pub flag_reason: Option<String>,
+    /// Version of the metadata, starting from 1 and incremented on each edit
+    pub metadata_version: u32,
+    /// Whether the metadata has been permanently frozen by the manager
+    pub metadata_locked: bool,
+    /// Id of the series the tea belongs to, if any
+    pub series: Option<u64>,
+    /// The weight the tea contributes to its holders' reputation scores, if set by the manager
+    pub weight: Option<Weight>,
+    /// The dedicated collection the tea's instances are minted on, if any. Otherwise they are
+    /// minted on the Hub's NFT contract.
+    pub nft: Option<String>,
 }
 
+/// NOTE: The registry of verified issuers, the flags, the metadata history, the series and the
+/// weights are not known from the tea alone, so the corresponding fields are left at their
+/// defaults here, to be filled in by the Hub.
 impl From<(u64, Tea)> for TeaResponse {
     fn from(item: (u64, Tea)) -> Self {
         let (id, tea) = item;
@@ -197,13 +717,51 @@ impl From<(u64, Tea)> for TeaResponse {
             expiry: tea.expiry,
             max_supply: tea.max_supply,
             current_supply: tea.current_supply,
+            reveal: tea.reveal,
+            issuer_verified: false,
+            flag_reason: None,
+            metadata_version: 1,
+            metadata_locked: false,
+            series: None,
+            weight: None,
+            nft: tea.nft.map(String::from),
         }
     }
 }
 
 #[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
 pub struct AllTeaResponse {
-    pub tea: Vec<TeaResponse>
+    pub tea: Vec<TeaResponse>,
+    /// Only returned by `AllTea`: the id to pass as `start_after` to continue enumerating, or None
+    /// if all tea have been gone through. With a status filter, a page can hold fewer tea than
+    /// `limit`, or none at all, even though more tea match further on.
+    pub next: Option<u64>,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct SeriesResponse {
+    pub id: u64,
+    pub manager: String,
+    pub metadata: Metadata,
+    /// Ids of the tea in the series, in the order they were added
+    pub tea: Vec<u64>,
+}
+
+impl From<(u64, Series)> for SeriesResponse {
+    fn from(item: (u64, Series)) -> Self {
+        let (id, series) = item;
+        SeriesResponse {
+            id,
+            manager: series.manager.into(),
+            metadata: series.metadata,
+            tea: series.tea,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct AllSeriesResponse {
+    pub series: Vec<SeriesResponse>,
 }
 
 #[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
@@ -223,7 +781,188 @@ pub struct OwnerResponse {
     pub claimed: bool,
 }
 
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct ScoreResponse {
+    pub user: String,
+    /// The category the score is within, or None if across all categories
+    pub category: Option<String>,
+    pub score: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct LeaderboardResponse {
+    pub scores: Vec<ScoreResponse>,
+}
+
 #[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
 pub struct OwnersResponse {
     pub owners: Vec<String>,
 }
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct ClaimedByResponse {
+    pub user: String,
+    pub ids: Vec<u64>,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct CanMintResponse {
+    pub ok: bool,
+    /// If the tea cannot be minted, a machine-readable code of the error that minting would fail
+    /// with, e.g. `expired`, `sold_out` or `already_claimed`, or `not_found` if the tea doesn't
+    /// exist
+    pub reason: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct StatsResponse {
+    pub tea_created: u64,
+    pub tea_minted: u64,
+    pub fees_collected: Vec<Coin>,
+    pub keys_purged: u64,
+    pub owners_purged: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct ActiveTeaCountResponse {
+    /// Number of active tea in this page
+    pub count: u64,
+    /// Cursor from which to count the next page, as {expiry, tea_id}, or None if all active tea
+    /// have been counted
+    pub next: Option<(u64, u64)>,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct TeaStatsResponse {
+    pub id: u64,
+    /// Number of instances minted, by the kind of mint rule under which they were minted
+    pub mints: BTreeMap<String, u64>,
+    pub keys_remaining: u64,
+    pub owners: u64,
+    pub burns: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct DepositResponse {
+    pub id: u64,
+    pub metadata_bytes: u64,
+    pub metadata_deposit: Vec<Coin>,
+    pub key_bytes: u64,
+    pub key_deposit: Vec<Coin>,
+    pub bounty_credit: Uint128,
+}
+
+impl From<(u64, Deposit)> for DepositResponse {
+    fn from(item: (u64, Deposit)) -> Self {
+        let (id, deposit) = item;
+        DepositResponse {
+            id,
+            metadata_bytes: deposit.metadata_bytes,
+            metadata_deposit: deposit.metadata_deposit,
+            key_bytes: deposit.key_bytes,
+            key_deposit: deposit.key_deposit,
+            bounty_credit: deposit.bounty_credit,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct FeeMultiplierResponse {
+    pub address: String,
+    pub multiplier: Decimal,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct FeeMultipliersResponse {
+    pub multipliers: Vec<FeeMultiplierResponse>,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct EstimateFeeResponse {
+    /// The fee required in each of the accepted denoms, uthiol first. Any one of them may be paid
+    pub fees: Vec<Coin>,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct IssuerResponse {
+    pub address: String,
+    pub verified: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct IssuersResponse {
+    pub issuers: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct FlagResponse {
+    pub id: u64,
+    pub reason: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct FlaggedTeaResponse {
+    pub tea: Vec<FlagResponse>,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct MetadataVersionResponse {
+    pub version: u32,
+    /// Hex-encoded SHA256 hash of the JSON-serialized metadata
+    pub hash: String,
+    /// The full metadata, if it was retained when replaced
+    pub metadata: Option<Metadata>,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct MetadataHistoryResponse {
+    pub id: u64,
+    pub current_version: u32,
+    pub locked: bool,
+    pub versions: Vec<MetadataVersionResponse>,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct UpgradeResponse {
+    /// Semver requirement the contract version must satisfy for this upgrade to be applied
+    pub from: String,
+    /// The contract version once this upgrade has been applied
+    pub to: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct PendingUpgradesResponse {
+    pub current_version: String,
+    pub target_version: String,
+    pub upgrades: Vec<UpgradeResponse>,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct HookResponse {
+    pub contract: String,
+    /// Whether a failure of the hook fails the whole transaction
+    pub fatal: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct HooksResponse {
+    pub hooks: Vec<HookResponse>,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct PreviousNftResponse {
+    pub nft: String,
+    /// The timestamp at which the contract was replaced
+    pub replaced_at: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct PreviousNftsResponse {
+    pub nfts: Vec<PreviousNftResponse>,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct CollectionResponse {
+    pub nft: String,
+    pub organizer: String,
+}
@@ -2,10 +2,13 @@
 
 mod tea;
 mod fee;
+pub mod hook;
 pub mod hub;
 mod mint_rule;
 pub mod nft;
+mod series;
 
-pub use tea::Tea;
-pub use fee::FeeRate;
+pub use tea::{MetadataVersion, Reveal, Tea, Weight};
+pub use fee::{BountyRate, Deposit, FeeRate};
 pub use mint_rule::MintRule;
+pub use series::Series;
@@ -28,6 +28,16 @@ pub enum MintRule {
     /// `clear_keys` method to remove unused keys from the contract storage, thereby reducing the
     /// size of the chain's state.
     ByKeys,
+
+    /// Tea's can be minted by holders of other tea, who burn the required number of instances of
+    /// each, e.g. five meetup badges to forge a gold badge. `inputs` lists the ids of the tea to be
+    /// burned, each with the number of instances required.
+    ///
+    /// The holder must approve the Hub as an operator of their tokens at the NFT contract, so that
+    /// it can burn them, and then invoke the `mint_by_burn` method.
+    ByBurn {
+        inputs: Vec<(u64, u64)>,
+    },
 }
 
 impl fmt::Display for MintRule {
@@ -36,12 +46,34 @@ impl fmt::Display for MintRule {
             MintRule::ByMinter(minter) => format!("by_minter:{}", minter),
             MintRule::ByKey(pubkey) => format!("by_key:{}", pubkey),
             MintRule::ByKeys => "by_keys".to_string(),
+            MintRule::ByBurn {
+                inputs,
+            } => {
+                let inputs = inputs
+                    .iter()
+                    .map(|(id, count)| format!("{}x{}", id, count))
+                    .collect::<Vec<_>>()
+                    .join(",");
+                format!("by_burn:{}", inputs)
+            },
         };
         write!(f, "{}", s)
     }
 }
 
 impl MintRule {
+    /// Name of the rule without its parameters, e.g. `by_minter` for `ByMinter("larry")`
+    pub fn kind(&self) -> &'static str {
+        match self {
+            MintRule::ByMinter(_) => "by_minter",
+            MintRule::ByKey(_) => "by_key",
+            MintRule::ByKeys => "by_keys",
+            MintRule::ByBurn {
+                ..
+            } => "by_burn",
+        }
+    }
+
     pub fn by_minter(minter: impl Into<String>) -> Self {
         MintRule::ByMinter(minter.into())
     }
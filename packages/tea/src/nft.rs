@@ -14,6 +14,22 @@ pub struct InstantiateMsg {
     pub api_url: String,
     /// SG-721 collection info
     pub collection_info: terp721::CollectionInfo<terp721::ResidualInfoResponse>,
+    /// Name of the collection. Defaults to that of the Hub's shared collection; dedicated
+    /// collections of a single organizer set their own.
+    #[serde(default)]
+    pub name: Option<String>,
+    /// Symbol of the collection. Defaults to that of the Hub's shared collection.
+    #[serde(default)]
+    pub symbol: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct MigrateMsg {
+    /// If provided, replace the URL of the API that serves the Tea's metadata
+    pub api_url: Option<String>,
+    /// If provided, replace the address of the Tea Hub contract, which is also the NFT's minter.
+    /// The new Hub is also where the NFT contract queries each Tea's metadata from.
+    pub hub: Option<String>,
 }
 
 // message types
@@ -0,0 +1,18 @@
+use cosmwasm_std::Addr;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use terp_metadata::Metadata;
+
+/// A group of related tea, e.g. the tracks of a conference, the modules of a course or the quests
+/// of a season
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct Series {
+    /// Account who has the authority to add tea to the series
+    pub manager: Addr,
+
+    /// The series' metadata
+    pub metadata: Metadata,
+
+    /// Ids of the tea in the series, in the order they were added
+    pub tea: Vec<u64>,
+}
@@ -6,12 +6,13 @@ use terp_metadata::Metadata;
 use crate::MintRule;
 
 #[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
-pub struct Tea {
+pub struct Tea<M = Metadata> {
     /// Account who has the authority to edit the tea's info.
     pub manager: Addr,
 
-    /// The tea's metadata
-    pub metadata: Metadata,
+    /// The tea's metadata. In the Hub's storage, the hex-encoded SHA256 hash of the metadata,
+    /// which is stored separately so that tea sharing the same metadata only store it once.
+    pub metadata: M,
 
     /// Whether this tea is transferrable
     pub transferrable: bool,
@@ -31,4 +32,68 @@ pub struct Tea {
     /// of tokens that can ever be minted. A user burning their tokens does not make room for new
     /// tokens to be minted.
     pub current_supply: u64,
+
+    /// If the tea's metadata is to be revealed at a later time, the placeholder served until then
+    /// and the commitment to the final metadata. None once revealed, or for tea that are not
+    /// revealed later.
+    #[serde(default)]
+    pub reveal: Option<Reveal>,
+
+    /// The dedicated collection the tea's instances are minted on. None for tea minted on the
+    /// Hub's shared NFT contract.
+    #[serde(default)]
+    pub nft: Option<Addr>,
+}
+
+impl<M> Tea<M> {
+    /// The same tea, with its metadata replaced by `metadata`
+    pub fn with_metadata<N>(self, metadata: N) -> Tea<N> {
+        Tea {
+            manager: self.manager,
+            metadata,
+            transferrable: self.transferrable,
+            rule: self.rule,
+            expiry: self.expiry,
+            max_supply: self.max_supply,
+            current_supply: self.current_supply,
+            reveal: self.reveal,
+            nft: self.nft,
+        }
+    }
+}
+
+/// A scheduled reveal of a tea's metadata, e.g. for surprise badges whose artwork should not be
+/// known before they are earned. Until revealed, the tea's own metadata is left empty.
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct Reveal {
+    /// Metadata served in place of the tea's until it is revealed
+    pub placeholder: Metadata,
+
+    /// The timestamp from which the final metadata can be revealed
+    pub time: u64,
+
+    /// Hex-encoded SHA256 hash of the JSON-serialized final metadata
+    pub hash: String,
+}
+
+/// A version of a tea's metadata, recorded each time the metadata is set
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct MetadataVersion {
+    /// Hex-encoded SHA256 hash of the JSON-serialized metadata
+    pub hash: String,
+
+    /// The full metadata of this version, if the manager chose to retain it when replacing it.
+    /// Never set for the current version, which is the tea's metadata.
+    pub metadata: Option<Metadata>,
+}
+
+/// The weight a tea contributes to the reputation score of each holder of its instances, in total
+/// and within its category
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct Weight {
+    /// Points each instance of the tea is worth
+    pub weight: u64,
+
+    /// Category the points count towards, e.g. `governance` or `education`
+    pub category: String,
 }
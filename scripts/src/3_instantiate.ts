@@ -45,57 +45,34 @@ const args = yargs(hideBin(process.argv))
   const key = await keystore.load(args["key"], password, args["key-dir"]);
   const { senderAddr, client } = await helpers.createSigningClient(args["network"], key);
 
-  process.stdout.write("instantiating hub contract... ");
-  const { contractAddress: hubAddr, transactionHash: hubTxHash } = await client.instantiate(
+  // the hub instantiates the nft contract itself, and records its address in the same tx
+  process.stdout.write("instantiating hub and nft contracts... ");
+  const { contractAddress: hubAddr, transactionHash } = await client.instantiate(
     senderAddr,
     args["hub-code-id"],
     {
       fee_per_byte: "200000", // 0.2 STARS per byte
-    },
-    "tea-hub",
-    "auto",
-    {
-      admin: senderAddr,
-    }
-  );
-  console.log(`success! contract address: ${hubAddr}, txhash: ${hubTxHash}`);
-
-  process.stdout.write("instantiating nft contract... ");
-  const { contractAddress: nftAddr, transactionHash: nftTxHash } = await client.instantiate(
-    senderAddr,
-    args["nft-code-id"],
-    {
-      hub: hubAddr,
-      api_url: "https://api.tea.fun/metadata",
-      collection_info: {
-        creator: senderAddr,
-        description: "TEA is an NFT protocol that allows anyone to create digital tea",
-        image: "https://tea.fun/logo.png",
-        external_link: "https://tea.fun",
-        residual_info: {
-          payment_address: senderAddr,
-          share: "0.05",
+      nft: {
+        code_id: args["nft-code-id"],
+        api_url: "https://api.tea.fun/metadata",
+        collection_info: {
+          creator: senderAddr,
+          description: "TEA is an NFT protocol that allows anyone to create digital tea",
+          image: "https://tea.fun/logo.png",
+          external_link: "https://tea.fun",
+          residual_info: {
+            payment_address: senderAddr,
+            share: "0.05",
+          },
         },
       },
     },
-    "tea-nft",
+    "tea-hub",
     "auto",
     {
       admin: senderAddr,
     }
   );
-  console.log(`success! contract address: ${nftAddr}, txhash: ${nftTxHash}`);
-
-  process.stdout.write("setting nft address at the hub contract... ");
-  const { transactionHash } = await client.execute(
-    senderAddr,
-    hubAddr,
-    {
-      set_nft: {
-        nft: nftAddr,
-      },
-    },
-    "auto"
-  );
-  console.log(`success! txhash: ${transactionHash}`);
+  const { nft: nftAddr } = await client.queryContractSmart(hubAddr, { config: {} });
+  console.log(`success! hub address: ${hubAddr}, nft address: ${nftAddr}, txhash: ${transactionHash}`);
 })();